@@ -4,6 +4,7 @@ use std::sync::mpsc;
 
 use divan::{Divan, Bencher};
 
+use pathmap::concurrent_map::ConcurrentPathMap;
 use pathmap::trie_map::BytesTrieMap;
 use pathmap::zipper::*;
 
@@ -371,6 +372,64 @@ fn parallel_copy_traverse(bencher: Bencher, (elements, thread_cnt): (usize, &str
     drop(zipper_head);
 }
 
+// The following two benchmarks compare many small inserts to unrelated keys, with each thread writing keys
+// interleaved with the other threads' keys
+#[divan::bench(sample_size = 1, args = TEST_ARGS)]
+fn parallel_small_insert_zipper_head(bencher: Bencher, (elements, thread_cnt): (usize, &str)) {
+    let thread_cnt = usize::from_str_radix(thread_cnt, 10).unwrap();
+    let real_thread_cnt = thread_cnt.max(1);
+
+    //ZipperHeadOwned is Sync, so the threads share it directly, and create a zipper for each insert
+    let zipper_head = BytesTrieMap::<usize>::new().into_zipper_head(&[]);
+    let insert_fn = |n: usize| {
+        for i in (n..elements).step_by(real_thread_cnt) {
+            let mut zipper = zipper_head.write_zipper_at_exclusive_path((i as u64).to_le_bytes()).unwrap();
+            zipper.set_value(i);
+        }
+    };
+
+    bencher.bench_local(|| {
+        if thread_cnt > 0 {
+            thread::scope(|scope| {
+                for n in 0..thread_cnt {
+                    let insert_fn = &insert_fn;
+                    scope.spawn(move || insert_fn(n));
+                }
+            });
+        } else {
+            //No-thread case, to measure overhead of spawning vs. 1-thread case
+            insert_fn(0);
+        }
+    });
+}
+
+#[divan::bench(sample_size = 1, args = TEST_ARGS)]
+fn parallel_small_insert_concurrent_map(bencher: Bencher, (elements, thread_cnt): (usize, &str)) {
+    let thread_cnt = usize::from_str_radix(thread_cnt, 10).unwrap();
+    let real_thread_cnt = thread_cnt.max(1);
+
+    let map = ConcurrentPathMap::<usize>::new();
+    let insert_fn = |n: usize| {
+        for i in (n..elements).step_by(real_thread_cnt) {
+            map.insert((i as u64).to_le_bytes(), i);
+        }
+    };
+
+    bencher.bench_local(|| {
+        if thread_cnt > 0 {
+            thread::scope(|scope| {
+                for n in 0..thread_cnt {
+                    let insert_fn = &insert_fn;
+                    scope.spawn(move || insert_fn(n));
+                }
+            });
+        } else {
+            //No-thread case, to measure overhead of spawning vs. 1-thread case
+            insert_fn(0);
+        }
+    });
+}
+
 fn prefix_key(k: &u64) -> &[u8] {
     let bs = (8 - k.leading_zeros()/8) as u8;
     let kp: *const u64 = k;
@@ -0,0 +1,171 @@
+//! Derive macros for the [`Lattice`] and [`DistributiveLattice`] traits from the `pathmap` crate
+//!
+//! These macros are re-exported from `pathmap::ring` when the `derive` feature of `pathmap` is enabled,
+//! so you should rarely need to depend on this crate directly.
+//!
+//! The derived impls operate on each field of a struct (named or tuple) independently, and combine the
+//! results.  If every field result is an identity of the same argument then the whole struct is an identity
+//! of that argument, otherwise a new element is built from the field results.  A struct can't represent
+//! a missing field, so a `None` result from any field of a join or meet makes the result for the whole
+//! struct `None`.
+//!
+//! That meet doesn't distribute over join once a struct has more than one field, so no subtraction could
+//! satisfy the `DistributiveLattice` laws.  `DistributiveLattice` can therefore only be derived for
+//! structs with at most one field, where it subtracts the field.
+//!
+//! ```ignore
+//! use pathmap::ring::{Lattice, DistributiveLattice};
+//!
+//! #[derive(Clone, Lattice)]
+//! struct Tagged {
+//!     tags: std::collections::HashSet<String>,
+//!     seen: bool,
+//! }
+//!
+//! #[derive(Clone, Lattice, DistributiveLattice)]
+//! struct Tags(std::collections::HashSet<String>);
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Index, Member};
+
+/// Derives `pathmap::ring::Lattice` for a struct, by joining and meeting each field
+///
+/// Every field type must implement `Lattice` and `Clone`
+#[proc_macro_derive(Lattice)]
+pub fn derive_lattice(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_lattice(input).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+/// Derives `pathmap::ring::DistributiveLattice` for a struct with at most one field, by subtracting the field
+///
+/// The field type must implement `DistributiveLattice` and `Clone`
+#[proc_macro_derive(DistributiveLattice)]
+pub fn derive_distributive_lattice(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_distributive_lattice(input).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+fn expand_lattice(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = struct_fields(&input)?;
+    add_field_bounds(&mut input, &fields, quote!(::pathmap::ring::Lattice));
+
+    let join_body = op_body(&fields, quote!(::pathmap::ring::Lattice::pjoin));
+    let meet_body = op_body(&fields, quote!(::pathmap::ring::Lattice::pmeet));
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::pathmap::ring::Lattice for #ident #ty_generics #where_clause {
+            fn pjoin(&self, other: &Self) -> ::pathmap::ring::AlgebraicResult<Self> {
+                #join_body
+            }
+            fn pmeet(&self, other: &Self) -> ::pathmap::ring::AlgebraicResult<Self> {
+                #meet_body
+            }
+        }
+    })
+}
+
+fn expand_distributive_lattice(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = struct_fields(&input)?;
+    if fields.len() > 1 {
+        return Err(Error::new(input.ident.span(), "DistributiveLattice can only be derived for structs with at most one field"))
+    }
+    add_field_bounds(&mut input, &fields, quote!(::pathmap::ring::DistributiveLattice));
+
+    let subtract_body = match fields.first() {
+        Some(field) => {
+            let member = &field.member;
+            quote! {
+                ::pathmap::ring::DistributiveLattice::psubtract(&self.#member, &other.#member).map(|field| Self { #member: field })
+            }
+        },
+        None => quote!(::pathmap::ring::AlgebraicResult::None),
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::pathmap::ring::DistributiveLattice for #ident #ty_generics #where_clause {
+            fn psubtract(&self, other: &Self) -> ::pathmap::ring::AlgebraicResult<Self> {
+                #subtract_body
+            }
+        }
+    })
+}
+
+/// A field of the struct being derived
+struct StructField {
+    member: Member,
+    ty: syn::Type,
+}
+
+/// Returns the fields of the struct, or an error if the input isn't a struct
+fn struct_fields(input: &DeriveInput) -> syn::Result<Vec<StructField>> {
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        Data::Enum(data) => return Err(Error::new(data.enum_token.span, "Lattice traits can only be derived for structs")),
+        Data::Union(data) => return Err(Error::new(data.union_token.span, "Lattice traits can only be derived for structs")),
+    };
+    let fields = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().map(|field| StructField {
+            member: Member::Named(field.ident.clone().unwrap()),
+            ty: field.ty.clone(),
+        }).collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().enumerate().map(|(idx, field)| StructField {
+            member: Member::Unnamed(Index { index: idx as u32, span: Span::call_site() }),
+            ty: field.ty.clone(),
+        }).collect(),
+        Fields::Unit => vec![],
+    };
+    Ok(fields)
+}
+
+/// Adds a `FieldType: Trait + Clone` bound to the where clause for every field
+fn add_field_bounds(input: &mut DeriveInput, fields: &[StructField], trait_path: TokenStream2) {
+    let where_clause = input.generics.make_where_clause();
+    for field in fields {
+        let ty = &field.ty;
+        where_clause.predicates.push(parse_quote!(#ty: #trait_path + ::core::clone::Clone));
+    }
+}
+
+/// Emits the body of a join or meet method, combining the results from each field
+///
+/// A struct without fields behaves like `()`.  Every instance is identical, so join and meet are always
+/// mutual identities.  Otherwise a `None` field result annihilates the whole struct.
+fn op_body(fields: &[StructField], op_path: TokenStream2) -> TokenStream2 {
+    if fields.is_empty() {
+        return quote!(::pathmap::ring::AlgebraicResult::Identity(::pathmap::ring::SELF_IDENT | ::pathmap::ring::COUNTER_IDENT))
+    }
+
+    let result_idents: Vec<_> = (0..fields.len()).map(|idx| format_ident!("__field_result_{}", idx)).collect();
+    let field_results = fields.iter().zip(result_idents.iter()).map(|(field, result)| {
+        let member = &field.member;
+        quote! {
+            let #result = #op_path(&self.#member, &other.#member);
+            match &#result {
+                ::pathmap::ring::AlgebraicResult::None => return ::pathmap::ring::AlgebraicResult::None,
+                ::pathmap::ring::AlgebraicResult::Identity(field_mask) => __identity_mask &= *field_mask,
+                ::pathmap::ring::AlgebraicResult::Element(_) => __identity_mask = 0,
+            }
+        }
+    });
+    let field_inits = fields.iter().zip(result_idents.iter()).map(|(field, result)| {
+        let member = &field.member;
+        quote!(#member: #result.into_option([&self.#member, &other.#member]).unwrap())
+    });
+
+    quote! {
+        let mut __identity_mask: u64 = ::pathmap::ring::SELF_IDENT | ::pathmap::ring::COUNTER_IDENT;
+        #( #field_results )*
+        if __identity_mask > 0 {
+            return ::pathmap::ring::AlgebraicResult::Identity(__identity_mask)
+        }
+        ::pathmap::ring::AlgebraicResult::Element(Self { #( #field_inits, )* })
+    }
+}
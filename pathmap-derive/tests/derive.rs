@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use pathmap::ring::{AlgebraicResult, Lattice, DistributiveLattice, SortedVecSet, SELF_IDENT, COUNTER_IDENT};
+use pathmap::trie_map::BytesTrieMap;
+use pathmap::lattice_laws::{check_lattice_laws_for, check_distributive_lattice_laws_for};
+
+#[derive(Clone, Debug, PartialEq, Lattice)]
+struct Tagged {
+    tags: HashSet<&'static str>,
+    flag: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Lattice)]
+struct Range(i64, i64);
+
+#[derive(Clone, Debug, PartialEq, Lattice, DistributiveLattice)]
+struct Wrapper<T>(SortedVecSet<T>);
+
+#[derive(Clone, Debug, PartialEq, Lattice, DistributiveLattice)]
+struct Unit;
+
+fn tagged(tags: &[&'static str], flag: bool) -> Tagged {
+    Tagged { tags: tags.iter().cloned().collect(), flag }
+}
+
+#[test]
+fn derive_named_struct_test() {
+    let a = tagged(&["a", "b"], false);
+    let b = tagged(&["b", "c"], true);
+
+    assert_eq!(a.pjoin(&b), AlgebraicResult::Element(tagged(&["a", "b", "c"], true)));
+    assert_eq!(a.pmeet(&b), AlgebraicResult::Element(tagged(&["b"], false)));
+
+    //Every field is an identity of the same argument
+    let c = tagged(&["a", "b", "c"], true);
+    assert_eq!(c.pjoin(&a), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!(a.pmeet(&c), AlgebraicResult::Identity(SELF_IDENT));
+
+    //A `None` field result annihilates the whole struct
+    let d = tagged(&["z"], false);
+    assert_eq!(a.pmeet(&d), AlgebraicResult::None);
+
+    let values = [a, b, c, d, tagged(&["a"], true), tagged(&["c", "z"], false)];
+    for x in values.iter() {
+        for y in values.iter() {
+            for z in values.iter() {
+                check_lattice_laws_for(x, y, z).unwrap();
+            }
+        }
+    }
+}
+
+#[test]
+fn derive_tuple_struct_test() {
+    let a = Range(-3, 10);
+    let b = Range(5, 2);
+    assert_eq!(a.pjoin(&b), AlgebraicResult::Element(Range(5, 10)));
+    assert_eq!(a.pmeet(&b), AlgebraicResult::Element(Range(-3, 2)));
+    assert_eq!(a.pjoin(&a), AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT));
+    assert_eq!(Range(0, 0).pjoin(&Range(1, 1)), AlgebraicResult::Identity(COUNTER_IDENT));
+
+    let wrapper = |keys: &[u8]| Wrapper(SortedVecSet::from(keys.to_vec()));
+    let w = wrapper(&[1, 2, 3]);
+    assert_eq!(w.pmeet(&wrapper(&[2, 4])), AlgebraicResult::Element(wrapper(&[2])));
+    assert_eq!(w.psubtract(&wrapper(&[4])), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!(w.psubtract(&wrapper(&[1, 4])), AlgebraicResult::Element(wrapper(&[2, 3])));
+    assert_eq!(w.psubtract(&w), AlgebraicResult::None);
+
+    let values = [w, wrapper(&[2, 4]), wrapper(&[1]), wrapper(&[3, 4, 5]), wrapper(&[6])];
+    for x in values.iter() {
+        for y in values.iter() {
+            for z in values.iter() {
+                check_distributive_lattice_laws_for(x, y, z).unwrap();
+            }
+        }
+    }
+}
+
+#[test]
+fn derive_unit_struct_test() {
+    assert_eq!(Unit.pjoin(&Unit), AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT));
+    assert_eq!(Unit.pmeet(&Unit), AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT));
+    assert_eq!(Unit.psubtract(&Unit), AlgebraicResult::None);
+}
+
+#[test]
+fn derive_map_value_test() {
+    let mut a = BytesTrieMap::<Range>::new();
+    a.insert("x", Range(0, 1));
+    let mut b = BytesTrieMap::<Range>::new();
+    b.insert("x", Range(2, -1));
+    b.insert("y", Range(3, 3));
+
+    let joined = a.join(&b);
+    assert_eq!(joined.get("x"), Some(&Range(2, 1)));
+    assert_eq!(joined.get("y"), Some(&Range(3, 3)));
+    let meet = a.meet(&b);
+    assert_eq!(meet.get("x"), Some(&Range(0, -1)));
+    assert_eq!(meet.get("y"), None);
+}
@@ -0,0 +1,191 @@
+use std::sync::RwLock;
+
+use crate::{Allocator, GlobalAlloc, global_alloc};
+use crate::parallel::{branch_map, graft_branch_map};
+use crate::trie_map::BytesTrieMap;
+use crate::zipper::*;
+
+/// A map that many threads can insert into, read, and remove from at the same time
+///
+/// The contents are split into 256 shards according to the first byte of each path, and each shard has its
+/// own lock.  Operations on paths that start with different bytes never wait for each other, and no
+/// zippers or [ZipperHead]s need to be coordinated.  The shard for each path only holds the remainder of
+/// the path, after the first byte.
+///
+/// For anything beyond point access, [to_map](ConcurrentPathMap::to_map) or [into_map](ConcurrentPathMap::into_map)
+/// combine the shards back into an ordinary [BytesTrieMap].
+///
+/// ```
+/// # use pathmap::concurrent_map::ConcurrentPathMap;
+/// let map = ConcurrentPathMap::new();
+/// std::thread::scope(|scope| {
+///     for n in 0..4u8 {
+///         let map = &map;
+///         scope.spawn(move || map.insert([n, b':'], n));
+///     }
+/// });
+/// assert_eq!(map.get([2, b':']), Some(2));
+/// assert_eq!(map.into_map().val_count(), 4);
+/// ```
+pub struct ConcurrentPathMap<V: Clone + Send + Sync, A: Allocator = GlobalAlloc> {
+    root_val: RwLock<Option<V>>,
+    shards: Box<[RwLock<BytesTrieMap<V, A>>]>,
+    alloc: A,
+}
+
+impl<V: Clone + Send + Sync + Unpin> Default for ConcurrentPathMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, A: Allocator> From<BytesTrieMap<V, A>> for ConcurrentPathMap<V, A> {
+    fn from(map: BytesTrieMap<V, A>) -> Self {
+        let new_map = Self::new_in(map.alloc.clone());
+        *new_map.root_val.write().unwrap() = map.read_zipper().get_value().cloned();
+        for byte in map.read_zipper().child_mask().iter() {
+            *new_map.shards[byte as usize].write().unwrap() = branch_map(&map, byte);
+        }
+        new_map
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin> ConcurrentPathMap<V> {
+    /// Creates a new empty map
+    pub fn new() -> Self {
+        Self::new_in(global_alloc())
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, A: Allocator> ConcurrentPathMap<V, A> {
+    /// Creates a new empty map in the specified allocator
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            root_val: RwLock::new(None),
+            shards: (0..256).map(|_| RwLock::new(BytesTrieMap::new_in(alloc.clone()))).collect(),
+            alloc,
+        }
+    }
+    /// Inserts `v` at path `k`, and returns the value that was replaced, if any
+    pub fn insert<K: AsRef<[u8]>>(&self, k: K, v: V) -> Option<V> {
+        match k.as_ref().split_first() {
+            Some((byte, rest)) => self.shards[*byte as usize].write().unwrap().insert(rest, v),
+            None => self.root_val.write().unwrap().replace(v),
+        }
+    }
+    /// Returns a clone of the value at `k`, or `None` if there is no value at `k`
+    pub fn get<K: AsRef<[u8]>>(&self, k: K) -> Option<V> {
+        match k.as_ref().split_first() {
+            Some((byte, rest)) => self.shards[*byte as usize].read().unwrap().get(rest).cloned(),
+            None => self.root_val.read().unwrap().clone(),
+        }
+    }
+    /// Returns `true` if there is a value at `k`, otherwise returns `false`
+    pub fn contains<K: AsRef<[u8]>>(&self, k: K) -> bool {
+        match k.as_ref().split_first() {
+            Some((byte, rest)) => self.shards[*byte as usize].read().unwrap().contains(rest),
+            None => self.root_val.read().unwrap().is_some(),
+        }
+    }
+    /// Removes the value at `k` and returns it, or returns `None` if there was no value at `k`
+    pub fn remove<K: AsRef<[u8]>>(&self, k: K) -> Option<V> {
+        match k.as_ref().split_first() {
+            Some((byte, rest)) => self.shards[*byte as usize].write().unwrap().remove(rest),
+            None => self.root_val.write().unwrap().take(),
+        }
+    }
+    /// Returns `true` if the map is empty, otherwise returns `false`
+    pub fn is_empty(&self) -> bool {
+        self.root_val.read().unwrap().is_none() &&
+            self.shards.iter().all(|shard| shard.read().unwrap().is_empty())
+    }
+    /// Returns the total number of values contained within the map, not counting a value at the root, the
+    /// same as [BytesTrieMap::val_count]
+    ///
+    /// WARNING: This is not a cheap method. It may have an order-N cost
+    pub fn val_count(&self) -> usize {
+        //The root value of each shard is the value at the shard's one-byte path, so it is counted
+        self.shards.iter().fold(0, |cnt, shard| {
+            let shard = shard.read().unwrap();
+            cnt + shard.val_count() + shard.read_zipper().get_value().is_some() as usize
+        })
+    }
+    /// Combines the shards into a new [BytesTrieMap], which shares its nodes with the shards
+    ///
+    /// The shards are locked one at a time, so changes made by other threads while this method runs may
+    /// be only partially reflected in the result.
+    pub fn to_map(&self) -> BytesTrieMap<V, A> {
+        let root_val = self.root_val.read().unwrap().clone();
+        let branches = self.shards.iter().map(|shard| shard.read().unwrap().clone());
+        Self::assemble_map(root_val, branches, self.alloc.clone())
+    }
+    /// Consumes the `ConcurrentPathMap`, and combines the shards into a [BytesTrieMap]
+    pub fn into_map(self) -> BytesTrieMap<V, A> {
+        let root_val = self.root_val.into_inner().unwrap();
+        let branches = self.shards.into_vec().into_iter().map(|shard| shard.into_inner().unwrap());
+        Self::assemble_map(root_val, branches, self.alloc)
+    }
+    /// Internal method to graft one branch per leading byte below a new root
+    fn assemble_map<I: Iterator<Item=BytesTrieMap<V, A>>>(root_val: Option<V>, branches: I, alloc: A) -> BytesTrieMap<V, A> {
+        let mut map = BytesTrieMap::new_in(alloc);
+        if let Some(val) = root_val {
+            map.insert(b"", val);
+        }
+        for (byte, branch) in branches.enumerate() {
+            graft_branch_map(&mut map, byte as u8, branch);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use super::*;
+
+    #[test]
+    fn concurrent_map_test() {
+        const THREADS: usize = 8;
+        const ELEMENTS: usize = 10000;
+        let map = ConcurrentPathMap::<usize>::new();
+        assert!(map.is_empty());
+
+        //Every thread writes keys spread across all of the shards
+        thread::scope(|scope| {
+            for n in 0..THREADS {
+                let map = &map;
+                scope.spawn(move || {
+                    for i in (n..ELEMENTS).step_by(THREADS) {
+                        assert_eq!(map.insert((i as u32).to_le_bytes(), i), None);
+                    }
+                    for i in (n..ELEMENTS).step_by(THREADS * 2) {
+                        assert_eq!(map.remove((i as u32).to_le_bytes()), Some(i));
+                    }
+                });
+            }
+        });
+        map.insert(b"", 0);
+        map.insert([7], 7);
+        assert_eq!(map.get(b""), Some(0));
+        assert!(map.contains([7]));
+        assert_eq!(map.get(8u32.to_le_bytes()), Some(8));
+        assert_eq!(map.get(0u32.to_le_bytes()), None);
+        assert_eq!(map.val_count(), ELEMENTS / 2 + 1);
+
+        let merged = map.to_map();
+        assert_eq!(merged.val_count(), map.val_count());
+        assert_eq!(merged.get(b""), Some(&0));
+        assert_eq!(merged.get([7]), Some(&7));
+        for i in 0..ELEMENTS {
+            let expected = (i % (THREADS * 2) >= THREADS).then_some(i);
+            assert_eq!(merged.get((i as u32).to_le_bytes()).copied(), expected);
+        }
+
+        //Splitting a map into shards and combining it again gives back the same contents
+        let round_trip = ConcurrentPathMap::from(merged.clone()).into_map();
+        assert_eq!(round_trip.iter().collect::<Vec<_>>(), merged.iter().collect::<Vec<_>>());
+        assert_eq!(round_trip.get(b""), Some(&0));
+        assert_eq!(map.remove(b""), Some(0));
+        assert_eq!(map.into_map().get(b""), None);
+    }
+}
@@ -0,0 +1,435 @@
+use core::marker::PhantomData;
+
+use crate::utils::{BitMask, ByteMask};
+use crate::trie_map::BytesTrieMap;
+use crate::trie_node::{AbstractNodeRef, TrieNode};
+use crate::zipper::*;
+use crate::zipper::zipper_priv::{ZipperPriv, FocusAddr};
+
+/// Decides which branches are visible through a [FilterZipper]
+pub trait BranchFilter<V> {
+    /// Returns the bytes that may be visible for the child branches of a focus `depth` bytes below the
+    /// zipper's root
+    fn depth_mask(&self, depth: usize) -> ByteMask;
+
+    /// Returns `true` if [is_visible](BranchFilter::is_visible) must be consulted, or `false` if the
+    /// `depth_mask` alone decides visibility
+    fn inspects_branches(&self) -> bool { true }
+
+    /// Returns `true` if the branch ending at `path` (relative to the zipper's root) should be visible
+    ///
+    /// `value` is the value at `path`.  This method is called for every path along a branch, including
+    /// paths without values, and returning `false` hides the entire subtrie below `path`.
+    fn is_visible(&self, path: &[u8], value: Option<&V>) -> bool;
+}
+
+/// Filters by depth.  The mask at index `n` specifies the bytes visible at the `n`th byte of the path, and
+/// all bytes are visible at depths beyond the end of the `Vec`
+impl<V> BranchFilter<V> for Vec<ByteMask> {
+    fn depth_mask(&self, depth: usize) -> ByteMask {
+        self.get(depth).copied().unwrap_or(ByteMask::FULL)
+    }
+    fn inspects_branches(&self) -> bool { false }
+    fn is_visible(&self, _path: &[u8], _value: Option<&V>) -> bool { true }
+}
+
+/// A [BranchFilter] that calls a predicate `(path, value) -> bool` to decide whether each branch is visible
+#[derive(Clone)]
+pub struct PathPredicate<F>(pub F);
+
+impl<V, F: Fn(&[u8], Option<&V>) -> bool> BranchFilter<V> for PathPredicate<F> {
+    fn depth_mask(&self, _depth: usize) -> ByteMask { ByteMask::FULL }
+    fn is_visible(&self, path: &[u8], value: Option<&V>) -> bool { (self.0)(path, value) }
+}
+
+/// A virtual [Zipper] that hides some branches of an underlying zipper, as decided by a [BranchFilter]
+///
+/// Hidden branches behave as if they don't exist.  The `FilterZipper` doesn't prune paths that lead only
+/// to hidden branches, so a visible path may exist without any values below it.
+///
+/// The `FilterZipper` implements [Catamorphism](crate::morphisms::Catamorphism), and may be used as the
+/// source for [ZipperWriting::graft] and the algebraic operations.  The visible subtrie below the focus is
+/// materialized for each such operation, which has a cost proportional to the size of the subtrie.
+///
+/// A `FilterZipper` can be a secondary factor of a [ProductZipper] by way of
+/// [ProductZipper::new_virtual_factors].
+pub struct FilterZipper<V, Z, F> {
+    z: Z,
+    filter: F,
+    /// The path length at which the focus entered a hidden branch, or `None` if the focus is visible
+    hidden_from: Option<usize>,
+    /// The visible children of the focus
+    mask: ByteMask,
+    _v: PhantomData<V>,
+}
+
+impl<V, Z: ZipperMoving + ZipperValues<V>> FilterZipper<V, Z, Vec<ByteMask>> {
+    /// Creates a new `FilterZipper` where the mask at index `n` of `masks` specifies the bytes visible at
+    /// the `n`th byte of the path
+    pub fn new_with_masks(z: Z, masks: Vec<ByteMask>) -> Self {
+        Self::new(z, masks)
+    }
+}
+
+impl<V, Z: ZipperMoving + ZipperValues<V>, F: Fn(&[u8], Option<&V>) -> bool> FilterZipper<V, Z, PathPredicate<F>> {
+    /// Creates a new `FilterZipper` where `predicate(path, value)` decides whether each branch is visible
+    ///
+    /// See [BranchFilter::is_visible] for the arguments and behavior of the predicate
+    pub fn new_with_predicate(z: Z, predicate: F) -> Self {
+        Self::new(z, PathPredicate(predicate))
+    }
+}
+
+impl<V, Z: ZipperMoving + ZipperValues<V>, F: BranchFilter<V>> FilterZipper<V, Z, F> {
+    /// Creates a new `FilterZipper` from a zipper and a [BranchFilter]
+    ///
+    /// The zipper is reset to its root, which becomes the root of the `FilterZipper`
+    pub fn new(mut z: Z, filter: F) -> Self {
+        z.reset();
+        let mut new_zipper = Self { z, filter, hidden_from: None, mask: ByteMask::EMPTY, _v: PhantomData };
+        new_zipper.refresh_mask();
+        new_zipper
+    }
+    /// Consumes the `FilterZipper`, and returns the underlying zipper
+    pub fn into_inner(self) -> Z {
+        self.z
+    }
+    /// Descends the underlying zipper one byte, and applies the filter, without refreshing the child mask
+    fn descend_byte_internal(&mut self, byte: u8) -> bool {
+        let depth = self.z.path().len();
+        let exists = self.z.descend_to_byte(byte);
+        if self.hidden_from.is_none() {
+            let visible = self.filter.depth_mask(depth).test_bit(byte) &&
+                (!self.filter.inspects_branches() || self.filter.is_visible(self.z.path(), self.z.value()));
+            if !visible {
+                self.hidden_from = Some(depth + 1);
+            }
+        }
+        exists && self.hidden_from.is_none()
+    }
+    /// Must be called after the zipper ascends, to exit the hidden branch if the focus is above it
+    fn ascended(&mut self) {
+        if let Some(hidden_from) = self.hidden_from {
+            if self.z.path().len() < hidden_from {
+                self.hidden_from = None;
+            }
+        }
+        self.refresh_mask();
+    }
+    /// Recomputes the visible children of the focus
+    fn refresh_mask(&mut self) {
+        if self.hidden_from.is_some() {
+            self.mask = ByteMask::EMPTY;
+            return
+        }
+        let mut mask = self.z.child_mask() & self.filter.depth_mask(self.z.path().len());
+        if self.filter.inspects_branches() {
+            let candidates = mask;
+            for byte in candidates.iter() {
+                self.z.descend_to_byte(byte);
+                if !self.filter.is_visible(self.z.path(), self.z.value()) {
+                    mask.clear_bit(byte);
+                }
+                self.z.ascend_byte();
+            }
+        }
+        self.mask = mask;
+    }
+    /// Calls `visit_f` for every visible value below the focus of `fork`, with the path relative to the fork's root
+    ///
+    /// `path` must contain the path from the `FilterZipper` root to the fork's focus, and `base_len` is the
+    /// length of the path to the fork's root
+    fn for_each_visible_value<Y, VisitF>(&self, fork: &mut Y, path: &mut Vec<u8>, base_len: usize, visit_f: &mut VisitF)
+        where Y: ZipperMoving + ZipperValues<V>, VisitF: FnMut(&[u8], &V)
+    {
+        let mask = fork.child_mask() & self.filter.depth_mask(path.len());
+        for byte in mask.iter() {
+            fork.descend_to_byte(byte);
+            path.push(byte);
+            if !self.filter.inspects_branches() || self.filter.is_visible(path, fork.value()) {
+                if let Some(val) = fork.value() {
+                    visit_f(&path[base_len..], val);
+                }
+                self.for_each_visible_value(fork, path, base_len, visit_f);
+            }
+            path.pop();
+            fork.ascend_byte();
+        }
+    }
+}
+
+impl<V, Z: ZipperMoving + ZipperValues<V>, F: BranchFilter<V>> Zipper for FilterZipper<V, Z, F> {
+    fn path_exists(&self) -> bool { self.hidden_from.is_none() && self.z.path_exists() }
+    fn is_value(&self) -> bool { self.hidden_from.is_none() && self.z.is_value() }
+    fn child_count(&self) -> usize { self.mask.count_bits() }
+    fn child_mask(&self) -> ByteMask { self.mask }
+}
+
+impl<V, Z: ZipperMoving + ZipperValues<V>, F: BranchFilter<V>> ZipperValues<V> for FilterZipper<V, Z, F> {
+    fn value(&self) -> Option<&V> {
+        match self.hidden_from {
+            None => self.z.value(),
+            Some(_) => None
+        }
+    }
+}
+
+impl<'a, V, Z: ZipperMoving + ZipperReadOnlyValues<'a, V>, F: BranchFilter<V>> ZipperReadOnlyValues<'a, V> for FilterZipper<V, Z, F> {
+    fn get_value(&self) -> Option<&'a V> {
+        match self.hidden_from {
+            None => self.z.get_value(),
+            Some(_) => None
+        }
+    }
+}
+
+impl<V, Z: ZipperMoving + ZipperValues<V> + ZipperForking<V>, F: BranchFilter<V>> ZipperMoving for FilterZipper<V, Z, F> {
+    fn at_root(&self) -> bool { self.z.at_root() }
+    fn reset(&mut self) {
+        self.z.reset();
+        self.hidden_from = None;
+        self.refresh_mask();
+    }
+    fn path(&self) -> &[u8] { self.z.path() }
+    fn val_count(&self) -> usize {
+        if self.hidden_from.is_some() {
+            return 0
+        }
+        let mut count = if self.z.is_value() { 1 } else { 0 };
+        let mut fork = self.z.fork_read_zipper();
+        let mut path = self.z.path().to_vec();
+        let base_len = path.len();
+        self.for_each_visible_value(&mut fork, &mut path, base_len, &mut |_, _| count += 1);
+        count
+    }
+    fn descend_to<K: AsRef<[u8]>>(&mut self, k: K) -> bool {
+        for &byte in k.as_ref() {
+            self.descend_byte_internal(byte);
+        }
+        self.refresh_mask();
+        self.path_exists()
+    }
+    fn descend_to_byte(&mut self, k: u8) -> bool {
+        let exists = self.descend_byte_internal(k);
+        self.refresh_mask();
+        exists
+    }
+    fn ascend(&mut self, steps: usize) -> bool {
+        let ascended = self.z.ascend(steps);
+        self.ascended();
+        ascended
+    }
+    fn ascend_byte(&mut self) -> bool {
+        let ascended = self.z.ascend_byte();
+        self.ascended();
+        ascended
+    }
+    fn ascend_until(&mut self) -> bool {
+        if self.at_root() {
+            return false
+        }
+        loop {
+            self.ascend_byte();
+            if self.child_count() > 1 || self.is_value() || self.at_root() {
+                return true
+            }
+        }
+    }
+    fn ascend_until_branch(&mut self) -> bool {
+        if self.at_root() {
+            return false
+        }
+        loop {
+            self.ascend_byte();
+            if self.child_count() > 1 || self.at_root() {
+                return true
+            }
+        }
+    }
+}
+
+impl<V, Z: ZipperMoving + ZipperValues<V> + ZipperForking<V>, F: BranchFilter<V>> ZipperIteration for FilterZipper<V, Z, F> { }
+
+impl<V, Z: ZipperAbsolutePath + ZipperValues<V> + ZipperForking<V>, F: BranchFilter<V>> ZipperAbsolutePath for FilterZipper<V, Z, F> {
+    fn origin_path(&self) -> &[u8] { self.z.origin_path() }
+    fn root_prefix_path(&self) -> &[u8] { self.z.root_prefix_path() }
+}
+
+impl<V, Z: ZipperPathBuffer + ZipperValues<V> + ZipperForking<V>, F: BranchFilter<V>> ZipperPathBuffer for FilterZipper<V, Z, F> {
+    unsafe fn origin_path_assert_len(&self, len: usize) -> &[u8] { unsafe{ self.z.origin_path_assert_len(len) } }
+    fn prepare_buffers(&mut self) { self.z.prepare_buffers() }
+    fn reserve_buffers(&mut self, path_len: usize, stack_depth: usize) { self.z.reserve_buffers(path_len, stack_depth) }
+}
+
+/// The visible subtrie may differ from the underlying subtrie, so a `FilterZipper` never reports a
+/// shared focus.  This disables caching, which would be incorrect across differently filtered locations.
+impl<V, Z, F> ZipperConcrete for FilterZipper<V, Z, F> {
+    fn is_shared(&self) -> bool { false }
+}
+
+impl<V, Z, F> ZipperConcretePriv for FilterZipper<V, Z, F> {
+    fn shared_addr(&self) -> Option<FocusAddr> { None }
+}
+
+/// The filtered subtrie doesn't exist as nodes in any trie, so `make_map` visits every visible value below
+/// the focus and inserts it into a new map.  The cost is proportional to the size of the underlying subtrie.
+impl<V: Clone + Send + Sync + Unpin, Z: ZipperMoving + ZipperValues<V> + ZipperForking<V>, F: BranchFilter<V>> ZipperSubtries<V> for FilterZipper<V, Z, F> {
+    fn make_map(&self) -> Option<BytesTrieMap<V>> {
+        if self.hidden_from.is_some() {
+            return None
+        }
+        let mut map = BytesTrieMap::new();
+        let mut fork = self.z.fork_read_zipper();
+        let mut path = self.z.path().to_vec();
+        let base_len = path.len();
+        self.for_each_visible_value(&mut fork, &mut path, base_len, &mut |rel_path, val| { map.insert(rel_path, val.clone()); });
+        if map.is_empty() {
+            None
+        } else {
+            Some(map)
+        }
+    }
+}
+
+/// `get_focus` builds a new trie with [make_map](ZipperSubtries::make_map) on every call, so each call costs
+/// as much as a full traversal of the underlying subtrie.  Avoid calling it repeatedly at the same focus.
+impl<V: Clone + Send + Sync + Unpin, Z: ZipperMoving + ZipperValues<V> + ZipperForking<V>, F: BranchFilter<V>> ZipperPriv for FilterZipper<V, Z, F> {
+    type V = V;
+    type A = crate::GlobalAlloc;
+    fn get_focus(&self) -> AbstractNodeRef<'_, V, crate::GlobalAlloc> {
+        match self.make_map().and_then(|map| map.into_root().0) {
+            Some(root) => AbstractNodeRef::OwnedRc(root),
+            None => AbstractNodeRef::None,
+        }
+    }
+    fn try_borrow_focus(&self) -> Option<&dyn TrieNode<V, crate::GlobalAlloc>> {
+        //The filtered subtrie doesn't exist as a node in the underlying trie
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{BitMask, ByteMask};
+    use crate::trie_map::BytesTrieMap;
+    use crate::morphisms::Catamorphism;
+    use crate::zipper::*;
+
+    fn test_map() -> BytesTrieMap<usize> {
+        BytesTrieMap::from_iter([("Apple", 1), ("apple", 2), ("apricot", 3), ("Banana", 4), ("banana", 5), ("cherry", 6)])
+    }
+
+    #[test]
+    fn filter_zipper_mask_test() {
+        let map = test_map();
+        let lowercase: ByteMask = (b'a'..=b'z').collect();
+        let mut fz = FilterZipper::new_with_masks(map.read_zipper(), vec![lowercase]);
+
+        assert_eq!(fz.child_mask().iter().collect::<Vec<u8>>(), vec![b'a', b'b', b'c']);
+        assert_eq!(fz.val_count(), 4);
+
+        assert!(fz.descend_to(b"apple"));
+        assert_eq!(fz.value(), Some(&2));
+        fz.reset();
+        assert!(!fz.descend_to(b"Apple"));
+        assert!(!fz.path_exists());
+        assert_eq!(fz.value(), None);
+        assert_eq!(fz.child_count(), 0);
+        assert!(fz.ascend(5));
+        assert!(fz.path_exists());
+
+        let mut values = vec![];
+        while fz.to_next_val() {
+            values.push(*fz.value().unwrap());
+        }
+        assert_eq!(values, vec![2, 3, 5, 6]);
+    }
+
+    #[test]
+    fn filter_zipper_predicate_test() {
+        let map = test_map();
+        //Hide every branch that leads to an even value
+        let mut fz = FilterZipper::new_with_predicate(map.read_zipper(), |_path: &[u8], val: Option<&usize>| {
+            val.map(|v| v % 2 == 1).unwrap_or(true)
+        });
+        assert_eq!(fz.val_count(), 3);
+        assert!(fz.descend_to(b"ap"));
+        assert_eq!(fz.child_count(), 2);
+        assert!(!fz.descend_to(b"ple"));
+        fz.ascend(3);
+        assert_eq!(fz.path(), b"ap");
+        assert!(fz.descend_to(b"ricot"));
+        assert_eq!(fz.value(), Some(&3));
+        assert!(fz.make_map().is_none());
+
+        fz.reset();
+        let map = fz.make_map().unwrap();
+        assert_eq!(map.iter().map(|(_, v)| *v).collect::<Vec<_>>(), vec![1, 3, 5]);
+
+        let mut target = BytesTrieMap::new();
+        target.write_zipper_at_path(b"odd:").graft(&fz);
+        assert_eq!(target.get(b"odd:banana"), Some(&5));
+        assert_eq!(target.get(b"odd:cherry"), None);
+    }
+
+    #[test]
+    fn filter_zipper_cata_test() {
+        let map = test_map();
+        let mut masks = vec![ByteMask::FULL; 2];
+        masks[1].clear_bit(b'p');
+        let fz = FilterZipper::new_with_masks(map.read_zipper(), masks);
+        let sum = fz.into_cata_side_effect(|_mask, children: &mut [usize], val, _path| {
+            children.iter().sum::<usize>() + val.copied().unwrap_or(0)
+        });
+        //Both "Apple" and "apple" are hidden by the mask at depth 1
+        assert_eq!(sum, 4 + 5 + 6);
+    }
+
+    #[test]
+    fn filter_zipper_product_factor_test() {
+        let prefixes = BytesTrieMap::from_iter([("x:", 0), ("y:", 0)]);
+        let map = test_map();
+        let lowercase: ByteMask = (b'a'..=b'z').collect();
+        let fz = FilterZipper::new_with_masks(map.read_zipper(), vec![lowercase]);
+
+        let mut pz = ProductZipper::new_with_primary(prefixes.read_zipper());
+        pz.new_virtual_factors([fz]);
+        assert_eq!(pz.factor_count(), 2);
+        assert!(pz.descend_to(b"x:"));
+        assert_eq!(pz.child_mask().iter().collect::<Vec<u8>>(), vec![b'a', b'b', b'c']);
+        assert!(!pz.descend_to(b"Apple"));
+        pz.reset();
+        assert!(pz.descend_to(b"y:apple"));
+        assert_eq!(pz.get_value(), Some(&2));
+
+        pz.reset();
+        let mut paths = vec![];
+        while pz.to_next_val() {
+            paths.push(pz.path().to_vec());
+        }
+        //The two prefixes, each followed by the four visible values
+        assert_eq!(paths.len(), 2 + 2 * 4);
+        assert!(paths.contains(&b"y:cherry".to_vec()));
+        assert!(!paths.contains(&b"y:Banana".to_vec()));
+    }
+
+    crate::zipper::zipper_moving_tests::zipper_moving_tests!(filter_zipper,
+        |keys: &[&[u8]]| {
+            let mut btm = BytesTrieMap::new();
+            keys.iter().for_each(|k| { btm.insert(k, ()); });
+            btm
+        },
+        |btm: &mut BytesTrieMap<()>, path: &[u8]| -> _ {
+            FilterZipper::new_with_masks(btm.read_zipper_at_path(path), vec![])
+    });
+
+    crate::zipper::zipper_iteration_tests::zipper_iteration_tests!(filter_zipper,
+        |keys: &[&[u8]]| {
+            let mut btm = BytesTrieMap::new();
+            keys.iter().for_each(|k| { btm.insert(k, ()); });
+            btm
+        },
+        |btm: &mut BytesTrieMap<()>, path: &[u8]| -> _ {
+            FilterZipper::new_with_predicate(btm.read_zipper_at_path(path), |_: &[u8], _: Option<&()>| true)
+    });
+}
@@ -0,0 +1,435 @@
+//! Property checks for [`Lattice`] and [`DistributiveLattice`] implementations
+//!
+//! The trie algebra assumes every value type obeys the lattice laws, and that the
+//! [`AlgebraicResult::Identity`] masks it returns are truthful.  A value type that breaks these assumptions
+//! won't cause a panic, but operations on maps containing it will silently produce wrong results.
+//!
+//! The functions in this module check the laws for a specific triple of values (e.g.
+//! [`check_lattice_laws_for`]), or for triples sampled from a [`Distribution`] (e.g. [`check_lattice_laws`]),
+//! so they can be called from the tests of a downstream crate with the [fuzzer](crate::fuzzer)
+//! distributions.  Each check returns the first [`LawViolation`] it encounters.
+//!
+//! ```ignore
+//! use rand::{SeedableRng, rngs::StdRng};
+//! use pathmap::lattice_laws::check_lattice_laws;
+//!
+//! let mut rng = StdRng::from_seed([0; 32]);
+//! check_lattice_laws(&my_value_distribution, &mut rng, 1000).unwrap();
+//! ```
+//!
+//! Equality is checked with [`PartialEq`], so values that aren't equal to themselves (such as `NaN`) will
+//! be reported as violations.
+
+use core::fmt::{Debug, Display, Formatter};
+use std::collections::BTreeSet;
+
+use rand::Rng;
+use rand_distr::Distribution;
+
+use crate::TrieValue;
+use crate::ring::*;
+use crate::trie_map::BytesTrieMap;
+
+/// Describes a law that was found not to hold, and the arguments that demonstrate it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LawViolation {
+    /// A short name for the law that was violated
+    pub law: &'static str,
+    /// A description of the arguments and results that violate the law
+    pub details: String,
+}
+
+impl Display for LawViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "lattice law violated: {}\n{}", self.law, self.details)
+    }
+}
+
+impl std::error::Error for LawViolation {}
+
+/// Checks the [`Lattice`] laws on `samples` triples of values drawn from `dist`
+///
+/// See [`check_lattice_laws_for`] for the laws that are checked.
+pub fn check_lattice_laws<V, D, R>(dist: &D, rng: &mut R, samples: usize) -> Result<(), LawViolation>
+    where V: Lattice + Clone + PartialEq + Debug, D: Distribution<V>, R: Rng + ?Sized
+{
+    for _ in 0..samples {
+        let a = dist.sample(rng);
+        let b = dist.sample(rng);
+        let c = dist.sample(rng);
+        check_lattice_laws_for(&a, &b, &c)?;
+    }
+    Ok(())
+}
+
+/// Checks the [`DistributiveLattice`] laws on `samples` triples of values drawn from `dist`
+///
+/// See [`check_distributive_lattice_laws_for`] for the laws that are checked.
+pub fn check_distributive_lattice_laws<V, D, R>(dist: &D, rng: &mut R, samples: usize) -> Result<(), LawViolation>
+    where V: Lattice + DistributiveLattice + Clone + PartialEq + Debug, D: Distribution<V>, R: Rng + ?Sized
+{
+    for _ in 0..samples {
+        let a = dist.sample(rng);
+        let b = dist.sample(rng);
+        let c = dist.sample(rng);
+        check_distributive_lattice_laws_for(&a, &b, &c)?;
+    }
+    Ok(())
+}
+
+/// Checks the trie-level [`Lattice`] identities on `samples` triples of maps drawn from `dist`, such as
+/// a [`UniformTrie`](crate::fuzzer::UniformTrie)
+///
+/// See [`check_trie_lattice_laws_for`] for the identities that are checked.
+pub fn check_trie_lattice_laws<V, D, R>(dist: &D, rng: &mut R, samples: usize) -> Result<(), LawViolation>
+    where V: TrieValue + Lattice + PartialEq + Debug, D: Distribution<BytesTrieMap<V>>, R: Rng + ?Sized
+{
+    for _ in 0..samples {
+        let a = dist.sample(rng);
+        let b = dist.sample(rng);
+        let c = dist.sample(rng);
+        check_trie_lattice_laws_for(&a, &b, &c)?;
+    }
+    Ok(())
+}
+
+/// Checks the trie-level [`DistributiveLattice`] identities on `samples` triples of maps drawn from `dist`
+///
+/// See [`check_trie_distributive_laws_for`] for the identities that are checked.
+pub fn check_trie_distributive_laws<V, D, R>(dist: &D, rng: &mut R, samples: usize) -> Result<(), LawViolation>
+    where V: TrieValue + Lattice + DistributiveLattice + PartialEq + Debug, D: Distribution<BytesTrieMap<V>>, R: Rng + ?Sized
+{
+    for _ in 0..samples {
+        let a = dist.sample(rng);
+        let b = dist.sample(rng);
+        check_trie_distributive_laws_for(&a, &b)?;
+    }
+    Ok(())
+}
+
+/// Checks the [`Lattice`] laws for a specific triple of values
+///
+/// A `None` result is treated as the bottom element, so e.g. associativity is checked on the `Option<V>`
+/// lattice.  The laws checked are:
+/// - Identity masks only contain [`SELF_IDENT`] and [`COUNTER_IDENT`], and only contain both when the
+///   arguments are equal
+/// - Commutativity of `pjoin` and `pmeet`
+/// - Associativity of `pjoin` and `pmeet`
+/// - Idempotence: `a ∨ a == a` and `a ∧ a == a`
+/// - Absorption: `a ∨ (a ∧ b) == a` and `a ∧ (a ∨ b) == a`
+/// - [`join_into`](Lattice::join_into) agrees with `pjoin`
+pub fn check_lattice_laws_for<V>(a: &V, b: &V, c: &V) -> Result<(), LawViolation>
+    where V: Lattice + Clone + PartialEq + Debug
+{
+    for (x, y) in [(a, b), (b, c), (a, c)] {
+        check_mask("pjoin identity mask", &x.pjoin(y), x, y, SELF_IDENT | COUNTER_IDENT)?;
+        check_mask("pmeet identity mask", &x.pmeet(y), x, y, SELF_IDENT | COUNTER_IDENT)?;
+
+        check_eq("pjoin commutativity", join(x, y), join(y, x), || format!("a: {x:?}\nb: {y:?}"))?;
+        check_eq("pmeet commutativity", meet(x, y), meet(y, x), || format!("a: {x:?}\nb: {y:?}"))?;
+
+        let x_opt = Some(x.clone());
+        check_eq("absorption of pjoin", join_opt(&x_opt, &meet(x, y)), x_opt.clone(), || format!("a: {x:?}\nb: {y:?}"))?;
+        check_eq("absorption of pmeet", meet_opt(&x_opt, &join(x, y)), x_opt.clone(), || format!("a: {x:?}\nb: {y:?}"))?;
+
+        check_join_into(x, y)?;
+    }
+
+    for x in [a, b, c] {
+        check_eq("pjoin idempotence", join(x, x), Some(x.clone()), || format!("a: {x:?}"))?;
+        check_eq("pmeet idempotence", meet(x, x), Some(x.clone()), || format!("a: {x:?}"))?;
+    }
+
+    let (a_opt, c_opt) = (Some(a.clone()), Some(c.clone()));
+    check_eq("pjoin associativity", join_opt(&join(a, b), &c_opt), join_opt(&a_opt, &join(b, c)),
+        || format!("a: {a:?}\nb: {b:?}\nc: {c:?}"))?;
+    check_eq("pmeet associativity", meet_opt(&meet(a, b), &c_opt), meet_opt(&a_opt, &meet(b, c)),
+        || format!("a: {a:?}\nb: {b:?}\nc: {c:?}"))?;
+
+    Ok(())
+}
+
+/// Checks the [`DistributiveLattice`] laws for a specific triple of values, in addition to the laws checked
+/// by [`check_lattice_laws_for`]
+///
+/// The additional laws checked are:
+/// - Identity masks from `psubtract` only contain [`SELF_IDENT`]
+/// - Annihilation: `a - a` is `None`
+/// - Idempotence of subtraction: `(a - b) - b == a - b`
+/// - Partition: `(a - b) ∨ (a ∧ b) == a`
+/// - Distributivity: `a ∧ (b ∨ c) == (a ∧ b) ∨ (a ∧ c)`
+pub fn check_distributive_lattice_laws_for<V>(a: &V, b: &V, c: &V) -> Result<(), LawViolation>
+    where V: Lattice + DistributiveLattice + Clone + PartialEq + Debug
+{
+    check_lattice_laws_for(a, b, c)?;
+
+    for (x, y) in [(a, b), (b, c), (a, c), (b, a)] {
+        check_mask("psubtract identity mask", &x.psubtract(y), x, y, SELF_IDENT)?;
+
+        let x_opt = Some(x.clone());
+        let y_opt = Some(y.clone());
+        let difference = subtract(x, y);
+        check_eq("psubtract idempotence", subtract_opt(&difference, &y_opt), difference.clone(),
+            || format!("a: {x:?}\nb: {y:?}"))?;
+        check_eq("psubtract partition", join_opt(&difference, &meet(x, y)), x_opt,
+            || format!("a: {x:?}\nb: {y:?}"))?;
+    }
+
+    for x in [a, b, c] {
+        check_eq("psubtract annihilation", subtract(x, x), None, || format!("a: {x:?}"))?;
+    }
+
+    let a_opt = Some(a.clone());
+    check_eq("distributivity", meet_opt(&a_opt, &join(b, c)), join_opt(&meet(a, b), &meet(a, c)),
+        || format!("a: {a:?}\nb: {b:?}\nc: {c:?}"))?;
+
+    Ok(())
+}
+
+/// Checks the [`Lattice`] identities for a specific triple of [`BytesTrieMap`]s
+///
+/// The identities checked are:
+/// - Every value in `a.join(&b)` and `a.meet(&b)` is the join or meet of the corresponding values in `a`
+///   and `b`, and there are no other values
+/// - Commutativity, associativity, idempotence and absorption of `join` and `meet`
+/// - The identity masks returned by `pjoin` and `pmeet` are consistent with the resulting maps
+pub fn check_trie_lattice_laws_for<V>(a: &BytesTrieMap<V>, b: &BytesTrieMap<V>, c: &BytesTrieMap<V>) -> Result<(), LawViolation>
+    where V: TrieValue + Lattice + PartialEq + Debug
+{
+    for (x, y) in [(a, b), (b, c), (a, c)] {
+        let joined = x.join(y);
+        let met = x.meet(y);
+        check_pointwise("trie join pointwise", &joined, x, y, join_opt)?;
+        check_pointwise("trie meet pointwise", &met, x, y, meet_opt)?;
+        check_trie_mask("trie pjoin identity mask", &x.pjoin(y), &joined, x, y)?;
+        check_trie_mask("trie pmeet identity mask", &x.pmeet(y), &met, x, y)?;
+
+        check_trie_eq("trie join commutativity", &joined, &y.join(x), || describe_maps(&[x, y]))?;
+        check_trie_eq("trie meet commutativity", &met, &y.meet(x), || describe_maps(&[x, y]))?;
+        check_trie_eq("trie join absorption", &x.join(&met), x, || describe_maps(&[x, y]))?;
+        check_trie_eq("trie meet absorption", &x.meet(&joined), x, || describe_maps(&[x, y]))?;
+    }
+
+    for x in [a, b, c] {
+        check_trie_eq("trie join idempotence", &x.join(x), x, || describe_maps(&[x]))?;
+        check_trie_eq("trie meet idempotence", &x.meet(x), x, || describe_maps(&[x]))?;
+    }
+
+    check_trie_eq("trie join associativity", &a.join(b).join(c), &a.join(&b.join(c)), || describe_maps(&[a, b, c]))?;
+    check_trie_eq("trie meet associativity", &a.meet(b).meet(c), &a.meet(&b.meet(c)), || describe_maps(&[a, b, c]))?;
+
+    Ok(())
+}
+
+/// Checks the [`DistributiveLattice`] identities for a specific pair of [`BytesTrieMap`]s
+///
+/// The identities checked are:
+/// - Every value in `a.subtract(&b)` is the difference of the corresponding values in `a` and `b`, and
+///   there are no other values
+/// - `a.subtract(&a)` is empty
+/// - `a.subtract(&b).join(&a.meet(&b)) == a`
+pub fn check_trie_distributive_laws_for<V>(a: &BytesTrieMap<V>, b: &BytesTrieMap<V>) -> Result<(), LawViolation>
+    where V: TrieValue + Lattice + DistributiveLattice + PartialEq + Debug
+{
+    for (x, y) in [(a, b), (b, a)] {
+        let difference = x.subtract(y);
+        check_pointwise("trie subtract pointwise", &difference, x, y, subtract_opt)?;
+        check_trie_eq("trie subtract partition", &difference.join(&x.meet(y)), x, || describe_maps(&[x, y]))?;
+        if !x.subtract(x).is_empty() {
+            return Err(LawViolation { law: "trie subtract annihilation", details: describe_maps(&[x]) })
+        }
+    }
+    Ok(())
+}
+
+fn join<V: Lattice + Clone>(a: &V, b: &V) -> Option<V> {
+    a.pjoin(b).into_option([a, b])
+}
+
+fn meet<V: Lattice + Clone>(a: &V, b: &V) -> Option<V> {
+    a.pmeet(b).into_option([a, b])
+}
+
+fn subtract<V: DistributiveLattice + Clone>(a: &V, b: &V) -> Option<V> {
+    a.psubtract(b).into_option([a, b])
+}
+
+fn join_opt<V: Lattice + Clone>(a: &Option<V>, b: &Option<V>) -> Option<V> {
+    a.pjoin(b).into_option([a, b]).flatten()
+}
+
+fn meet_opt<V: Lattice + Clone>(a: &Option<V>, b: &Option<V>) -> Option<V> {
+    a.pmeet(b).into_option([a, b]).flatten()
+}
+
+fn subtract_opt<V: DistributiveLattice + Clone>(a: &Option<V>, b: &Option<V>) -> Option<V> {
+    a.psubtract(b).into_option([a, b]).flatten()
+}
+
+fn check_eq<V: PartialEq + Debug, F: FnOnce() -> String>(law: &'static str, left: V, right: V, describe_args: F) -> Result<(), LawViolation> {
+    if left == right {
+        Ok(())
+    } else {
+        Err(LawViolation { law, details: format!("{}\nleft: {left:?}\nright: {right:?}", describe_args()) })
+    }
+}
+
+/// Checks that an identity mask only contains `allowed` bits, and only claims a mutual identity for equal args
+fn check_mask<V: PartialEq + Debug>(law: &'static str, result: &AlgebraicResult<V>, a: &V, b: &V, allowed: u64) -> Result<(), LawViolation> {
+    if let AlgebraicResult::Identity(mask) = result {
+        if *mask == 0 || mask & !allowed != 0 {
+            return Err(LawViolation { law, details: format!("a: {a:?}\nb: {b:?}\nmask: {mask:#b}") })
+        }
+        if mask & SELF_IDENT > 0 && mask & COUNTER_IDENT > 0 && a != b {
+            return Err(LawViolation { law, details: format!("a: {a:?}\nb: {b:?}\nmask claims both args are identities, but they differ") })
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `join_into` produces the same value as `pjoin`, and reports a consistent status
+fn check_join_into<V: Lattice + Clone + PartialEq + Debug>(a: &V, b: &V) -> Result<(), LawViolation> {
+    let expected = join(a, b);
+    let mut joined = a.clone();
+    let status = joined.join_into(b.clone());
+    let describe = || format!("a: {a:?}\nb: {b:?}\nstatus: {status:?}");
+    match status {
+        //A `None` status can't be represented in `self`, so there is nothing to compare
+        AlgebraicStatus::None => Ok(()),
+        AlgebraicStatus::Identity => {
+            check_eq("join_into identity status", &joined, a, describe)?;
+            check_eq("join_into agrees with pjoin", Some(joined), expected, describe)
+        },
+        AlgebraicStatus::Element => check_eq("join_into agrees with pjoin", Some(joined), expected, describe),
+    }
+}
+
+fn check_trie_eq<V, F>(law: &'static str, left: &BytesTrieMap<V>, right: &BytesTrieMap<V>, describe_args: F) -> Result<(), LawViolation>
+    where V: TrieValue + PartialEq + Debug, F: FnOnce() -> String
+{
+    if left.iter().eq(right.iter()) && left.get(b"") == right.get(b"") {
+        Ok(())
+    } else {
+        Err(LawViolation { law, details: format!("{}\nleft: {}\nright: {}", describe_args(), describe_map(left), describe_map(right)) })
+    }
+}
+
+/// Checks that an identity mask from a map operation agrees with the map that the operation produced
+fn check_trie_mask<V>(law: &'static str, result: &AlgebraicResult<BytesTrieMap<V>>, resolved: &BytesTrieMap<V>, a: &BytesTrieMap<V>, b: &BytesTrieMap<V>) -> Result<(), LawViolation>
+    where V: TrieValue + PartialEq + Debug
+{
+    match result {
+        AlgebraicResult::None => {
+            if !resolved.is_empty() {
+                return Err(LawViolation { law, details: format!("{}\nNone result, but resolved map is non-empty", describe_maps(&[a, b])) })
+            }
+        },
+        AlgebraicResult::Identity(mask) => {
+            if *mask == 0 || mask & !(SELF_IDENT | COUNTER_IDENT) != 0 {
+                return Err(LawViolation { law, details: format!("{}\nmask: {mask:#b}", describe_maps(&[a, b])) })
+            }
+            if mask & SELF_IDENT > 0 {
+                check_trie_eq(law, resolved, a, || describe_maps(&[a, b]))?;
+            }
+            if mask & COUNTER_IDENT > 0 {
+                check_trie_eq(law, resolved, b, || describe_maps(&[a, b]))?;
+            }
+        },
+        AlgebraicResult::Element(_) => {},
+    }
+    Ok(())
+}
+
+/// Checks that every value in `result` is `op` applied to the values at the same path in `a` and `b`
+fn check_pointwise<V, F>(law: &'static str, result: &BytesTrieMap<V>, a: &BytesTrieMap<V>, b: &BytesTrieMap<V>, op: F) -> Result<(), LawViolation>
+    where V: TrieValue + PartialEq + Debug, F: Fn(&Option<V>, &Option<V>) -> Option<V>
+{
+    let mut paths: BTreeSet<Vec<u8>> = a.iter().chain(b.iter()).map(|(path, _)| path).collect();
+    paths.extend(result.iter().map(|(path, _)| path));
+    paths.insert(vec![]);
+    for path in paths {
+        let expected = op(&a.get(&path).cloned(), &b.get(&path).cloned());
+        let found = result.get(&path).cloned();
+        if found != expected {
+            return Err(LawViolation { law, details: format!("{}\npath: {path:?}\nexpected: {expected:?}\nfound: {found:?}", describe_maps(&[a, b])) })
+        }
+    }
+    Ok(())
+}
+
+fn describe_map<V: TrieValue + Debug>(map: &BytesTrieMap<V>) -> String {
+    let mut entries: Vec<(Vec<u8>, &V)> = map.get(b"").map(|root_val| (vec![], root_val)).into_iter().collect();
+    entries.extend(map.iter());
+    format!("{entries:?}")
+}
+
+fn describe_maps<V: TrieValue + Debug>(maps: &[&BytesTrieMap<V>]) -> String {
+    let names = ["a", "b", "c"];
+    maps.iter().zip(names).map(|(map, name)| format!("{name}: {}", describe_map(map))).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::marker::PhantomData;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use rand::distr::Uniform;
+    use crate::fuzzer::*;
+    use crate::lattice_laws::*;
+    use crate::ring::SortedVecSet;
+
+    fn path_fuzzer() -> Repeated<u8, Uniform<usize>, Categorical<u8, Uniform<usize>>> {
+        Repeated { lengthd: Uniform::try_from(1..4).unwrap(), itemd: Categorical { elements: "abc".as_bytes().to_vec(),
+            ed: Uniform::try_from(0..3).unwrap() }, pd: PhantomData::default() }
+    }
+
+    #[test]
+    fn lattice_laws_primitives_test() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        check_distributive_lattice_laws(&Categorical { elements: vec![false, true], ed: Uniform::try_from(0..2).unwrap() }, &mut rng, 50).unwrap();
+        check_lattice_laws(&Uniform::try_from(-5i32..5).unwrap(), &mut rng, 200).unwrap();
+        check_lattice_laws(&Uniform::try_from(-1.0f64..1.0).unwrap(), &mut rng, 200).unwrap();
+
+        let tuples = Product2 { dx: Uniform::try_from(-3i64..3).unwrap(), dy: Categorical { elements: vec![false, true], ed: Uniform::try_from(0..2).unwrap() },
+            f: |x, y| (x, y), pd: PhantomData::default() };
+        check_lattice_laws(&tuples, &mut rng, 200).unwrap();
+    }
+
+    #[test]
+    fn lattice_laws_sets_test() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let sets = Mapped { d: Repeated { lengthd: Uniform::try_from(1..5).unwrap(), itemd: Uniform::try_from(0u8..8).unwrap(), pd: PhantomData::default() },
+            f: |items: Vec<u8>| items.into_iter().collect::<HashSet<u8>>(), pd: PhantomData::default() };
+        check_distributive_lattice_laws(&sets, &mut rng, 200).unwrap();
+
+        let sorted_vecs = Mapped { d: Repeated { lengthd: Uniform::try_from(1..5).unwrap(), itemd: Uniform::try_from(0u8..8).unwrap(), pd: PhantomData::default() },
+            f: |items: Vec<u8>| SortedVecSet::from(items), pd: PhantomData::default() };
+        check_distributive_lattice_laws(&sorted_vecs, &mut rng, 200).unwrap();
+
+        //Only 1-tuples are distributive; wider tuples of sets are checked as plain lattices
+        let singles = Mapped { d: sorted_vecs.clone(), f: |set: SortedVecSet<u8>| (set,), pd: PhantomData::default() };
+        check_distributive_lattice_laws(&singles, &mut rng, 200).unwrap();
+        let pairs = Product2 { dx: sorted_vecs.clone(), dy: sets.clone(), f: |x, y| (x, y), pd: PhantomData::default() };
+        check_lattice_laws(&pairs, &mut rng, 200).unwrap();
+    }
+
+    #[test]
+    fn lattice_laws_trie_test() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let unit_tries = UniformTrie { size: 8, pd: path_fuzzer(), vd: Degenerate { element: () }, ph: PhantomData::default() };
+        check_trie_lattice_laws(&unit_tries, &mut rng, 50).unwrap();
+        check_trie_distributive_laws(&unit_tries, &mut rng, 50).unwrap();
+
+        let bool_tries = UniformTrie { size: 8, pd: path_fuzzer(), vd: Categorical { elements: vec![false, true], ed: Uniform::try_from(0..2).unwrap() }, ph: PhantomData::default() };
+        check_trie_lattice_laws(&bool_tries, &mut rng, 50).unwrap();
+        check_trie_distributive_laws(&bool_tries, &mut rng, 50).unwrap();
+    }
+
+    #[test]
+    fn lattice_laws_violation_test() {
+        //`usize` is a placeholder impl that always returns its left argument, so it isn't commutative
+        let violation = check_lattice_laws_for(&1usize, &2usize, &3usize).unwrap_err();
+        assert_eq!(violation.law, "pjoin commutativity");
+    }
+}
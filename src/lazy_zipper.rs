@@ -0,0 +1,613 @@
+use core::cell::RefCell;
+
+use crate::{Allocator, GlobalAlloc, global_alloc};
+use crate::utils::{BitMask, ByteMask};
+use crate::ring::Lattice;
+use crate::morphisms::{TrieBuilder, WOrNode};
+use crate::trie_map::BytesTrieMap;
+use crate::trie_node::{AbstractNodeRef, TrieNode};
+use crate::zipper::*;
+use crate::zipper::zipper_priv::ZipperPriv;
+
+/// A virtual [Zipper] over a trie that is generated on demand by a coalgebra
+///
+/// The coalgebra has the same form as the closure passed to
+/// [new_from_ana](crate::trie_map::BytesTrieMap::new_from_ana), however it runs when the zipper descends
+/// to a path where a `W` was pushed, rather than ahead of time.  This allows the `LazyZipper` to explore
+/// spaces that are too large, or even infinite, to materialize.
+///
+/// When memoization is enabled, the subtries generated by the coalgebra are retained after the zipper
+/// ascends, so each `W` is expanded at most once.  Otherwise the coalgebra is run again each time a path is
+/// revisited, and only the branch along the focus path is held in memory.
+///
+/// WARNING: [val_count](ZipperMoving::val_count), [make_map](ZipperSubtries::make_map), and the use of a
+/// `LazyZipper` as the source for [ZipperWriting::graft] or the algebraic operations will traverse the
+/// entire space below the focus, and will never finish if that space is infinite.  Use
+/// [meet_with](LazyZipper::meet_with) to intersect an infinite space with a finite trie.
+///
+/// A [ProductZipper] factor must be a concrete trie, so use [make_map](ZipperSubtries::make_map) to
+/// materialize a finite `LazyZipper` before using it as a factor.
+pub struct LazyZipper<V: Clone + Send + Sync, W, AlgF, A: Allocator = GlobalAlloc> {
+    alg_f: RefCell<AlgF>,
+    memoize: bool,
+    path: Vec<u8>,
+    /// The expanded nodes along the path.  The first frame is the root
+    frames: Vec<LazyFrame<V, W, A>>,
+    /// The location of the focus, relative to the last frame
+    focus: LazyFocus,
+    alloc: A,
+}
+
+/// The output of one run of the coalgebra
+struct LazyNode<V: Clone + Send + Sync, W, A: Allocator> {
+    val: Option<V>,
+    mask: ByteMask,
+    /// Sorted by the first byte of each child's path
+    children: Vec<LazyChild<V, W, A>>,
+}
+
+struct LazyChild<V: Clone + Send + Sync, W, A: Allocator> {
+    path: Vec<u8>,
+    branch: LazyBranch<V, W, A>,
+}
+
+enum LazyBranch<V: Clone + Send + Sync, W, A: Allocator> {
+    /// A branch that will be expanded by the coalgebra, and its retained expansion if memoization is enabled
+    W { w: W, memo: Option<Box<LazyNode<V, W, A>>> },
+    /// A subtrie grafted by the coalgebra
+    Trie(BytesTrieMap<V, A>),
+}
+
+struct LazyFrame<V: Clone + Send + Sync, W, A: Allocator> {
+    node: LazyNode<V, W, A>,
+    /// The path length at the node
+    depth: usize,
+    /// The index of the node within the parent frame's children
+    child_idx: usize,
+}
+
+#[derive(Clone, Copy)]
+enum LazyFocus {
+    /// The focus is at the last frame's node
+    Node,
+    /// The focus is partway along the path to a child
+    Stem { child_idx: usize, consumed: usize },
+    /// The focus is within a grafted subtrie
+    Trie { child_idx: usize },
+    /// The focus is on a non-existent path
+    Missing,
+}
+
+/// An item visited during a traversal of the space below the focus
+enum LazyVisit<'a, V: Clone + Send + Sync, A: Allocator> {
+    Value(&'a V),
+    Trie(&'a BytesTrieMap<V, A>),
+}
+
+impl<V: Clone + Send + Sync + Unpin, W: Default + Clone, AlgF> LazyZipper<V, W, AlgF, GlobalAlloc>
+    where AlgF: FnMut(W, &mut Option<V>, &mut TrieBuilder<V, W, GlobalAlloc>, &[u8])
+{
+    /// Creates a new `LazyZipper` that expands the space from `w` with `alg_f`, without memoization
+    ///
+    /// See [new_from_ana](crate::trie_map::BytesTrieMap::new_from_ana) for a description of `alg_f`
+    pub fn new(w: W, alg_f: AlgF) -> Self {
+        Self::new_in(w, alg_f, false, global_alloc())
+    }
+    /// Creates a new `LazyZipper` that expands the space from `w` with `alg_f`, retaining the expanded
+    /// subtries
+    pub fn new_memoizing(w: W, alg_f: AlgF) -> Self {
+        Self::new_in(w, alg_f, true, global_alloc())
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, W: Default + Clone, AlgF, A: Allocator> LazyZipper<V, W, AlgF, A>
+    where AlgF: FnMut(W, &mut Option<V>, &mut TrieBuilder<V, W, A>, &[u8])
+{
+    /// Creates a new `LazyZipper` in the specified allocator.  See [new](LazyZipper::new)
+    pub fn new_in(w: W, alg_f: AlgF, memoize: bool, alloc: A) -> Self {
+        let alg_f = RefCell::new(alg_f);
+        let root = Self::run_alg(&alg_f, w, &[], &alloc);
+        Self {
+            alg_f,
+            memoize,
+            path: vec![],
+            frames: vec![LazyFrame { node: root, depth: 0, child_idx: 0 }],
+            focus: LazyFocus::Node,
+            alloc,
+        }
+    }
+    /// Returns `true` if the `LazyZipper` retains the subtries it has expanded
+    pub fn is_memoizing(&self) -> bool {
+        self.memoize
+    }
+    /// Returns a new [BytesTrieMap] containing the meet of the space below the focus with the subtrie
+    /// below `other`'s focus
+    ///
+    /// Only the branches that also exist in `other` are expanded, so this method is suitable for spaces
+    /// that are infinite, as long as `other` is finite.  The values at the focus are included at the root
+    /// of the returned map.
+    pub fn meet_with<Z: ZipperMoving + ZipperValues<V>>(&mut self, mut other: Z) -> BytesTrieMap<V, A>
+        where V: Lattice
+    {
+        let mut result = BytesTrieMap::new_in(self.alloc.clone());
+        let base_len = self.path.len();
+        self.meet_recursive(&mut other, base_len, &mut result);
+        result
+    }
+    fn meet_recursive<Z: ZipperMoving + ZipperValues<V>>(&mut self, other: &mut Z, base_len: usize, result: &mut BytesTrieMap<V, A>)
+        where V: Lattice
+    {
+        if let (Some(self_val), Some(other_val)) = (self.value(), other.value()) {
+            if let Some(val) = self_val.pmeet(other_val).into_option([self_val, other_val]) {
+                result.insert(&self.path[base_len..], val);
+            }
+        }
+        let mask = self.child_mask() & other.child_mask();
+        for byte in mask.iter() {
+            self.descend_to_byte(byte);
+            other.descend_to_byte(byte);
+            self.meet_recursive(other, base_len, result);
+            other.ascend_byte();
+            self.ascend_byte();
+        }
+    }
+    /// Runs the coalgebra for `w` at `path`, and collects the results into a [LazyNode]
+    fn run_alg(alg_f: &RefCell<AlgF>, w: W, path: &[u8], alloc: &A) -> LazyNode<V, W, A> {
+        let mut builder = TrieBuilder::<V, W, A>::new_in(alloc.clone());
+        let mut val = None;
+        (alg_f.borrow_mut())(w, &mut val, &mut builder, path);
+        builder.finalize();
+
+        let mut mask = ByteMask::EMPTY;
+        let mut children = vec![];
+        while let Some(w_or_node) = builder.take_next() {
+            let byte = builder.taken_child_byte();
+            mask.set_bit(byte);
+            let mut child_path = vec![byte];
+            if let Some(remaining) = builder.taken_child_remaining_path(byte) {
+                child_path.extend_from_slice(remaining);
+            }
+            let branch = match w_or_node {
+                WOrNode::W(w) => LazyBranch::W { w, memo: None },
+                WOrNode::Node(node) => LazyBranch::Trie(BytesTrieMap::new_with_root_in(Some(node), None, alloc.clone())),
+            };
+            children.push(LazyChild { path: child_path, branch });
+        }
+        LazyNode { val, mask, children }
+    }
+    /// Pushes a new frame for the child at `child_idx` of the last frame, which must be a `W` branch whose
+    /// path ends at the focus
+    fn expand(&mut self, child_idx: usize) {
+        let frame = self.frames.last_mut().unwrap();
+        let node = match &mut frame.node.children[child_idx].branch {
+            LazyBranch::W { w, memo } => match memo.take() {
+                Some(node) => *node,
+                None => Self::run_alg(&self.alg_f, w.clone(), &self.path, &self.alloc),
+            },
+            LazyBranch::Trie(_) => unreachable!(),
+        };
+        self.frames.push(LazyFrame { node, depth: self.path.len(), child_idx });
+    }
+    /// Pops frames below the focus, retaining their nodes if memoization is enabled
+    fn pop_frames(&mut self) {
+        while self.frames.last().unwrap().depth > self.path.len() {
+            let frame = self.frames.pop().unwrap();
+            if self.memoize {
+                let parent = self.frames.last_mut().unwrap();
+                if let LazyBranch::W { memo, .. } = &mut parent.node.children[frame.child_idx].branch {
+                    *memo = Some(Box::new(frame.node));
+                }
+            }
+        }
+    }
+    /// Visits every value and grafted subtrie below `node`.  `path` is the origin path to `node`
+    fn visit_node<VisitF>(&self, node: &LazyNode<V, W, A>, path: &mut Vec<u8>, visit_f: &mut VisitF)
+        where VisitF: FnMut(&[u8], LazyVisit<'_, V, A>)
+    {
+        for child in node.children.iter() {
+            let path_len = path.len();
+            path.extend_from_slice(&child.path);
+            self.visit_branch(&child.branch, path, visit_f);
+            path.truncate(path_len);
+        }
+    }
+    /// Visits a branch, including the value at its root.  `path` is the origin path to the branch
+    fn visit_branch<VisitF>(&self, branch: &LazyBranch<V, W, A>, path: &mut Vec<u8>, visit_f: &mut VisitF)
+        where VisitF: FnMut(&[u8], LazyVisit<'_, V, A>)
+    {
+        match branch {
+            LazyBranch::W { w: _, memo: Some(node) } => {
+                if let Some(val) = &node.val {
+                    visit_f(path, LazyVisit::Value(val));
+                }
+                self.visit_node(node, path, visit_f);
+            },
+            LazyBranch::W { w, memo: None } => {
+                let node = Self::run_alg(&self.alg_f, w.clone(), path, &self.alloc);
+                if let Some(val) = &node.val {
+                    visit_f(path, LazyVisit::Value(val));
+                }
+                self.visit_node(&node, path, visit_f);
+            },
+            LazyBranch::Trie(map) => visit_f(path, LazyVisit::Trie(map)),
+        }
+    }
+    /// Visits every value and grafted subtrie below the focus, not including the focus itself
+    fn visit_below_focus<VisitF>(&self, visit_f: &mut VisitF)
+        where VisitF: FnMut(&[u8], LazyVisit<'_, V, A>)
+    {
+        let frame = self.frames.last().unwrap();
+        let mut path = self.path.clone();
+        match self.focus {
+            LazyFocus::Node => self.visit_node(&frame.node, &mut path, visit_f),
+            LazyFocus::Stem { child_idx, consumed } => {
+                let child = &frame.node.children[child_idx];
+                path.extend_from_slice(&child.path[consumed..]);
+                self.visit_branch(&child.branch, &mut path, visit_f);
+            },
+            LazyFocus::Trie { .. } | LazyFocus::Missing => {}
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, W, AlgF, A: Allocator> LazyZipper<V, W, AlgF, A> {
+    /// Recomputes the focus from the path, and the last frame
+    fn refresh_focus(&mut self) {
+        let frame = self.frames.last().unwrap();
+        let remaining = &self.path[frame.depth..];
+        self.focus = match remaining.first() {
+            None => LazyFocus::Node,
+            Some(&byte) => {
+                match frame.node.children.binary_search_by_key(&byte, |child| child.path[0]) {
+                    Ok(child_idx) => {
+                        let child = &frame.node.children[child_idx];
+                        if remaining.len() < child.path.len() && child.path.starts_with(remaining) {
+                            LazyFocus::Stem { child_idx, consumed: remaining.len() }
+                        } else if remaining.starts_with(&child.path) && matches!(child.branch, LazyBranch::Trie(_)) {
+                            LazyFocus::Trie { child_idx }
+                        } else {
+                            LazyFocus::Missing
+                        }
+                    },
+                    Err(_) => LazyFocus::Missing
+                }
+            }
+        };
+    }
+    /// Returns the path of the focus within the grafted subtrie of the child at `child_idx`
+    fn trie_ref(&self, child_idx: usize) -> TrieRef<'_, V, A> {
+        let frame = self.frames.last().unwrap();
+        let child = &frame.node.children[child_idx];
+        match &child.branch {
+            LazyBranch::Trie(map) => map.trie_ref_at_path(&self.path[frame.depth + child.path.len()..]),
+            LazyBranch::W { .. } => unreachable!()
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, W, AlgF, A: Allocator> Zipper for LazyZipper<V, W, AlgF, A> {
+    fn path_exists(&self) -> bool {
+        match self.focus {
+            LazyFocus::Node | LazyFocus::Stem { .. } => true,
+            LazyFocus::Trie { child_idx } => self.trie_ref(child_idx).path_exists(),
+            LazyFocus::Missing => false,
+        }
+    }
+    fn is_value(&self) -> bool {
+        self.value().is_some()
+    }
+    fn child_count(&self) -> usize {
+        self.child_mask().count_bits()
+    }
+    fn child_mask(&self) -> ByteMask {
+        let frame = self.frames.last().unwrap();
+        match self.focus {
+            LazyFocus::Node => frame.node.mask,
+            LazyFocus::Stem { child_idx, consumed } => {
+                let mut mask = ByteMask::EMPTY;
+                mask.set_bit(frame.node.children[child_idx].path[consumed]);
+                mask
+            },
+            LazyFocus::Trie { child_idx } => self.trie_ref(child_idx).child_mask(),
+            LazyFocus::Missing => ByteMask::EMPTY,
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, W, AlgF, A: Allocator> ZipperValues<V> for LazyZipper<V, W, AlgF, A> {
+    fn value(&self) -> Option<&V> {
+        match self.focus {
+            LazyFocus::Node => self.frames.last().unwrap().node.val.as_ref(),
+            LazyFocus::Trie { child_idx } => self.trie_ref(child_idx).get_value(),
+            LazyFocus::Stem { .. } | LazyFocus::Missing => None,
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, W: Default + Clone, AlgF, A: Allocator> ZipperMoving for LazyZipper<V, W, AlgF, A>
+    where AlgF: FnMut(W, &mut Option<V>, &mut TrieBuilder<V, W, A>, &[u8])
+{
+    fn at_root(&self) -> bool { self.path.is_empty() }
+    fn reset(&mut self) {
+        self.path.clear();
+        self.pop_frames();
+        self.refresh_focus();
+    }
+    fn path(&self) -> &[u8] { &self.path }
+    fn val_count(&self) -> usize {
+        let mut count = if self.is_value() { 1 } else { 0 };
+        match self.focus {
+            LazyFocus::Trie { child_idx } => {
+                let frame = self.frames.last().unwrap();
+                let child = &frame.node.children[child_idx];
+                if let LazyBranch::Trie(map) = &child.branch {
+                    count = map.read_zipper_at_path(&self.path[frame.depth + child.path.len()..]).val_count();
+                }
+            },
+            _ => self.visit_below_focus(&mut |_, item| {
+                match item {
+                    LazyVisit::Value(_) => count += 1,
+                    LazyVisit::Trie(map) => count += map.val_count(),
+                }
+            }),
+        }
+        count
+    }
+    fn descend_to<K: AsRef<[u8]>>(&mut self, k: K) -> bool {
+        for &byte in k.as_ref() {
+            self.descend_to_byte(byte);
+        }
+        self.path_exists()
+    }
+    fn descend_to_byte(&mut self, k: u8) -> bool {
+        self.path.push(k);
+        if let LazyFocus::Node | LazyFocus::Stem { .. } = self.focus {
+            let frame = self.frames.last().unwrap();
+            let remaining = &self.path[frame.depth..];
+            if let Ok(child_idx) = frame.node.children.binary_search_by_key(&remaining[0], |child| child.path[0]) {
+                let child = &frame.node.children[child_idx];
+                if child.path == remaining && matches!(child.branch, LazyBranch::W { .. }) {
+                    self.expand(child_idx);
+                }
+            }
+        }
+        self.refresh_focus();
+        self.path_exists()
+    }
+    fn ascend(&mut self, steps: usize) -> bool {
+        let ascended = steps <= self.path.len();
+        let new_len = self.path.len().saturating_sub(steps);
+        self.path.truncate(new_len);
+        self.pop_frames();
+        self.refresh_focus();
+        ascended
+    }
+    fn ascend_until(&mut self) -> bool {
+        if self.at_root() {
+            return false
+        }
+        loop {
+            self.ascend_byte();
+            if self.child_count() > 1 || self.is_value() || self.at_root() {
+                return true
+            }
+        }
+    }
+    fn ascend_until_branch(&mut self) -> bool {
+        if self.at_root() {
+            return false
+        }
+        loop {
+            self.ascend_byte();
+            if self.child_count() > 1 || self.at_root() {
+                return true
+            }
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, W: Default + Clone, AlgF, A: Allocator> ZipperIteration for LazyZipper<V, W, AlgF, A>
+    where AlgF: FnMut(W, &mut Option<V>, &mut TrieBuilder<V, W, A>, &[u8])
+{ }
+
+impl<V: Clone + Send + Sync + Unpin, W: Default + Clone, AlgF, A: Allocator> ZipperAbsolutePath for LazyZipper<V, W, AlgF, A>
+    where AlgF: FnMut(W, &mut Option<V>, &mut TrieBuilder<V, W, A>, &[u8])
+{
+    fn origin_path(&self) -> &[u8] { &self.path }
+    fn root_prefix_path(&self) -> &[u8] { &[] }
+}
+
+impl<V: Clone + Send + Sync + Unpin, W: Default + Clone, AlgF, A: Allocator> ZipperSubtries<V, A> for LazyZipper<V, W, AlgF, A>
+    where AlgF: FnMut(W, &mut Option<V>, &mut TrieBuilder<V, W, A>, &[u8])
+{
+    fn make_map(&self) -> Option<BytesTrieMap<V, A>> {
+        let mut map = BytesTrieMap::new_in(self.alloc.clone());
+        match self.focus {
+            LazyFocus::Trie { child_idx } => {
+                let frame = self.frames.last().unwrap();
+                let child = &frame.node.children[child_idx];
+                if let LazyBranch::Trie(subtrie) = &child.branch {
+                    return subtrie.read_zipper_at_path(&self.path[frame.depth + child.path.len()..]).make_map()
+                }
+            },
+            _ => {
+                let base_len = self.path.len();
+                self.visit_below_focus(&mut |path, item| {
+                    match item {
+                        LazyVisit::Value(val) => { map.insert(&path[base_len..], val.clone()); },
+                        LazyVisit::Trie(subtrie) => map.write_zipper_at_path(&path[base_len..]).graft_map(subtrie.clone()),
+                    }
+                });
+            }
+        }
+        if map.is_empty() {
+            None
+        } else {
+            Some(map)
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, W: Default + Clone, AlgF, A: Allocator> ZipperPriv for LazyZipper<V, W, AlgF, A>
+    where AlgF: FnMut(W, &mut Option<V>, &mut TrieBuilder<V, W, A>, &[u8])
+{
+    type V = V;
+    type A = A;
+    fn get_focus(&self) -> AbstractNodeRef<'_, V, A> {
+        match self.make_map().and_then(|map| map.into_root().0) {
+            Some(root) => AbstractNodeRef::OwnedRc(root),
+            None => AbstractNodeRef::None,
+        }
+    }
+    fn try_borrow_focus(&self) -> Option<&dyn TrieNode<V, A>> {
+        //The lazy subtrie doesn't exist as a node until it's materialized
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use crate::trie_map::BytesTrieMap;
+    use crate::zipper::*;
+
+    /// Generates every string of `b'0'`s and `b'1'`s, and sets a value on the strings that contain the
+    /// same number of each
+    fn balanced_strings(val: &mut Option<usize>, children: &mut crate::morphisms::TrieBuilder<usize, i64, crate::GlobalAlloc>, w: i64, path: &[u8]) {
+        if w == 0 {
+            *val = Some(path.len());
+        }
+        children.push_byte(b'0', w - 1);
+        children.push_byte(b'1', w + 1);
+    }
+
+    #[test]
+    fn lazy_zipper_infinite_test() {
+        let runs = Cell::new(0);
+        let mut lz = LazyZipper::new(0i64, |w, val, children, path| {
+            runs.set(runs.get() + 1);
+            balanced_strings(val, children, w, path)
+        });
+        assert_eq!(lz.value(), Some(&0));
+        assert_eq!(lz.child_count(), 2);
+        assert!(lz.descend_to(b"0110"));
+        assert_eq!(lz.value(), Some(&4));
+        assert!(lz.descend_to(b"1"));
+        assert_eq!(lz.value(), None);
+        assert_eq!(runs.get(), 6);
+
+        //Without memoization, revisiting the path runs the coalgebra again
+        lz.reset();
+        assert!(lz.descend_to(b"01"));
+        assert_eq!(lz.value(), Some(&2));
+        assert_eq!(runs.get(), 8);
+
+        let finite = BytesTrieMap::from_iter([("01", 1), ("0011", 1), ("0", 7), ("2", 9)]);
+        lz.reset();
+        let met = lz.meet_with(finite.read_zipper());
+        assert_eq!(met.iter().collect::<Vec<_>>(), vec![(b"0011".to_vec(), &4), (b"01".to_vec(), &2)]);
+    }
+
+    #[test]
+    fn lazy_zipper_memoizing_test() {
+        let runs = Cell::new(0);
+        let mut lz = LazyZipper::new_memoizing(3u8, |w, val, children, _path| {
+            runs.set(runs.get() + 1);
+            if w > 0 {
+                children.push(b"L", w - 1);
+                children.push(b"Right", w - 1);
+            } else {
+                *val = Some(());
+            }
+        });
+        assert!(lz.is_memoizing());
+        assert!(lz.descend_to(b"RightL"));
+        assert_eq!(lz.path(), b"RightL");
+        assert!(lz.descend_to(b"Ri"));
+        assert_eq!(lz.child_mask().iter().collect::<Vec<u8>>(), vec![b'g']);
+        assert!(!lz.descend_to(b"x"));
+        assert!(!lz.path_exists());
+        assert!(lz.ascend(3));
+        assert_eq!(runs.get(), 3);
+        assert_eq!(lz.val_count(), 2);
+
+        //The expanded branch is retained, so revisiting the path doesn't run the coalgebra
+        let expanded_runs = runs.get();
+        lz.reset();
+        assert!(lz.descend_to(b"RightL"));
+        assert_eq!(runs.get(), expanded_runs);
+
+        lz.reset();
+        assert_eq!(lz.val_count(), 8);
+        let map = lz.make_map().unwrap();
+        assert_eq!(map.val_count(), 8);
+        assert_eq!(map.get(b"LRightL"), Some(&()));
+
+        let mut target = BytesTrieMap::new();
+        target.insert(b"LLL", ());
+        target.insert(b"LLx", ());
+        let _ = target.write_zipper().meet(&lz);
+        assert_eq!(target.iter().map(|(k, _)| k).collect::<Vec<_>>(), vec![b"LLL".to_vec()]);
+    }
+
+    #[test]
+    fn lazy_zipper_graft_test() {
+        let sub = BytesTrieMap::from_iter([("a", 1), ("bc", 2)]);
+        let mut lz = LazyZipper::new(0u8, |w, val, children, _path| {
+            if w == 0 {
+                *val = Some(0);
+                children.push(b"xy", 1);
+            } else {
+                children.graft_at_byte(b':', &sub.read_zipper());
+            }
+        });
+        assert_eq!(lz.val_count(), 3);
+        assert!(lz.descend_to(b"xy:b"));
+        assert!(lz.path_exists());
+        assert_eq!(lz.child_mask().iter().collect::<Vec<u8>>(), vec![b'c']);
+        assert!(lz.descend_to_byte(b'c'));
+        assert_eq!(lz.value(), Some(&2));
+        assert!(lz.ascend(4));
+        assert_eq!(lz.path(), b"x");
+        let map = lz.make_map().unwrap();
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![(b"y:a".to_vec(), &1), (b"y:bc".to_vec(), &2)]);
+    }
+
+    crate::zipper::zipper_moving_tests::zipper_moving_tests!(lazy_zipper,
+        |keys: &[&[u8]]| {
+            let mut btm = BytesTrieMap::new();
+            keys.iter().for_each(|k| { btm.insert(k, ()); });
+            btm
+        },
+        |btm: &mut BytesTrieMap<()>, path: &[u8]| -> _ {
+            let btm = &*btm;
+            LazyZipper::new(path.to_vec(), move |w: Vec<u8>, val, children, _path| {
+                let rz = btm.read_zipper_at_path(&w);
+                *val = rz.value().cloned();
+                for byte in rz.child_mask().iter() {
+                    let mut child_w = w.clone();
+                    child_w.push(byte);
+                    children.push_byte(byte, child_w);
+                }
+            })
+    });
+
+    crate::zipper::zipper_iteration_tests::zipper_iteration_tests!(lazy_zipper,
+        |keys: &[&[u8]]| {
+            let mut btm = BytesTrieMap::new();
+            keys.iter().for_each(|k| { btm.insert(k, ()); });
+            btm
+        },
+        |btm: &mut BytesTrieMap<()>, path: &[u8]| -> _ {
+            let btm = &*btm;
+            LazyZipper::new_memoizing(path.to_vec(), move |w: Vec<u8>, val, children, _path| {
+                let rz = btm.read_zipper_at_path(&w);
+                *val = rz.value().cloned();
+                for byte in rz.child_mask().iter() {
+                    let mut child_w = w.clone();
+                    child_w.push(byte);
+                    children.push_byte(byte, child_w);
+                }
+            })
+    });
+}
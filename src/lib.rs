@@ -49,10 +49,32 @@ mod zipper_head;
 #[cfg(feature = "fuzzer")]
 pub mod fuzzer;
 
+/// Property checks to validate [Lattice](crate::ring::Lattice) implementations for custom value types
+#[cfg(feature = "fuzzer")]
+pub mod lattice_laws;
+
 /// Features to inspect performance properties of trees, for optimizing
 #[cfg(feature = "counters")]
 pub mod counters;
 
+/// Lists the differences between two versions of a trie, and creates patches to apply them
+pub mod trie_diff;
+
+/// A trie map that records a history of committed versions
+pub mod versioned_map;
+
+/// Records every change to a trie in a write-ahead log, for crash recovery
+pub mod write_ahead_log;
+
+/// Helpers to process the branches of a trie on several threads
+pub mod parallel;
+
+/// A map edited by a single writer, which publishes consistent snapshots to readers on other threads
+pub mod shared_map;
+
+/// A map sharded by the first byte of each path, so many threads can write to it at once
+pub mod concurrent_map;
+
 pub mod serialization;
 pub mod path_serialization;
 pub mod tree_serialization;
@@ -60,6 +82,13 @@ pub mod tree_serialization;
 mod trie_node;
 mod write_zipper;
 mod product_zipper;
+mod overlay_zipper;
+mod filter_zipper;
+mod remap_zipper;
+mod lazy_zipper;
+mod zipper_join;
+mod undo_zipper;
+mod observed_zipper;
 mod trie_ref;
 mod dense_byte_node;
 pub(crate) mod line_list_node;
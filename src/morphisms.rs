@@ -977,7 +977,7 @@ pub struct TrieBuilder<V: Clone + Send + Sync, W, A: Allocator> {
 }
 
 /// Internal structure 
-enum WOrNode<V: Clone + Send + Sync, W, A: Allocator> {
+pub(crate) enum WOrNode<V: Clone + Send + Sync, W, A: Allocator> {
     W(W),
     Node(TrieNodeODRc<V, A>)
 }
@@ -994,7 +994,7 @@ impl<V: Clone + Send + Sync, W: Default, A: Allocator> Default for WOrNode<V, W,
 
 impl<V: Clone + Send + Sync, W: Default, A: Allocator> TrieBuilder<V, W, A> {
     /// Internal method to make a new empty `TrieBuilder`
-    fn new_in(alloc: A) -> Self {
+    pub(crate) fn new_in(alloc: A) -> Self {
         Self {
             child_mask: [0u64; 4],
             cur_mask_word: 0,
@@ -1011,19 +1011,19 @@ impl<V: Clone + Send + Sync, W: Default, A: Allocator> TrieBuilder<V, W, A> {
         self.child_paths.clear();
     }
     /// Internal method.  Called after the user code has run to fill the builder, but before we start to empty it
-    fn finalize(&mut self) {
+    pub(crate) fn finalize(&mut self) {
         self.cur_mask_word = 0;
         while self.cur_mask_word < 4 && self.child_mask[self.cur_mask_word] == 0 {
             self.cur_mask_word += 1;
         }
     }
     /// Internal method to get the next child from the builder in the push order.  Used by the anamorphism
-    fn take_next(&mut self) -> Option<WOrNode<V, W, A>> {
+    pub(crate) fn take_next(&mut self) -> Option<WOrNode<V, W, A>> {
         self.child_structs.pop_front().map(|element| core::mem::take(element))
     }
     /// Internal method.  After [Self::take_next] returns `Some`, this method will return the first byte of the
     /// associated path.
-    fn taken_child_byte(&mut self) -> u8 {
+    pub(crate) fn taken_child_byte(&mut self) -> u8 {
         let least_component = self.child_mask[self.cur_mask_word].trailing_zeros() as u8;
         debug_assert!(least_component < 64);
         let byte = (self.cur_mask_word * 64) as u8 + least_component;
@@ -1035,7 +1035,7 @@ impl<V: Clone + Send + Sync, W: Default, A: Allocator> TrieBuilder<V, W, A> {
     }
     /// Internal method.  After [Self::take_next] returns `Some`, this method will return the associated path
     /// beyond the first byte, or `None` if the path is only 1-byte long
-    fn taken_child_remaining_path(&mut self, byte: u8) -> Option<&[u8]> {
+    pub(crate) fn taken_child_remaining_path(&mut self, byte: u8) -> Option<&[u8]> {
         if self.child_paths.get(0).map(|path| path[0]) != Some(byte) {
             None
         } else {
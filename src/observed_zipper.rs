@@ -0,0 +1,374 @@
+use core::marker::PhantomData;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::{Allocator, GlobalAlloc};
+use crate::ring::{AlgebraicStatus, Lattice};
+use crate::trie_map::BytesTrieMap;
+use crate::utils::ByteMask;
+use crate::zipper::*;
+
+/// The kind of change described by a [ChangeEvent]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChangeKind {
+    /// A value was set at the event's path
+    ValueSet,
+    /// A value was removed from the event's path
+    ValueRemoved,
+    /// The subtrie below the event's path was replaced or joined with another subtrie
+    SubtrieGrafted,
+    /// The subtrie below the event's path was removed
+    SubtrieRemoved,
+}
+
+impl ChangeKind {
+    /// Returns `true` if the change affects the paths below the event's path
+    pub fn affects_subtrie(&self) -> bool {
+        matches!(self, Self::SubtrieGrafted | Self::SubtrieRemoved)
+    }
+}
+
+/// A change to a trie, delivered to the subscribers of a [ChangeNotifier]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChangeEvent<'a> {
+    /// The kind of change
+    pub kind: ChangeKind,
+    /// The path to the change, from the root of the trie
+    pub path: &'a [u8],
+}
+
+/// Identifies a subscription registered with a [ChangeNotifier]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type ChangeCallback = Arc<dyn Fn(&ChangeEvent) + Send + Sync>;
+
+#[derive(Default)]
+struct Subscribers {
+    by_prefix: BytesTrieMap<Vec<(SubscriptionId, ChangeCallback)>>,
+    prefixes: HashMap<SubscriptionId, Vec<u8>>,
+}
+
+/// A registry of subscribers that are notified of changes under path prefixes
+///
+/// A subscriber registered at a prefix receives every event at a path that begins with the prefix.  It also
+/// receives events for changes to a subtrie that contains the prefix, such as removing the branches at a
+/// shorter path.
+///
+/// Changes made through an [ObservedZipper] are reported to its notifier after each operation.  A notifier
+/// can be shared by zippers from the same [ZipperHead] across threads, because the events carry paths from
+/// the root of the trie, rather than from the root of each zipper.
+///
+/// When there are no subscribers, sending an event costs a single atomic load.
+///
+/// ```
+/// # use pathmap::trie_map::BytesTrieMap;
+/// # use pathmap::zipper::*;
+/// # use std::sync::{Arc, Mutex};
+/// let notifier = ChangeNotifier::new();
+/// let changed = Arc::new(Mutex::new(vec![]));
+/// let changed_ref = changed.clone();
+/// notifier.subscribe(b"index:", move |event| changed_ref.lock().unwrap().push(event.path.to_vec()));
+///
+/// let mut map = BytesTrieMap::<usize>::new();
+/// let mut z = ObservedZipper::new(map.write_zipper(), &notifier);
+/// z.descend_to(b"index:a");
+/// z.set_value(1);
+/// z.reset();
+/// z.descend_to(b"other");
+/// z.set_value(2);
+/// assert_eq!(*changed.lock().unwrap(), vec![b"index:a".to_vec()]);
+/// ```
+#[derive(Default)]
+pub struct ChangeNotifier {
+    subscriber_cnt: AtomicUsize,
+    next_id: AtomicU64,
+    subscribers: RwLock<Subscribers>,
+}
+
+impl ChangeNotifier {
+    /// Creates a new `ChangeNotifier` with no subscribers
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Registers `callback` to receive the events that affect paths beginning with `prefix`
+    ///
+    /// The callback is invoked on the thread that made the change, after the change is made.  Callbacks
+    /// may subscribe and unsubscribe, but must not make changes through an `ObservedZipper` that shares
+    /// this notifier, unless the changes can't affect the callback's own prefix.
+    pub fn subscribe<K: AsRef<[u8]>, F: Fn(&ChangeEvent) + Send + Sync + 'static>(&self, prefix: K, callback: F) -> SubscriptionId {
+        let prefix = prefix.as_ref();
+        let id = SubscriptionId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let mut subscribers = self.subscribers.write().unwrap();
+        let mut wz = subscribers.by_prefix.write_zipper_at_path(prefix);
+        wz.get_value_or_insert_with(Vec::new).push((id, Arc::new(callback)));
+        drop(wz);
+        subscribers.prefixes.insert(id, prefix.to_vec());
+        self.subscriber_cnt.fetch_add(1, Ordering::Release);
+        id
+    }
+    /// Removes a subscription.  Returns `false` if the subscription didn't exist
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.subscribers.write().unwrap();
+        let prefix = match subscribers.prefixes.remove(&id) {
+            Some(prefix) => prefix,
+            None => return false
+        };
+        let mut wz = subscribers.by_prefix.write_zipper_at_path(&prefix);
+        if let Some(callbacks) = wz.get_value_mut() {
+            callbacks.retain(|(callback_id, _)| *callback_id != id);
+            if callbacks.is_empty() {
+                wz.remove_value();
+            }
+        }
+        self.subscriber_cnt.fetch_sub(1, Ordering::Release);
+        true
+    }
+    /// Returns the number of subscriptions
+    pub fn subscriber_count(&self) -> usize {
+        self.subscriber_cnt.load(Ordering::Acquire)
+    }
+    /// Sends an event to the subscribers it affects
+    ///
+    /// This is called by [ObservedZipper] after each change, but may also be called to report changes made
+    /// by other means.
+    pub fn notify(&self, kind: ChangeKind, path: &[u8]) {
+        if self.subscriber_cnt.load(Ordering::Acquire) == 0 {
+            return
+        }
+        let callbacks = self.affected_callbacks(kind, path);
+        let event = ChangeEvent { kind, path };
+        for callback in callbacks {
+            callback(&event);
+        }
+    }
+    /// Collects the callbacks affected by an event, so they can be invoked after releasing the lock
+    fn affected_callbacks(&self, kind: ChangeKind, path: &[u8]) -> Vec<ChangeCallback> {
+        let subscribers = self.subscribers.read().unwrap();
+        let mut callbacks = vec![];
+        let mut rz = subscribers.by_prefix.read_zipper();
+        let mut collect = |subs: Option<&Vec<(SubscriptionId, ChangeCallback)>>| {
+            if let Some(subs) = subs {
+                callbacks.extend(subs.iter().map(|(_, callback)| callback.clone()));
+            }
+        };
+
+        //Subscribers at the path and above it
+        collect(rz.get_value());
+        for &byte in path {
+            rz.descend_to_byte(byte);
+            if !rz.path_exists() {
+                return callbacks
+            }
+            collect(rz.get_value());
+        }
+
+        //Subscribers below the path, if the change affects the whole subtrie
+        if kind.affects_subtrie() {
+            let mut below = rz.fork_read_zipper();
+            while let Some(subs) = below.to_next_get_value() {
+                collect(Some(subs));
+            }
+        }
+        callbacks
+    }
+}
+
+/// Wraps a [write zipper](ZipperWriting), and reports each change it makes to a [ChangeNotifier]
+///
+/// Only the changes made through the methods of `ObservedZipper` are reported.  See [ChangeNotifier] for
+/// an example.
+pub struct ObservedZipper<'n, Z, V, A = GlobalAlloc> {
+    z: Z,
+    notifier: &'n ChangeNotifier,
+    _v: PhantomData<(V, A)>,
+}
+
+impl<'n, Z, V, A> ObservedZipper<'n, Z, V, A>
+    where
+    Z: ZipperWriting<V, A> + ZipperAbsolutePath,
+    V: Clone + Send + Sync + Unpin,
+    A: Allocator,
+{
+    /// Creates a new `ObservedZipper`, reporting the changes made through `z` to `notifier`
+    pub fn new(z: Z, notifier: &'n ChangeNotifier) -> Self {
+        Self { z, notifier, _v: PhantomData }
+    }
+    /// Consumes the `ObservedZipper`, and returns the wrapped zipper
+    pub fn into_inner(self) -> Z {
+        self.z
+    }
+    /// Returns the notifier that receives this zipper's changes
+    pub fn notifier(&self) -> &'n ChangeNotifier {
+        self.notifier
+    }
+    /// Observed version of [ZipperWriting::set_value]
+    pub fn set_value(&mut self, val: V) -> Option<V> {
+        let replaced = self.z.set_value(val);
+        self.notify(ChangeKind::ValueSet);
+        replaced
+    }
+    /// Observed version of [ZipperWriting::remove_value]
+    pub fn remove_value(&mut self) -> Option<V> {
+        let removed = self.z.remove_value();
+        if removed.is_some() {
+            self.notify(ChangeKind::ValueRemoved);
+        }
+        removed
+    }
+    /// Observed version of [ZipperWriting::graft]
+    pub fn graft<RZ: ZipperSubtries<V, A>>(&mut self, read_zipper: &RZ) {
+        self.z.graft(read_zipper);
+        self.notify(ChangeKind::SubtrieGrafted);
+    }
+    /// Observed version of [ZipperWriting::graft_map]
+    pub fn graft_map(&mut self, map: BytesTrieMap<V, A>) {
+        self.z.graft_map(map);
+        self.notify(ChangeKind::SubtrieGrafted);
+    }
+    /// Observed version of [ZipperWriting::join]
+    pub fn join<RZ: ZipperSubtries<V, A>>(&mut self, read_zipper: &RZ) -> AlgebraicStatus where V: Lattice {
+        let status = self.z.join(read_zipper);
+        if matches!(status, AlgebraicStatus::Element) {
+            self.notify(ChangeKind::SubtrieGrafted);
+        }
+        status
+    }
+    /// Observed version of [ZipperWriting::remove_branches]
+    pub fn remove_branches(&mut self) -> bool {
+        let removed = self.z.remove_branches();
+        if removed {
+            self.notify(ChangeKind::SubtrieRemoved);
+        }
+        removed
+    }
+    fn notify(&self, kind: ChangeKind) {
+        self.notifier.notify(kind, self.z.origin_path());
+    }
+}
+
+impl<Z: Zipper, V, A> Zipper for ObservedZipper<'_, Z, V, A> {
+    fn path_exists(&self) -> bool { self.z.path_exists() }
+    fn is_value(&self) -> bool { self.z.is_value() }
+    fn child_count(&self) -> usize { self.z.child_count() }
+    fn child_mask(&self) -> ByteMask { self.z.child_mask() }
+}
+
+impl<Z: ZipperValues<V>, V, A> ZipperValues<V> for ObservedZipper<'_, Z, V, A> {
+    fn value(&self) -> Option<&V> { self.z.value() }
+}
+
+impl<Z: ZipperMoving, V, A> ZipperMoving for ObservedZipper<'_, Z, V, A> {
+    crate::zipper::forward_zipper_moving!();
+}
+
+impl<Z: ZipperAbsolutePath, V, A> ZipperAbsolutePath for ObservedZipper<'_, Z, V, A> {
+    fn origin_path(&self) -> &[u8] { self.z.origin_path() }
+    fn root_prefix_path(&self) -> &[u8] { self.z.root_prefix_path() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use crate::trie_map::BytesTrieMap;
+    use crate::zipper::*;
+
+    type EventLog = Arc<Mutex<Vec<(&'static str, ChangeKind, Vec<u8>)>>>;
+
+    fn subscribe(notifier: &ChangeNotifier, log: &EventLog, name: &'static str, prefix: &[u8]) -> SubscriptionId {
+        let log = log.clone();
+        notifier.subscribe(prefix, move |event| log.lock().unwrap().push((name, event.kind, event.path.to_vec())))
+    }
+
+    fn take_events(log: &EventLog) -> Vec<(&'static str, ChangeKind, Vec<u8>)> {
+        let mut events = core::mem::take(&mut *log.lock().unwrap());
+        events.sort();
+        events
+    }
+
+    #[test]
+    fn observed_zipper_test1() {
+        let notifier = ChangeNotifier::new();
+        let log = EventLog::default();
+        let all = subscribe(&notifier, &log, "all", b"");
+        let users = subscribe(&notifier, &log, "users", b"user:");
+        subscribe(&notifier, &log, "alice", b"user:alice");
+        assert_eq!(notifier.subscriber_count(), 3);
+
+        let mut map = BytesTrieMap::<usize>::new();
+        let src = BytesTrieMap::<usize>::from_iter([("a", 1), ("b", 2)]);
+        let mut z = ObservedZipper::new(map.write_zipper(), &notifier);
+        z.descend_to(b"user:bob");
+        z.set_value(1);
+        assert_eq!(take_events(&log), vec![
+            ("all", ChangeKind::ValueSet, b"user:bob".to_vec()),
+            ("users", ChangeKind::ValueSet, b"user:bob".to_vec()),
+        ]);
+
+        z.reset();
+        z.descend_to(b"user:alice:friends:");
+        z.graft(&src.read_zipper());
+        z.reset();
+        z.descend_to(b"group:");
+        z.join(&src.read_zipper());
+        assert_eq!(take_events(&log), vec![
+            ("alice", ChangeKind::SubtrieGrafted, b"user:alice:friends:".to_vec()),
+            ("all", ChangeKind::SubtrieGrafted, b"group:".to_vec()),
+            ("all", ChangeKind::SubtrieGrafted, b"user:alice:friends:".to_vec()),
+            ("users", ChangeKind::SubtrieGrafted, b"user:alice:friends:".to_vec()),
+        ]);
+
+        //Operations that don't change anything aren't reported
+        z.join(&src.read_zipper());
+        z.reset();
+        z.descend_to(b"user:nobody");
+        assert_eq!(z.remove_value(), None);
+        assert!(take_events(&log).is_empty());
+
+        //Removing a subtrie notifies the subscribers below it
+        z.reset();
+        z.descend_to(b"user");
+        assert!(z.remove_branches());
+        assert_eq!(take_events(&log), vec![
+            ("alice", ChangeKind::SubtrieRemoved, b"user".to_vec()),
+            ("all", ChangeKind::SubtrieRemoved, b"user".to_vec()),
+            ("users", ChangeKind::SubtrieRemoved, b"user".to_vec()),
+        ]);
+
+        assert!(notifier.unsubscribe(all));
+        assert!(!notifier.unsubscribe(all));
+        assert!(notifier.unsubscribe(users));
+        z.reset();
+        z.descend_to(b"group:a");
+        assert_eq!(z.remove_value(), Some(1));
+        assert!(take_events(&log).is_empty());
+        assert_eq!(notifier.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn observed_zipper_head_test() {
+        let notifier = ChangeNotifier::new();
+        let log = EventLog::default();
+        subscribe(&notifier, &log, "users", b"user:");
+
+        let mut map = BytesTrieMap::<usize>::new();
+        let zipper_head = map.zipper_head();
+        let user_z = zipper_head.write_zipper_at_exclusive_path(b"user:").unwrap();
+        let group_z = zipper_head.write_zipper_at_exclusive_path(b"group:").unwrap();
+        std::thread::scope(|scope| {
+            let notifier = &notifier;
+            scope.spawn(move || {
+                let mut z = ObservedZipper::new(user_z, notifier);
+                z.descend_to(b"carol");
+                z.set_value(3);
+            });
+            scope.spawn(move || {
+                let mut z = ObservedZipper::new(group_z, notifier);
+                z.descend_to(b"staff");
+                z.set_value(4);
+            });
+        });
+        assert_eq!(take_events(&log), vec![("users", ChangeKind::ValueSet, b"user:carol".to_vec())]);
+    }
+}
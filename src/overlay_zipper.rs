@@ -0,0 +1,416 @@
+use core::cell::OnceCell;
+
+use crate::Allocator;
+use crate::utils::{BitMask, ByteMask};
+use crate::ring::{AlgebraicResult, Lattice};
+use crate::trie_map::BytesTrieMap;
+use crate::trie_node::{AbstractNodeRef, TrieNode};
+use crate::zipper::*;
+use crate::zipper::zipper_priv::ZipperPriv;
+
+/// A virtual [Zipper] that presents the union of several tries, without materializing the union
+///
+/// Each of the underlying zippers, called layers, is moved in lockstep with the `OverlayZipper`.  A path
+/// exists in the overlay if it exists in any layer, and the [child_mask](Zipper::child_mask) is the union
+/// of the layers' masks.
+///
+/// When more than one layer has a value at the focus, the values are resolved according to how the
+/// overlay was created.  Either the value from the layer with the lowest index takes priority, or the
+/// values are combined with a function such as [Lattice::pjoin].
+///
+/// When the layers implement [ZipperSubtries], the `OverlayZipper` can be used as the source for
+/// [ZipperWriting::graft], [ZipperWriting::join], and the other algebraic operations.  The union below the
+/// focus is materialized for each such operation, sharing structure with the last layer, so the cost is
+/// proportional to the size of the earlier layers.  This makes it cheap to apply a small delta on top of
+/// a large base.
+pub struct OverlayZipper<V, Z> {
+    layers: Vec<Z>,
+    /// Combines the values from two layers.  `None` means the value from the earlier layer takes priority
+    combine: Option<fn(&V, &V) -> AlgebraicResult<V>>,
+    /// The combined value at the focus, computed the first time it is requested
+    combined_value: OnceCell<Option<V>>,
+}
+
+impl<V: Clone + Send + Sync + Unpin, Z: ZipperMoving + ZipperValues<V>> OverlayZipper<V, Z> {
+    /// Creates a new `OverlayZipper` where values from earlier layers take priority over later layers
+    ///
+    /// Each zipper is reset to its root, which becomes the root of the `OverlayZipper`.  Panics if `layers`
+    /// is empty.
+    pub fn new<I: IntoIterator<Item=Z>>(layers: I) -> Self {
+        Self::new_internal(layers, None)
+    }
+    /// Creates a new `OverlayZipper` where values found in multiple layers are combined using [Lattice::pjoin]
+    pub fn new_with_join<I: IntoIterator<Item=Z>>(layers: I) -> Self where V: Lattice {
+        Self::new_internal(layers, Some(<V as Lattice>::pjoin))
+    }
+    /// Creates a new `OverlayZipper` where values found in multiple layers are combined using `combine`
+    ///
+    /// `combine` is called with the value from the earlier layer as its first argument, and is applied from
+    /// the first layer to the last.  An [AlgebraicResult::None] result discards the value, so a later
+    /// layer's value will be used if there is one.  A path where the last combination is `None` has no
+    /// value in the overlay.
+    pub fn new_with_combine<I: IntoIterator<Item=Z>>(layers: I, combine: fn(&V, &V) -> AlgebraicResult<V>) -> Self {
+        Self::new_internal(layers, Some(combine))
+    }
+    fn new_internal<I: IntoIterator<Item=Z>>(layers: I, combine: Option<fn(&V, &V) -> AlgebraicResult<V>>) -> Self {
+        let mut layers: Vec<Z> = layers.into_iter().collect();
+        assert!(!layers.is_empty(), "OverlayZipper requires at least one layer");
+        for layer in layers.iter_mut() {
+            layer.reset();
+        }
+        Self { layers, combine, combined_value: OnceCell::new() }
+    }
+    /// Returns the number of layers composing the `OverlayZipper`
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+    /// Returns a reference to the layer at `idx`, so its value can be inspected without resolution
+    pub fn layer(&self, idx: usize) -> &Z {
+        &self.layers[idx]
+    }
+    /// Consumes the `OverlayZipper`, and returns the underlying layers
+    pub fn into_layers(self) -> Vec<Z> {
+        self.layers
+    }
+    #[inline]
+    fn invalidate_value(&mut self) {
+        self.combined_value.take();
+    }
+}
+
+impl<V: Clone, Z: Zipper + ZipperValues<V>> Zipper for OverlayZipper<V, Z> {
+    fn path_exists(&self) -> bool {
+        self.layers.iter().any(|layer| layer.path_exists())
+    }
+    fn is_value(&self) -> bool {
+        match self.combine {
+            Some(_) => self.value().is_some(),
+            None => self.layers.iter().any(|layer| layer.is_value()),
+        }
+    }
+    fn child_count(&self) -> usize {
+        self.child_mask().count_bits()
+    }
+    fn child_mask(&self) -> ByteMask {
+        self.layers.iter().fold(ByteMask::EMPTY, |mask, layer| mask | layer.child_mask())
+    }
+}
+
+impl<V: Clone, Z: Zipper + ZipperValues<V>> ZipperValues<V> for OverlayZipper<V, Z> {
+    fn value(&self) -> Option<&V> {
+        let mut layer_vals = self.layers.iter().filter_map(|layer| layer.value()).peekable();
+        let first = layer_vals.next()?;
+        if self.combine.is_none() || layer_vals.peek().is_none() {
+            return Some(first)
+        }
+        self.combined_value.get_or_init(|| {
+            resolve_layer_values(self.combine, core::iter::once(first).chain(layer_vals))
+        }).as_ref()
+    }
+}
+
+impl<V: Clone, Z: ZipperMoving + ZipperValues<V> + ZipperForking<V>> ZipperMoving for OverlayZipper<V, Z> {
+    fn at_root(&self) -> bool {
+        self.layers[0].at_root()
+    }
+    fn reset(&mut self) {
+        self.invalidate_value();
+        for layer in self.layers.iter_mut() {
+            layer.reset();
+        }
+    }
+    fn path(&self) -> &[u8] {
+        self.layers[0].path()
+    }
+    fn val_count(&self) -> usize {
+        let mut forks: Vec<_> = self.layers.iter().map(|layer| layer.fork_read_zipper()).collect();
+        overlay_val_count(self.combine, &mut forks)
+    }
+    fn descend_to<K: AsRef<[u8]>>(&mut self, k: K) -> bool {
+        self.invalidate_value();
+        let k = k.as_ref();
+        self.layers.iter_mut().fold(false, |exists, layer| layer.descend_to(k) | exists)
+    }
+    fn descend_to_byte(&mut self, k: u8) -> bool {
+        self.invalidate_value();
+        self.layers.iter_mut().fold(false, |exists, layer| layer.descend_to_byte(k) | exists)
+    }
+    fn ascend(&mut self, steps: usize) -> bool {
+        self.invalidate_value();
+        self.layers.iter_mut().fold(true, |moved, layer| layer.ascend(steps) & moved)
+    }
+    fn ascend_byte(&mut self) -> bool {
+        self.invalidate_value();
+        self.layers.iter_mut().fold(true, |moved, layer| layer.ascend_byte() & moved)
+    }
+    fn ascend_until(&mut self) -> bool {
+        if self.at_root() {
+            return false
+        }
+        loop {
+            self.ascend_byte();
+            if self.child_count() > 1 || self.is_value() || self.at_root() {
+                return true
+            }
+        }
+    }
+    fn ascend_until_branch(&mut self) -> bool {
+        if self.at_root() {
+            return false
+        }
+        loop {
+            self.ascend_byte();
+            if self.child_count() > 1 || self.at_root() {
+                return true
+            }
+        }
+    }
+}
+
+impl<V: Clone, Z: ZipperMoving + ZipperValues<V> + ZipperForking<V>> ZipperIteration for OverlayZipper<V, Z> { }
+
+impl<V: Clone + Send + Sync + Unpin, A: Allocator, Z: ZipperMoving + ZipperSubtries<V, A>> ZipperSubtries<V, A> for OverlayZipper<V, Z> {
+    fn make_map(&self) -> Option<BytesTrieMap<V, A>> {
+        let layer_maps: Vec<Option<BytesTrieMap<V, A>>> = self.layers.iter().map(|layer| layer.make_map()).collect();
+        let (last_map, earlier_maps) = layer_maps.split_last().unwrap();
+        let mut result = match last_map {
+            Some(last_map) => last_map.clone(),
+            None => BytesTrieMap::new_in(earlier_maps.iter().flatten().next()?.alloc.clone()),
+        };
+        //Only the paths with a value in an earlier layer can differ from the last layer
+        let mut wz = result.write_zipper();
+        for layer_map in earlier_maps.iter().flatten() {
+            for (path, _) in layer_map.iter() {
+                wz.move_to_path(&path);
+                let layer_vals = layer_maps.iter().flatten().filter_map(|map| map.get(&path));
+                match resolve_layer_values(self.combine, layer_vals) {
+                    Some(new_val) => { wz.set_value(new_val); },
+                    None => { wz.remove_value(); }
+                }
+            }
+        }
+        drop(wz);
+        if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, A: Allocator, Z> ZipperPriv for OverlayZipper<V, Z>
+    where Z: ZipperMoving + ZipperPriv<V=V, A=A> + ZipperSubtries<V, A>
+{
+    type V = V;
+    type A = A;
+    fn get_focus(&self) -> AbstractNodeRef<'_, V, A> {
+        match <Self as ZipperSubtries<V, A>>::make_map(self).and_then(|map| map.into_root().0) {
+            Some(root) => AbstractNodeRef::OwnedRc(root),
+            None => AbstractNodeRef::None,
+        }
+    }
+    fn try_borrow_focus(&self) -> Option<&dyn TrieNode<V, A>> {
+        //The union doesn't exist as a node in any underlying trie
+        None
+    }
+}
+
+/// Resolves the values that the layers have at one path into the value of the overlay
+///
+/// This is the single place the resolution rule lives.  Without a `combine` function the value from the
+/// earliest layer takes priority.  Otherwise the values are folded from the first layer to the last, and an
+/// [AlgebraicResult::None] result discards the value accumulated so far, so the next layer's value starts
+/// a new accumulation.
+fn resolve_layer_values<'a, V: Clone + 'a, I>(combine: Option<fn(&V, &V) -> AlgebraicResult<V>>, layer_vals: I) -> Option<V>
+    where I: IntoIterator<Item=&'a V>
+{
+    let mut layer_vals = layer_vals.into_iter();
+    let combine = match combine {
+        Some(combine) => combine,
+        None => return layer_vals.next().cloned(),
+    };
+    let mut acc: Option<V> = None;
+    for next in layer_vals {
+        acc = match acc {
+            Some(acc) => combine(&acc, next).into_option([&acc, next]),
+            None => Some(next.clone()),
+        };
+    }
+    acc
+}
+
+/// Counts the values at and below the focus of the union of `layers`, which must all be at the same path
+fn overlay_val_count<V: Clone, Z: ZipperMoving + ZipperValues<V>>(combine: Option<fn(&V, &V) -> AlgebraicResult<V>>, layers: &mut [Z]) -> usize {
+    let has_value = match combine {
+        Some(_) => resolve_layer_values(combine, layers.iter().filter_map(|layer| layer.value())).is_some(),
+        None => layers.iter().any(|layer| layer.is_value()),
+    };
+    let mut count = has_value as usize;
+    let mask = layers.iter().fold(ByteMask::EMPTY, |mask, layer| mask | layer.child_mask());
+    for byte in mask.iter() {
+        for layer in layers.iter_mut() {
+            layer.descend_to_byte(byte);
+        }
+        count += overlay_val_count(combine, layers);
+        for layer in layers.iter_mut() {
+            layer.ascend_byte();
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ring::{AlgebraicResult, SELF_IDENT};
+    use crate::trie_map::BytesTrieMap;
+    use crate::zipper::*;
+
+    #[test]
+    fn overlay_zipper_test1() {
+        let base = BytesTrieMap::from_iter([("apple", 1), ("banana", 2), ("cherry", 3)]);
+        let delta = BytesTrieMap::from_iter([("banana", 20), ("bandana", 40), ("date", 4)]);
+        let mut oz = OverlayZipper::new([delta.read_zipper(), base.read_zipper()]);
+
+        assert_eq!(oz.layer_count(), 2);
+        assert_eq!(oz.child_mask().iter().collect::<Vec<u8>>(), vec![b'a', b'b', b'c', b'd']);
+        assert_eq!(oz.val_count(), 5);
+
+        assert!(oz.descend_to(b"ban"));
+        assert_eq!(oz.child_count(), 2);
+        assert!(oz.descend_to(b"ana"));
+        assert_eq!(oz.value(), Some(&20));
+        oz.reset();
+        assert!(oz.descend_to(b"cherry"));
+        assert_eq!(oz.value(), Some(&3));
+        oz.reset();
+        assert!(!oz.descend_to(b"egg"));
+        assert!(!oz.path_exists());
+
+        oz.reset();
+        let mut paths = vec![];
+        while oz.to_next_val() {
+            paths.push((oz.path().to_vec(), *oz.value().unwrap()));
+        }
+        assert_eq!(paths, vec![(b"apple".to_vec(), 1), (b"banana".to_vec(), 20), (b"bandana".to_vec(), 40), (b"cherry".to_vec(), 3), (b"date".to_vec(), 4)]);
+    }
+
+    #[test]
+    fn overlay_zipper_combine_test() {
+        let a = BytesTrieMap::from_iter([("one", 1u64), ("two", 2)]);
+        let b = BytesTrieMap::from_iter([("two", 20u64), ("three", 30)]);
+        let c = BytesTrieMap::from_iter([("two", 200u64)]);
+        let mut oz = OverlayZipper::new_with_combine([a.read_zipper(), b.read_zipper(), c.read_zipper()],
+            |x, y| AlgebraicResult::Element(x + y));
+
+        assert!(oz.descend_to(b"two"));
+        assert_eq!(oz.value(), Some(&222));
+        assert!(oz.ascend(3));
+        assert!(oz.descend_to(b"three"));
+        assert_eq!(oz.value(), Some(&30));
+
+        //Always keeping the first argument behaves the same as a priority overlay
+        let mut oz = OverlayZipper::new_with_combine([a.read_zipper(), b.read_zipper()], |_, _| AlgebraicResult::Identity(SELF_IDENT));
+        assert!(oz.descend_to(b"two"));
+        assert_eq!(oz.value(), Some(&2));
+    }
+
+    #[test]
+    fn overlay_zipper_annihilate_test() {
+        //Equal values annihilate, so a path can exist in the overlay without a value
+        fn cancel(x: &u64, y: &u64) -> AlgebraicResult<u64> {
+            if x == y { AlgebraicResult::None } else { AlgebraicResult::Element(x + y) }
+        }
+        let a = BytesTrieMap::from_iter([("k", 1u64), ("m", 5)]);
+        let b = BytesTrieMap::from_iter([("k", 1u64), ("n", 2)]);
+        let mut oz = OverlayZipper::new_with_combine([a.read_zipper(), b.read_zipper()], cancel);
+
+        assert!(oz.descend_to(b"k"));
+        assert!(oz.path_exists());
+        assert!(!oz.is_value());
+        assert_eq!(oz.value(), None);
+        oz.reset();
+        assert_eq!(oz.val_count(), 2);
+        let mut paths = vec![];
+        while oz.to_next_val() {
+            paths.push(oz.path().to_vec());
+        }
+        assert_eq!(paths, vec![b"m".to_vec(), b"n".to_vec()]);
+        oz.reset();
+        let map = oz.make_map().unwrap();
+        assert_eq!(map.iter().map(|(path, val)| (path, *val)).collect::<Vec<_>>(), vec![(b"m".to_vec(), 5), (b"n".to_vec(), 2)]);
+
+        //A later layer's value starts over after an annihilation, in every method
+        let c = BytesTrieMap::from_iter([("k", 7u64)]);
+        let mut oz = OverlayZipper::new_with_combine([a.read_zipper(), b.read_zipper(), c.read_zipper()], cancel);
+        assert_eq!(oz.val_count(), 3);
+        assert_eq!(oz.make_map().unwrap().get(b"k"), Some(&7));
+        assert!(oz.descend_to(b"k"));
+        assert!(oz.is_value());
+        assert_eq!(oz.value(), Some(&7));
+    }
+
+    #[test]
+    fn overlay_zipper_make_map_test() {
+        let base = BytesTrieMap::from_iter([("a:apple", ()), ("a:banana", ()), ("b:cherry", ())]);
+        let delta = BytesTrieMap::from_iter([("a:bandana", ()), ("c:date", ())]);
+
+        let oz = OverlayZipper::new([delta.read_zipper_at_path(b"a:"), base.read_zipper_at_path(b"a:")]);
+        let union = oz.make_map().unwrap();
+        assert_eq!(union.iter().map(|(path, _)| path).collect::<Vec<_>>(), vec![b"apple".to_vec(), b"banana".to_vec(), b"bandana".to_vec()]);
+
+        let mut target = BytesTrieMap::new();
+        let mut wz = target.write_zipper_at_path(b"x:");
+        wz.graft(&oz);
+        drop(wz);
+        assert_eq!(target.val_count(), 3);
+        assert!(target.contains(b"x:bandana"));
+
+        let mut wz = target.write_zipper_at_path(b"y:");
+        wz.join(&oz);
+        drop(wz);
+        assert_eq!(target.val_count(), 6);
+
+        let oz = OverlayZipper::new([delta.read_zipper_at_path(b"z:"), base.read_zipper_at_path(b"z:")]);
+        assert!(oz.make_map().is_none());
+    }
+
+    #[test]
+    fn overlay_zipper_join_test() {
+        let a = BytesTrieMap::from_iter([("k", true), ("x", false)]);
+        let b = BytesTrieMap::from_iter([("k", false), ("x", true)]);
+        let mut oz = OverlayZipper::new_with_join([a.read_zipper(), b.read_zipper()]);
+        assert!(oz.descend_to(b"k"));
+        assert_eq!(oz.value(), Some(&true));
+        assert!(oz.ascend_byte());
+        assert!(oz.descend_to_byte(b'x'));
+        assert_eq!(oz.value(), Some(&true));
+
+        //There is nothing below the focus
+        assert!(oz.make_map().is_none());
+        oz.reset();
+        let joined = oz.make_map().unwrap();
+        assert_eq!(joined.get(b"k"), Some(&true));
+        assert_eq!(joined.get(b"x"), Some(&true));
+    }
+
+    /// Splits the keys between two maps, so the tests exercise the union
+    fn split_keys(keys: &[&[u8]]) -> (BytesTrieMap<()>, BytesTrieMap<()>) {
+        let mut maps = (BytesTrieMap::new(), BytesTrieMap::new());
+        keys.iter().enumerate().for_each(|(idx, k)| {
+            if idx % 2 == 0 { maps.0.insert(k, ()); } else { maps.1.insert(k, ()); }
+        });
+        maps
+    }
+
+    crate::zipper::zipper_moving_tests::zipper_moving_tests!(overlay_zipper,
+        split_keys,
+        |maps: &mut (BytesTrieMap<()>, BytesTrieMap<()>), path: &[u8]| -> _ {
+            OverlayZipper::new([maps.0.read_zipper_at_path(path), maps.1.read_zipper_at_path(path)])
+    });
+
+    crate::zipper::zipper_iteration_tests::zipper_iteration_tests!(overlay_zipper,
+        split_keys,
+        |maps: &mut (BytesTrieMap<()>, BytesTrieMap<()>), path: &[u8]| -> _ {
+            OverlayZipper::new([maps.0.read_zipper_at_path(path), maps.1.read_zipper_at_path(path)])
+    });
+}
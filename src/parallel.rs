@@ -0,0 +1,573 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::Allocator;
+use crate::morphisms::Catamorphism;
+use crate::ring::{DistributiveLattice, Lattice};
+use crate::trie_map::BytesTrieMap;
+use crate::utils::{BitMask, ByteMask};
+use crate::zipper::*;
+use crate::zipper::zipper_priv::{FocusAddr, ZipperConcretePriv};
+use crate::zipper_tracking::Conflict;
+
+/// Calls `f` on a separate [WriteZipperTracked] for each branch `depth` bytes below the root of a
+/// [ZipperHead], spreading the branches across threads
+///
+/// See [par_map_reduce] for how the branches are found and balanced.
+pub fn par_for_each_branch<'a, 'trie: 'a, Z, V, A, F>(zipper_head: &'a Z, depth: usize, f: F) -> Result<(), Conflict>
+    where
+    Z: ZipperCreation<'trie, V, A>,
+    V: Clone + Send + Sync + Unpin + 'trie,
+    A: Allocator + 'trie,
+    F: Fn(&mut WriteZipperTracked<'_, 'static, V, A>) + Sync,
+{
+    par_map_reduce(zipper_head, depth, f, |(), ()| ()).map(|_| ())
+}
+
+/// Calls `map_f` on a separate [WriteZipperTracked] for each branch `depth` bytes below the root of a
+/// [ZipperHead], spreading the branches across threads, and combines the results with `reduce_f`
+///
+/// The branches are the existing paths that are exactly `depth` bytes long, so values on shorter paths
+/// aren't visited.  Branches are assigned to threads according to the number of values they contain, rather
+/// than evenly by count, so a few large branches don't hold up the whole operation.  Values are only counted
+/// up to a fixed limit per branch, so weighing the branches doesn't cost a traversal of the whole trie.  The
+/// results are combined in no particular order, so `reduce_f` should be associative and commutative.
+/// Returns `None` if there are no branches at `depth`.
+///
+/// Write zippers for all of the branches are acquired together with [ZipperCreation::acquire_branches]
+/// before any thread starts, and returns a [Conflict] if any branch is unavailable.  Each zipper is cleaned
+/// up with [ZipperCreation::cleanup_write_zipper] after `map_f` returns.
+pub fn par_map_reduce<'a, 'trie: 'a, Z, V, A, T, MapF, ReduceF>(zipper_head: &'a Z, depth: usize, map_f: MapF, reduce_f: ReduceF) -> Result<Option<T>, Conflict>
+    where
+    Z: ZipperCreation<'trie, V, A>,
+    V: Clone + Send + Sync + Unpin + 'trie,
+    A: Allocator + 'trie,
+    T: Send,
+    MapF: Fn(&mut WriteZipperTracked<'_, 'static, V, A>) -> T + Sync,
+    ReduceF: Fn(T, T) -> T + Sync,
+{
+    let zippers = zipper_head.acquire_branches(depth)?;
+    if zippers.is_empty() {
+        return Ok(None)
+    }
+
+    //Greedily assign the heaviest remaining branch to the least-loaded thread
+    let thread_cnt = thread::available_parallelism().map_or(1, |n| n.get()).min(zippers.len());
+    let mut weighted: Vec<_> = zippers.into_iter().map(|zipper| {
+        let weight = bounded_val_count(zipper.fork_read_zipper(), MAX_BRANCH_WEIGHT);
+        (zipper, weight)
+    }).collect();
+    weighted.sort_by_key(|(_, weight)| Reverse(*weight));
+    let mut bins: Vec<(usize, Vec<WriteZipperTracked<'a, 'static, V, A>>)> = (0..thread_cnt).map(|_| (0, vec![])).collect();
+    for (zipper, weight) in weighted {
+        let bin = bins.iter_mut().min_by_key(|(load, _)| *load).unwrap();
+        bin.0 += weight.max(1);
+        bin.1.push(zipper);
+    }
+
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = bins.into_iter().map(|(_, mut zippers)| {
+            let map_f = &map_f;
+            let reduce_f = &reduce_f;
+            scope.spawn(move || {
+                let result = zippers.iter_mut()
+                    .map(map_f)
+                    .reduce(reduce_f);
+                (result, zippers)
+            })
+        }).collect();
+        handles.into_iter().map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))).collect()
+    });
+
+    let mut total = None;
+    for (result, zippers) in results {
+        for zipper in zippers {
+            zipper_head.cleanup_write_zipper(zipper);
+        }
+        total = match (total, result) {
+            (Some(total), Some(result)) => Some(reduce_f(total, result)),
+            (total, result) => total.or(result),
+        };
+    }
+    Ok(total)
+}
+
+/// The number of values at which [par_map_reduce] stops counting the values in a branch
+const MAX_BRANCH_WEIGHT: usize = 1 << 16;
+
+/// Returns the number of values below the focus of `z`, or `limit` if there are at least `limit` values
+pub(crate) fn bounded_val_count<Z: ZipperIteration>(mut z: Z, limit: usize) -> usize {
+    let mut cnt = 0;
+    while cnt < limit && z.to_next_val() {
+        cnt += 1;
+    }
+    cnt
+}
+
+/// The minimum number of values for the parallel operations in this module to split their work across threads
+const PAR_THRESHOLD: usize = 1 << 12;
+
+/// Which top-level branches of the arguments to an algebraic operation may appear in the result
+#[derive(Clone, Copy, Debug)]
+enum BranchSelection {
+    /// Branches from either argument, e.g. for `join`
+    Union,
+    /// Branches present in both arguments, e.g. for `meet`
+    Intersection,
+    /// Branches from the first argument, e.g. for `subtract`
+    Left,
+}
+
+/// Returns the number of threads to use for parallel operations
+fn default_thread_cnt() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+impl<V: Clone + Send + Sync + Unpin, A: Allocator> BytesTrieMap<V, A> {
+    /// Same as [join](Self::join), but the top-level branches of large maps are joined on separate threads
+    ///
+    /// Maps with few values are joined on the calling thread, since spawning threads would cost more than it saves.
+    pub fn par_join(&self, other: &Self) -> Self where V: Lattice {
+        par_algebraic_op(self, other, BranchSelection::Union, default_thread_cnt(), PAR_THRESHOLD, &Self::join)
+    }
+
+    /// Same as [meet](Self::meet), but the top-level branches of large maps are intersected on separate threads
+    pub fn par_meet(&self, other: &Self) -> Self where V: Lattice {
+        par_algebraic_op(self, other, BranchSelection::Intersection, default_thread_cnt(), PAR_THRESHOLD, &Self::meet)
+    }
+
+    /// Same as [subtract](Self::subtract), but the top-level branches of large maps are subtracted on separate threads
+    pub fn par_subtract(&self, other: &Self) -> Self where V: DistributiveLattice {
+        par_algebraic_op(self, other, BranchSelection::Left, default_thread_cnt(), PAR_THRESHOLD, &Self::subtract)
+    }
+}
+
+/// Applies `op` to `a` and `b` one top-level branch at a time, combining the branches on up to `thread_cnt`
+/// threads and assembling the results into a single map
+///
+/// `op` must be an operation that acts independently on each branch, in other words the result's branch
+/// at each byte must depend only on the arguments' branches at the same byte.  Branches are balanced
+/// across threads by their number of values, and a branch that receives several threads is split again
+/// at its own top-level branches.  Maps with fewer than `threshold` values are passed to `op` directly.
+///
+/// Each branch's values are only counted up to `threshold`, so the cost of weighing the branches at each
+/// level is bounded, rather than a traversal of both maps.  Branches with at least `threshold` values are
+/// treated as equally heavy.
+fn par_algebraic_op<V, A, OpF>(a: &BytesTrieMap<V, A>, b: &BytesTrieMap<V, A>, selection: BranchSelection, thread_cnt: usize, threshold: usize, op: &OpF) -> BytesTrieMap<V, A>
+    where
+    V: Clone + Send + Sync + Unpin,
+    A: Allocator,
+    OpF: Fn(&BytesTrieMap<V, A>, &BytesTrieMap<V, A>) -> BytesTrieMap<V, A> + Sync,
+{
+    if thread_cnt < 2 {
+        return op(a, b)
+    }
+    let a_mask = a.read_zipper().child_mask();
+    let b_mask = b.read_zipper().child_mask();
+    let mask = match selection {
+        BranchSelection::Union => a_mask | b_mask,
+        BranchSelection::Intersection => a_mask & b_mask,
+        BranchSelection::Left => a_mask,
+    };
+    let mut branches: Vec<_> = mask.iter().map(|byte| {
+        let a_branch = branch_map(a, byte);
+        let b_branch = branch_map(b, byte);
+        let weight = bounded_val_count(a_branch.read_zipper(), threshold) + bounded_val_count(b_branch.read_zipper(), threshold);
+        (byte, a_branch, b_branch, weight)
+    }).collect();
+    let total: usize = branches.iter().map(|(_, _, _, weight)| *weight).sum();
+    if total < threshold {
+        return op(a, b)
+    }
+
+    //Greedily assign the heaviest remaining branch to the least-loaded bin, and give each bin a share
+    // of the threads in proportion to its load
+    branches.sort_by_key(|(_, _, _, weight)| Reverse(*weight));
+    let bin_cnt = thread_cnt.min(branches.len());
+    let mut bins: Vec<(usize, Vec<_>)> = (0..bin_cnt).map(|_| (0, vec![])).collect();
+    for (byte, a_branch, b_branch, weight) in branches {
+        let bin = bins.iter_mut().min_by_key(|(load, _)| *load).unwrap();
+        bin.0 += weight;
+        bin.1.push((byte, a_branch, b_branch));
+    }
+
+    let results: Vec<(u8, BytesTrieMap<V, A>)> = thread::scope(|scope| {
+        let handles: Vec<_> = bins.into_iter().map(|(load, branches)| {
+            let bin_threads = (thread_cnt * load).div_ceil(total.max(1)).max(1);
+            scope.spawn(move || {
+                branches.into_iter().map(|(byte, a_branch, b_branch)| {
+                    (byte, par_algebraic_op(&a_branch, &b_branch, selection, bin_threads, threshold, op))
+                }).collect::<Vec<_>>()
+            })
+        }).collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))).collect()
+    });
+
+    let root_val = op(&root_val_map(a), &root_val_map(b)).into_root().1;
+    let mut result = BytesTrieMap::new_with_root_in(None, root_val, a.alloc.clone());
+    for (byte, branch) in results {
+        graft_branch_map(&mut result, byte, branch);
+    }
+    result
+}
+
+/// Returns the branch of `map` below `byte` as a new map, including the value at `byte` as its root value
+pub(crate) fn branch_map<V: Clone + Send + Sync + Unpin, A: Allocator>(map: &BytesTrieMap<V, A>, byte: u8) -> BytesTrieMap<V, A> {
+    let rz = map.read_zipper_at_path([byte]);
+    let mut branch = rz.make_map().unwrap_or_else(|| BytesTrieMap::new_in(map.alloc.clone()));
+    *branch.root_val_mut() = rz.get_value().cloned();
+    branch
+}
+
+/// Grafts `branch` into `map` below `byte`, and sets the value at `byte` to the root value of `branch`.
+/// The inverse of [branch_map]
+pub(crate) fn graft_branch_map<V: Clone + Send + Sync + Unpin, A: Allocator>(map: &mut BytesTrieMap<V, A>, byte: u8, mut branch: BytesTrieMap<V, A>) {
+    let val = branch.root_val_mut().take();
+    if branch.is_empty() && val.is_none() {
+        return
+    }
+    let path = [byte];
+    let mut wz = map.write_zipper_at_path(&path);
+    if !branch.is_empty() {
+        wz.graft_map(branch);
+    }
+    if let Some(val) = val {
+        wz.set_value(val);
+    }
+}
+
+/// Returns a map containing only the root value of `map`
+fn root_val_map<V: Clone + Send + Sync + Unpin, A: Allocator>(map: &BytesTrieMap<V, A>) -> BytesTrieMap<V, A> {
+    BytesTrieMap::new_with_root_in(None, map.root_val().cloned(), map.alloc.clone())
+}
+
+/// Same as [Catamorphism::into_cata_jumping_side_effect], but independent branches of the trie are
+/// evaluated on separate threads
+///
+/// `alg_f` is called exactly once for each path where the sequential method would call it, with the same
+/// arguments, however the calls for different branches may happen concurrently and in any order.  Branches
+/// with few values are evaluated on a single thread.
+pub fn par_cata_jumping<V, A, W, AlgF>(map: &BytesTrieMap<V, A>, alg_f: AlgF) -> W
+    where
+    V: Clone + Send + Sync + Unpin,
+    A: Allocator,
+    W: Send,
+    AlgF: Fn(&ByteMask, &mut [W], usize, Option<&V>, &[u8]) -> W + Sync,
+{
+    par_cata_jumping_with(map, alg_f, default_thread_cnt(), PAR_THRESHOLD)
+}
+
+/// Same as [Catamorphism::into_cata_jumping_cached], but independent branches of the trie are evaluated
+/// on separate threads
+///
+/// Results for shared subtries are cached in a map that all threads share, so a subtrie that is reachable
+/// from several branches is usually evaluated only once.  The same caveat about the `path` argument as
+/// [Catamorphism::into_cata_jumping_cached] applies.
+pub fn par_cata_jumping_cached<V, A, W, AlgF>(map: &BytesTrieMap<V, A>, alg_f: AlgF) -> W
+    where
+    V: Clone + Send + Sync + Unpin,
+    A: Allocator,
+    W: Clone + Send,
+    AlgF: Fn(&ByteMask, &mut [W], usize, Option<&V>, &[u8]) -> W + Sync,
+{
+    par_cata_jumping_cached_with(map, alg_f, default_thread_cnt(), PAR_THRESHOLD)
+}
+
+/// Implementation of [par_cata_jumping] with an explicit thread count and threshold
+fn par_cata_jumping_with<V, A, W, AlgF>(map: &BytesTrieMap<V, A>, alg_f: AlgF, thread_cnt: usize, threshold: usize) -> W
+    where
+    V: Clone + Send + Sync + Unpin,
+    A: Allocator,
+    W: Send,
+    AlgF: Fn(&ByteMask, &mut [W], usize, Option<&V>, &[u8]) -> W + Sync,
+{
+    let seq_f = |path: &[u8]| {
+        map.read_zipper_at_path(path).into_cata_jumping_side_effect(|mask, children, jump_len, val, path| alg_f(mask, children, jump_len, val, path))
+    };
+    par_cata_body(map, &[], &alg_f, &seq_f, &NoSharedCache, thread_cnt, threshold)
+}
+
+/// Implementation of [par_cata_jumping_cached] with an explicit thread count and threshold
+fn par_cata_jumping_cached_with<V, A, W, AlgF>(map: &BytesTrieMap<V, A>, alg_f: AlgF, thread_cnt: usize, threshold: usize) -> W
+    where
+    V: Clone + Send + Sync + Unpin,
+    A: Allocator,
+    W: Clone + Send,
+    AlgF: Fn(&ByteMask, &mut [W], usize, Option<&V>, &[u8]) -> W + Sync,
+{
+    let seq_f = |path: &[u8]| {
+        map.read_zipper_at_path(path).into_cata_jumping_cached(|mask, children, jump_len, val, path| alg_f(mask, children, jump_len, val, path))
+    };
+    let cache = Mutex::new(HashMap::new());
+    par_cata_body(map, &[], &alg_f, &seq_f, &cache, thread_cnt, threshold)
+}
+
+/// A cache of catamorphism results for shared subtries, which may be accessed from several threads
+trait SharedCataCache<W>: Sync {
+    fn get(&self, addr: FocusAddr) -> Option<W>;
+    fn insert(&self, addr: FocusAddr, w: &W);
+}
+
+/// Caching is disabled
+struct NoSharedCache;
+
+impl<W> SharedCataCache<W> for NoSharedCache {
+    fn get(&self, _addr: FocusAddr) -> Option<W> { None }
+    fn insert(&self, _addr: FocusAddr, _w: &W) {}
+}
+
+impl<W: Clone + Send> SharedCataCache<W> for Mutex<HashMap<FocusAddr, W>> {
+    fn get(&self, addr: FocusAddr) -> Option<W> {
+        self.lock().unwrap().get(&addr).cloned()
+    }
+    fn insert(&self, addr: FocusAddr, w: &W) {
+        self.lock().unwrap().insert(addr, w.clone());
+    }
+}
+
+/// Evaluates a jumping catamorphism over the branch of `map` starting at `start`, which must either be
+/// the root or the path immediately below a point where `alg_f` is called
+///
+/// The branch is descended to its first fork or value, and the branches below that point are evaluated
+/// on up to `thread_cnt` threads before `alg_f` is called on it.  The `jumped_byte_cnt` for that call is
+/// the distance descended from `start`, which is what the sequential catamorphism computes as it ascends.
+/// Branches with fewer than `threshold` values are passed to `seq_f`.
+///
+/// Branches are weighed by their values, counted up to `threshold`.  A branch whose result is already in
+/// `cache` isn't weighed or evaluated again, and a subtrie that appears in several branches only counts
+/// towards the load once, because the other branches are likely to find its result in `cache`.
+fn par_cata_body<V, A, W, AlgF, SeqF, Cache>(map: &BytesTrieMap<V, A>, start: &[u8], alg_f: &AlgF, seq_f: &SeqF, cache: &Cache, thread_cnt: usize, threshold: usize) -> W
+    where
+    V: Clone + Send + Sync + Unpin,
+    A: Allocator,
+    W: Send,
+    AlgF: Fn(&ByteMask, &mut [W], usize, Option<&V>, &[u8]) -> W + Sync,
+    SeqF: Fn(&[u8]) -> W + Sync,
+    Cache: SharedCataCache<W>,
+{
+    if thread_cnt < 2 {
+        return seq_f(start)
+    }
+    let mut z = map.read_zipper_at_path(start);
+    if z.child_count() == 1 && !z.is_value() {
+        z.descend_until();
+    }
+    let child_mask = z.child_mask();
+    if child_mask.is_empty_mask() {
+        return seq_f(start)
+    }
+    let mut results: Vec<(usize, W)> = vec![];
+    let mut branches = Vec::with_capacity(z.child_count());
+    let mut weighed_addrs = HashSet::new();
+    for (idx, byte) in child_mask.iter().enumerate() {
+        z.descend_to_byte(byte);
+        let addr = z.shared_addr();
+        match addr.and_then(|addr| cache.get(addr)) {
+            Some(w) => results.push((idx, w)),
+            None => {
+                let weight = match addr {
+                    Some(addr) if !weighed_addrs.insert(addr) => 0,
+                    _ => bounded_val_count(z.fork_read_zipper(), threshold),
+                };
+                branches.push((idx, z.origin_path().to_vec(), addr, weight));
+            }
+        }
+        z.ascend_byte();
+    }
+    let total: usize = branches.iter().map(|(_, _, _, weight)| *weight).sum();
+    if results.is_empty() && total < threshold {
+        return seq_f(start)
+    }
+
+    let eval_branch = |path: &[u8], addr: Option<FocusAddr>, branch_threads: usize| {
+        let cached = addr.and_then(|addr| cache.get(addr));
+        cached.unwrap_or_else(|| {
+            let w = par_cata_body(map, path, alg_f, seq_f, cache, branch_threads, threshold);
+            if let Some(addr) = addr {
+                cache.insert(addr, &w);
+            }
+            w
+        })
+    };
+    if total < threshold {
+        for (idx, path, addr, _) in branches {
+            results.push((idx, eval_branch(&path, addr, 1)));
+        }
+        return finish_cata_body(&z, child_mask, results, alg_f)
+    }
+
+    //Greedily assign the heaviest remaining branch to the least-loaded bin, and give each bin a share
+    // of the threads in proportion to its load
+    branches.sort_by_key(|(_, _, _, weight)| Reverse(*weight));
+    let bin_cnt = thread_cnt.min(branches.len());
+    let mut bins: Vec<(usize, Vec<_>)> = (0..bin_cnt).map(|_| (0, vec![])).collect();
+    for (idx, path, addr, weight) in branches {
+        let bin = bins.iter_mut().min_by_key(|(load, _)| *load).unwrap();
+        bin.0 += weight;
+        bin.1.push((idx, path, addr));
+    }
+
+    let eval_branch = &eval_branch;
+    let thread_results: Vec<(usize, W)> = thread::scope(|scope| {
+        let handles: Vec<_> = bins.into_iter().map(|(load, branches)| {
+            let bin_threads = (thread_cnt * load).div_ceil(total.max(1)).max(1);
+            scope.spawn(move || {
+                branches.into_iter().map(|(idx, path, addr)| {
+                    (idx, eval_branch(&path, addr, bin_threads))
+                }).collect::<Vec<_>>()
+            })
+        }).collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))).collect()
+    });
+    results.extend(thread_results);
+    finish_cata_body(&z, child_mask, results, alg_f)
+}
+
+/// Calls `alg_f` at the focus of `z`, with the results for its branches in byte order
+fn finish_cata_body<'a, V, A, W, AlgF>(z: &ReadZipperUntracked<'a, 'static, V, A>, child_mask: ByteMask, mut results: Vec<(usize, W)>, alg_f: &AlgF) -> W
+    where
+    V: Clone + Send + Sync + Unpin,
+    A: Allocator,
+    AlgF: Fn(&ByteMask, &mut [W], usize, Option<&V>, &[u8]) -> W,
+{
+    results.sort_by_key(|(idx, _)| *idx);
+    let mut children: Vec<W> = results.into_iter().map(|(_, w)| w).collect();
+    let jump_len = z.path().len();
+    alg_f(&child_mask, &mut children, jump_len, z.get_value(), z.origin_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn par_for_each_branch_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        for i in 0..1000usize {
+            map.insert(format!("{}:{i}", i % 7), i);
+        }
+        map.insert(b"x", 1000);
+        let zipper_head = map.zipper_head();
+
+        //Double every value below each branch
+        par_for_each_branch(&zipper_head, 2, |z| {
+            let mut paths = vec![];
+            let mut rz = z.fork_read_zipper();
+            while rz.to_next_val() {
+                paths.push(rz.path().to_vec());
+            }
+            drop(rz);
+            for path in paths {
+                z.descend_to(&path);
+                *z.get_value_mut().unwrap() *= 2;
+                z.reset();
+            }
+        }).unwrap();
+
+        //Count the values below each branch
+        let count = par_map_reduce(&zipper_head, 2, |z| z.val_count(), |a, b| a + b).unwrap();
+        assert_eq!(count, Some(1000));
+        let count = par_map_reduce(&zipper_head, 20, |z| z.val_count(), |a, b| a + b).unwrap();
+        assert_eq!(count, None);
+
+        //No branches are visited while another zipper conflicts
+        let reader = zipper_head.read_zipper_at_path(b"3:").unwrap();
+        assert!(par_for_each_branch(&zipper_head, 2, |_| panic!()).is_err());
+        drop(reader);
+
+        //Zippers outside the branches don't conflict
+        let other_reader = zipper_head.read_zipper_at_path(b"x").unwrap();
+        let count = par_map_reduce(&zipper_head, 2, |z| z.val_count(), |a, b| a + b).unwrap();
+        assert_eq!(count, Some(1000));
+        drop(other_reader);
+        drop(zipper_head);
+
+        for i in 0..1000usize {
+            assert_eq!(map.get(format!("{}:{i}", i % 7)), Some(&(i * 2)));
+        }
+        assert_eq!(map.get(b"x"), Some(&1000));
+    }
+    #[test]
+    fn par_algebraic_op_test() {
+        let mut a = BytesTrieMap::<()>::new();
+        let mut b = BytesTrieMap::<()>::new();
+        for i in 0..3000usize {
+            a.insert(format!("{}:{i}", i % 5), ());
+            b.insert(format!("{}:{}", i % 7, i * 2), ());
+        }
+        a.insert(b"", ());
+        a.insert(b"1", ());
+        b.insert(b"1", ());
+        b.insert(b"prefix", ());
+
+        //A low threshold forces the maps to be split all the way down
+        for thread_cnt in [1, 2, 3, 16] {
+            let joined = par_algebraic_op(&a, &b, BranchSelection::Union, thread_cnt, 1, &BytesTrieMap::join);
+            assert_eq!(joined.iter().collect::<Vec<_>>(), a.join(&b).iter().collect::<Vec<_>>());
+            assert_eq!(joined.get(b""), Some(&()));
+            let met = par_algebraic_op(&a, &b, BranchSelection::Intersection, thread_cnt, 1, &BytesTrieMap::meet);
+            assert_eq!(met.iter().collect::<Vec<_>>(), a.meet(&b).iter().collect::<Vec<_>>());
+            assert_eq!(met.get(b""), None);
+            let subtracted = par_algebraic_op(&a, &b, BranchSelection::Left, thread_cnt, 1, &BytesTrieMap::subtract);
+            assert_eq!(subtracted.iter().collect::<Vec<_>>(), a.subtract(&b).iter().collect::<Vec<_>>());
+            assert_eq!(subtracted.get(b""), Some(&()));
+        }
+
+        assert_eq!(a.par_join(&b).val_count(), a.join(&b).val_count());
+        assert_eq!(a.par_meet(&b).val_count(), a.meet(&b).val_count());
+        assert_eq!(a.par_subtract(&b).val_count(), a.subtract(&b).val_count());
+    }
+    #[test]
+    fn par_cata_jumping_test() {
+        //Record every call to the algebra, so the calls can be compared with the sequential catamorphism
+        fn alg(mask: &ByteMask, children: &mut [Vec<(Vec<u8>, usize, usize, Option<usize>)>], jump_len: usize, val: Option<&usize>, path: &[u8]) -> Vec<(Vec<u8>, usize, usize, Option<usize>)> {
+            let mut calls: Vec<_> = children.iter_mut().flat_map(core::mem::take).collect();
+            calls.push((path.to_vec(), jump_len, mask.iter().count(), val.cloned()));
+            calls
+        }
+
+        let mut map = BytesTrieMap::<usize>::new();
+        for i in 0..2000usize {
+            map.insert(format!("prefix/{}:{i}", i % 11), i);
+        }
+        map.insert(b"prefix/3", 3);
+        let mut rooted = map.clone();
+        rooted.insert(b"", 0);
+        rooted.insert(b"x", 1);
+        let empty = BytesTrieMap::<usize>::new();
+
+        for map in [&map, &rooted, &empty] {
+            let expected = map.read_zipper().into_cata_jumping_side_effect(alg);
+            //Branches evaluated on different threads are concatenated in byte order, so even the order matches
+            for thread_cnt in [1, 2, 5] {
+                assert_eq!(par_cata_jumping_with(map, alg, thread_cnt, 1), expected);
+            }
+            assert_eq!(par_cata_jumping(map, alg), expected);
+        }
+    }
+
+    #[test]
+    fn par_cata_jumping_cached_test() {
+        let mut branch = BytesTrieMap::<usize>::new();
+        for i in 0..500usize {
+            branch.insert(format!("{i}"), i);
+        }
+        //The same subtrie is grafted below several paths, so the cache is shared across branches
+        let mut map = BytesTrieMap::<usize>::new();
+        for prefix in [&b"a:"[..], b"b:", b"c:", b"d:"] {
+            map.write_zipper_at_path(prefix).graft_map(branch.clone());
+        }
+
+        let count = |_mask: &ByteMask, children: &mut [usize], _jump_len: usize, val: Option<&usize>, _path: &[u8]| {
+            children.iter().sum::<usize>() + val.is_some() as usize
+        };
+        let expected = map.read_zipper().into_cata_jumping_cached(count);
+        assert_eq!(expected, 2000);
+        for thread_cnt in [1, 2, 3, 8] {
+            assert_eq!(par_cata_jumping_cached_with(&map, count, thread_cnt, 1), expected);
+        }
+        assert_eq!(par_cata_jumping_cached(&map, count), expected);
+    }
+}
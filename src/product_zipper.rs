@@ -1,6 +1,7 @@
 
 use crate::{Allocator, GlobalAlloc};
 use crate::utils::ByteMask;
+use crate::trie_map::BytesTrieMap;
 use crate::trie_node::*;
 use crate::zipper::*;
 use zipper_priv::*;
@@ -16,7 +17,7 @@ pub struct ProductZipper<'factor_z, 'trie, V: Clone + Send + Sync, A: Allocator
     /// which is conceptually the same as the end-point of each indexed factor
     factor_paths: Vec<usize>,
     /// We need to hang onto the zippers for the life of this object, so their trackers stay alive
-    source_zippers: Vec<Box<dyn zipper_priv::ZipperReadOnlyPriv<'trie, V, A> + 'factor_z>>
+    source_zippers: Vec<Box<dyn zipper_priv::ZipperReadOnlyPriv<'trie, V, A> + 'factor_z>>,
 }
 
 impl<'factor_z, 'trie, V: Clone + Send + Sync + Unpin, A: Allocator> ProductZipper<'factor_z, 'trie, V, A> {
@@ -80,6 +81,25 @@ impl<'factor_z, 'trie, V: Clone + Send + Sync + Unpin, A: Allocator> ProductZipp
             self.source_zippers.push(Box::new(other_z));
         }
     }
+    /// Appends additional factors from virtual zippers, such as [FilterZipper] or [RemapZipper], whose
+    /// subtries don't exist as nodes in any trie
+    ///
+    /// The subtrie below the focus of each zipper is materialized with [make_map](ZipperSubtries::make_map)
+    /// and added as a [ReadZipperOwned] factor, so the cost is proportional to the size of each factor.  A
+    /// factor with nothing below its focus behaves as an empty trie.
+    pub fn new_virtual_factors<OtherZ, ZipperList>(&mut self, other_zippers: ZipperList)
+        where
+        V: 'static,
+        A: 'static,
+        OtherZ: ZipperSubtries<V, A>,
+        ZipperList: IntoIterator<Item=OtherZ>,
+    {
+        let owned_factors: Vec<_> = other_zippers.into_iter().map(|other_z| {
+            let map = other_z.make_map().unwrap_or_else(|| BytesTrieMap::new_in(self.z.alloc.clone()));
+            map.into_read_zipper("")
+        }).collect();
+        self.new_factors(owned_factors);
+    }
     /// Returns the number of factors composing the `ProductZipper`
     ///
     /// The minimum returned value will be 1 because the primary factor is counted.
@@ -0,0 +1,445 @@
+use crate::utils::{BitMask, ByteMask};
+use crate::trie_map::BytesTrieMap;
+use crate::trie_node::{AbstractNodeRef, TrieNode};
+use crate::zipper::*;
+use crate::zipper::zipper_priv::{ZipperPriv, FocusAddr};
+
+/// A virtual [Zipper] that presents the paths of an underlying zipper with every byte translated through a
+/// 256-entry byte map
+///
+/// The byte map doesn't need to be injective.  If several underlying bytes map to the same byte, for
+/// example when folding case, their branches are merged in the view.  When more than one merged branch has
+/// a value at the same path, the value from the branch with the lowest underlying bytes takes priority.
+///
+/// Each position in the view may correspond to several positions in the underlying trie.  The underlying
+/// zipper is cloned where the view merges branches, and each clone follows the focus until the
+/// `RemapZipper` ascends above the point where it was cloned.
+///
+/// The `RemapZipper` implements [Catamorphism](crate::morphisms::Catamorphism), and may be used as the
+/// source for [ZipperWriting::graft] and the algebraic operations.  The remapped subtrie below the focus is
+/// materialized for each such operation, which has a cost proportional to the size of the subtrie.
+///
+/// A `RemapZipper` can be a secondary factor of a [ProductZipper] by way of
+/// [ProductZipper::new_virtual_factors].
+#[derive(Clone)]
+pub struct RemapZipper<Z> {
+    /// The byte in the view for each byte in the underlying trie
+    map: Box<[u8; 256]>,
+    /// The underlying bytes for each byte in the view
+    inverse: Box<[ByteMask; 256]>,
+    /// The underlying root prefix path, followed by the path in the view
+    origin_path: Vec<u8>,
+    /// The length of the root prefix at the start of `origin_path`
+    prefix_len: usize,
+    /// The underlying zippers, in priority order.  One for each underlying path that leads to the focus
+    sources: Vec<RemapSource<Z>>,
+    /// The view's child mask at each level of the path.  The last entry corresponds to the focus
+    masks: Vec<ByteMask>,
+}
+
+/// One of the underlying zippers in a [RemapZipper]
+#[derive(Clone)]
+struct RemapSource<Z> {
+    z: Z,
+    /// The depth in the view at which this zipper was cloned from the one before it, or 0 for the original
+    cloned_at: usize,
+    /// The depth in the view at which this zipper's path ended, or `None` if it is at the focus.  A zipper
+    /// that doesn't follow the focus stays where it stopped, until the `RemapZipper` ascends above it
+    stopped_at: Option<usize>,
+}
+
+impl<Z: ZipperMoving + ZipperAbsolutePath + Clone> RemapZipper<Z> {
+    /// Creates a new `RemapZipper`, where `map[b]` is the byte presented in place of underlying byte `b`
+    ///
+    /// The zipper is reset to its root, which becomes the root of the `RemapZipper`
+    pub fn new(mut z: Z, map: [u8; 256]) -> Self {
+        z.reset();
+        let mut inverse = Box::new([ByteMask::EMPTY; 256]);
+        for (src, &dst) in map.iter().enumerate() {
+            inverse[dst as usize].set_bit(src as u8);
+        }
+        let origin_path = z.root_prefix_path().to_vec();
+        let prefix_len = origin_path.len();
+        let mut new_zipper = Self {
+            map: Box::new(map),
+            inverse,
+            origin_path,
+            prefix_len,
+            sources: vec![RemapSource { z, cloned_at: 0, stopped_at: None }],
+            masks: vec![],
+        };
+        new_zipper.push_mask();
+        new_zipper
+    }
+    /// Creates a new `RemapZipper` that folds ASCII upper case letters in the underlying paths to lower case
+    pub fn new_ascii_lowercase(z: Z) -> Self {
+        Self::new(z, core::array::from_fn(|b| (b as u8).to_ascii_lowercase()))
+    }
+}
+
+impl<Z: ZipperMoving + Clone> RemapZipper<Z> {
+    /// Returns the byte map used by the `RemapZipper`
+    pub fn byte_map(&self) -> &[u8; 256] {
+        &self.map
+    }
+    /// Pushes the view's child mask for the focus, computed from the underlying zippers
+    fn push_mask(&mut self) {
+        let mut mask = ByteMask::EMPTY;
+        for z in self.focus_zippers() {
+            for src in z.child_mask().iter() {
+                mask.set_bit(self.map[src as usize]);
+            }
+        }
+        self.masks.push(mask);
+    }
+    /// Returns the underlying zippers at the focus, in priority order
+    fn focus_zippers(&self) -> impl Iterator<Item=&Z> {
+        self.sources.iter().filter(|source| source.stopped_at.is_none()).map(|source| &source.z)
+    }
+    /// Returns the depth of the focus in the view
+    fn depth(&self) -> usize {
+        self.origin_path.len() - self.prefix_len
+    }
+    /// Adds the number of values below the focus to `count`
+    fn count_values(&mut self, count: &mut usize) {
+        let mask = *self.masks.last().unwrap();
+        for byte in mask.iter() {
+            ZipperMoving::descend_to_byte(self, byte);
+            if self.is_value() {
+                *count += 1;
+            }
+            self.count_values(count);
+            ZipperMoving::ascend_byte(self);
+        }
+    }
+    /// Calls `visit_f` for every value below the focus, with the path relative to the focus
+    fn for_each_value<V, VisitF>(&mut self, base_len: usize, visit_f: &mut VisitF)
+        where Z: ZipperValues<V>, VisitF: FnMut(&[u8], &V)
+    {
+        let mask = *self.masks.last().unwrap();
+        for byte in mask.iter() {
+            ZipperMoving::descend_to_byte(self, byte);
+            if let Some(val) = self.focus_zippers().find_map(|z| z.value()) {
+                visit_f(&self.origin_path[base_len..], val);
+            }
+            self.for_each_value(base_len, visit_f);
+            ZipperMoving::ascend_byte(self);
+        }
+    }
+}
+
+impl<Z: ZipperMoving + Clone> Zipper for RemapZipper<Z> {
+    fn path_exists(&self) -> bool { self.focus_zippers().any(|z| z.path_exists()) }
+    fn is_value(&self) -> bool { self.focus_zippers().any(|z| z.is_value()) }
+    fn child_count(&self) -> usize { self.masks.last().unwrap().count_bits() }
+    fn child_mask(&self) -> ByteMask { *self.masks.last().unwrap() }
+}
+
+impl<V, Z: ZipperMoving + ZipperValues<V> + Clone> ZipperValues<V> for RemapZipper<Z> {
+    fn value(&self) -> Option<&V> {
+        self.focus_zippers().find_map(|z| z.value())
+    }
+}
+
+impl<'a, V, Z: ZipperMoving + ZipperReadOnlyValues<'a, V> + Clone> ZipperReadOnlyValues<'a, V> for RemapZipper<Z> {
+    fn get_value(&self) -> Option<&'a V> {
+        self.focus_zippers().find_map(|z| z.get_value())
+    }
+}
+
+impl<Z: ZipperMoving + Clone> ZipperMoving for RemapZipper<Z> {
+    fn at_root(&self) -> bool { self.masks.len() == 1 }
+    fn reset(&mut self) {
+        self.sources.truncate(1);
+        let source = &mut self.sources[0];
+        source.z.reset();
+        source.stopped_at = None;
+        self.masks.truncate(1);
+        self.origin_path.truncate(self.prefix_len);
+    }
+    fn path(&self) -> &[u8] { &self.origin_path[self.prefix_len..] }
+    fn val_count(&self) -> usize {
+        let mut temp = self.clone();
+        let mut count = if self.is_value() { 1 } else { 0 };
+        temp.count_values(&mut count);
+        count
+    }
+    fn descend_to<K: AsRef<[u8]>>(&mut self, k: K) -> bool {
+        for &byte in k.as_ref() {
+            self.descend_to_byte(byte);
+        }
+        self.path_exists()
+    }
+    fn descend_to_byte(&mut self, k: u8) -> bool {
+        let src_bytes = self.inverse[k as usize];
+        self.origin_path.push(k);
+        let depth = self.depth();
+        let mut idx = 0;
+        while idx < self.sources.len() {
+            let source = &mut self.sources[idx];
+            idx += 1;
+            if source.stopped_at.is_some() {
+                continue
+            }
+            let mut src_iter = (source.z.child_mask() & src_bytes).iter();
+            let first_src = match src_iter.next() {
+                Some(src) => src,
+                None => { source.stopped_at = Some(depth); continue },
+            };
+            //Each additional underlying branch that merges into `k` gets its own clone
+            let parent = source.z.clone();
+            source.z.descend_to_byte(first_src);
+            for src in src_iter {
+                let mut z = parent.clone();
+                z.descend_to_byte(src);
+                self.sources.insert(idx, RemapSource { z, cloned_at: depth, stopped_at: None });
+                idx += 1;
+            }
+        }
+        self.push_mask();
+        self.path_exists()
+    }
+    fn ascend(&mut self, steps: usize) -> bool {
+        if steps > self.depth() {
+            self.reset();
+            return false
+        }
+        for _ in 0..steps {
+            let depth = self.depth();
+            self.sources.retain(|source| source.cloned_at != depth);
+            for source in self.sources.iter_mut() {
+                match source.stopped_at {
+                    None => { source.z.ascend_byte(); },
+                    Some(stopped_at) => if stopped_at == depth { source.stopped_at = None },
+                }
+            }
+            self.masks.pop();
+            self.origin_path.pop();
+        }
+        true
+    }
+    fn ascend_until(&mut self) -> bool {
+        if self.at_root() {
+            return false
+        }
+        loop {
+            self.ascend_byte();
+            if self.child_count() > 1 || self.is_value() || self.at_root() {
+                return true
+            }
+        }
+    }
+    fn ascend_until_branch(&mut self) -> bool {
+        if self.at_root() {
+            return false
+        }
+        loop {
+            self.ascend_byte();
+            if self.child_count() > 1 || self.at_root() {
+                return true
+            }
+        }
+    }
+}
+
+impl<Z: ZipperMoving + Clone> ZipperIteration for RemapZipper<Z> { }
+
+impl<Z: ZipperMoving + Clone> ZipperAbsolutePath for RemapZipper<Z> {
+    fn origin_path(&self) -> &[u8] { &self.origin_path }
+    fn root_prefix_path(&self) -> &[u8] { &self.origin_path[..self.prefix_len] }
+}
+
+impl<Z: ZipperMoving + Clone> ZipperPathBuffer for RemapZipper<Z> {
+    unsafe fn origin_path_assert_len(&self, len: usize) -> &[u8] {
+        assert!(len <= self.origin_path.capacity());
+        unsafe{ core::slice::from_raw_parts(self.origin_path.as_ptr(), len) }
+    }
+    fn prepare_buffers(&mut self) { }
+    fn reserve_buffers(&mut self, path_len: usize, stack_depth: usize) {
+        self.origin_path.reserve(path_len.saturating_sub(self.origin_path.len()));
+        self.masks.reserve(stack_depth.saturating_sub(self.masks.len()));
+    }
+}
+
+/// A remapped focus may merge several underlying subtries, so a `RemapZipper` never reports a shared focus
+impl<Z> ZipperConcrete for RemapZipper<Z> {
+    fn is_shared(&self) -> bool { false }
+}
+
+impl<Z> ZipperConcretePriv for RemapZipper<Z> {
+    fn shared_addr(&self) -> Option<FocusAddr> { None }
+}
+
+/// The remapped subtrie doesn't exist as nodes in any trie, so `make_map` visits every value below the
+/// focus and inserts it into a new map.  The cost is proportional to the size of the remapped subtrie.
+impl<V: Clone + Send + Sync + Unpin, Z: ZipperMoving + ZipperValues<V> + Clone> ZipperSubtries<V> for RemapZipper<Z> {
+    fn make_map(&self) -> Option<BytesTrieMap<V>> {
+        let mut map = BytesTrieMap::new();
+        let mut temp = self.clone();
+        let base_len = temp.origin_path.len();
+        temp.for_each_value(base_len, &mut |rel_path, val: &V| { map.insert(rel_path, val.clone()); });
+        if map.is_empty() {
+            None
+        } else {
+            Some(map)
+        }
+    }
+}
+
+/// `get_focus` builds a new trie with [make_map](ZipperSubtries::make_map) on every call, so each call costs
+/// as much as a full traversal of the remapped subtrie.  Avoid calling it repeatedly at the same focus.
+impl<V: Clone + Send + Sync + Unpin, Z: ZipperMoving + ZipperValues<V> + Clone> ZipperPriv for RemapZipper<Z> {
+    type V = V;
+    type A = crate::GlobalAlloc;
+    fn get_focus(&self) -> AbstractNodeRef<'_, V, crate::GlobalAlloc> {
+        match self.make_map().and_then(|map| map.into_root().0) {
+            Some(root) => AbstractNodeRef::OwnedRc(root),
+            None => AbstractNodeRef::None,
+        }
+    }
+    fn try_borrow_focus(&self) -> Option<&dyn TrieNode<V, crate::GlobalAlloc>> {
+        //The remapped subtrie doesn't exist as a node in the underlying trie
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trie_map::BytesTrieMap;
+    use crate::morphisms::Catamorphism;
+    use crate::zipper::*;
+
+    fn identity_map() -> [u8; 256] {
+        core::array::from_fn(|b| b as u8)
+    }
+
+    #[test]
+    fn remap_zipper_case_fold_test() {
+        let map = BytesTrieMap::from_iter([("Apple", 1), ("apple", 2), ("apRicot", 3), ("BANANA", 4)]);
+        let mut rz = RemapZipper::new_ascii_lowercase(map.read_zipper());
+
+        assert_eq!(rz.child_mask().iter().collect::<Vec<u8>>(), vec![b'a', b'b']);
+        assert_eq!(rz.val_count(), 3);
+        assert!(rz.descend_to(b"ap"));
+        assert_eq!(rz.child_count(), 2);
+        assert!(rz.descend_to(b"ple"));
+        //"Apple" sorts before "apple", so it takes priority
+        assert_eq!(rz.value(), Some(&1));
+        assert!(rz.ascend(3));
+        assert!(rz.descend_to(b"ricot"));
+        assert_eq!(rz.value(), Some(&3));
+        rz.reset();
+        assert!(!rz.descend_to(b"BANANA"));
+        rz.reset();
+        assert!(rz.descend_to(b"banana"));
+        assert_eq!(rz.value(), Some(&4));
+
+        rz.reset();
+        let mut paths = vec![];
+        while rz.to_next_val() {
+            paths.push(rz.path().to_vec());
+        }
+        assert_eq!(paths, vec![b"apple".to_vec(), b"apricot".to_vec(), b"banana".to_vec()]);
+    }
+
+    #[test]
+    fn remap_zipper_graft_test() {
+        let map = BytesTrieMap::from_iter([("ab", 1), ("ba", 2)]);
+        let mut swap = identity_map();
+        swap[b'a' as usize] = b'b';
+        swap[b'b' as usize] = b'a';
+        let mut rz = RemapZipper::new(map.read_zipper(), swap);
+        assert!(rz.descend_to(b"a"));
+        assert_eq!(rz.val_count(), 1);
+
+        let mut target = BytesTrieMap::new();
+        target.write_zipper_at_path(b"x").graft(&rz);
+        assert_eq!(target.get(b"xb"), Some(&2));
+        assert_eq!(target.val_count(), 1);
+
+        rz.reset();
+        let remapped = rz.make_map().unwrap();
+        assert_eq!(remapped.iter().collect::<Vec<_>>(), vec![(b"ab".to_vec(), &2), (b"ba".to_vec(), &1)]);
+    }
+
+    #[test]
+    fn remap_zipper_cata_test() {
+        let map = BytesTrieMap::from_iter([("Ab", 1), ("aB", 2), ("c", 3)]);
+        let rz = RemapZipper::new_ascii_lowercase(map.read_zipper());
+        let (count, sum) = rz.into_cata_side_effect(|_mask, children: &mut [(usize, usize)], val, _path| {
+            let (mut count, mut sum) = children.iter().fold((0, 0), |acc, c| (acc.0 + c.0, acc.1 + c.1));
+            if let Some(val) = val {
+                count += 1;
+                sum += *val;
+            }
+            (count, sum)
+        });
+        //"Ab" and "aB" are merged, and only the value from "Ab" remains
+        assert_eq!((count, sum), (2, 4));
+    }
+
+    #[test]
+    fn remap_zipper_merge_test() {
+        //Several underlying branches merge and separate again as the zipper moves
+        let map = BytesTrieMap::from_iter([("AbC", 1), ("aBc", 2), ("abD", 3), ("Bx", 4)]);
+        let mut rz = RemapZipper::new_ascii_lowercase(map.read_zipper());
+        assert!(rz.descend_to(b"ab"));
+        assert_eq!(rz.child_mask().iter().collect::<Vec<u8>>(), vec![b'c', b'd']);
+        assert!(rz.descend_to_byte(b'c'));
+        assert_eq!(rz.value(), Some(&1));
+        assert!(rz.ascend_byte());
+        assert!(rz.descend_to_byte(b'd'));
+        assert_eq!(rz.value(), Some(&3));
+        assert!(!rz.descend_to_byte(b'e'));
+        assert!(rz.ascend(4));
+        assert!(rz.at_root());
+        assert!(rz.descend_to(b"bx"));
+        assert_eq!(rz.value(), Some(&4));
+        assert_eq!(rz.val_count(), 1);
+        rz.reset();
+        assert_eq!(rz.val_count(), 3);
+    }
+
+    #[test]
+    fn remap_zipper_product_factor_test() {
+        let prefixes = BytesTrieMap::from_iter([("x:", 0), ("y:", 0)]);
+        let map = BytesTrieMap::from_iter([("Apple", 1), ("apple", 2), ("BANANA", 3)]);
+        let rz = RemapZipper::new_ascii_lowercase(map.read_zipper());
+
+        let mut pz = ProductZipper::new_with_primary(prefixes.read_zipper());
+        pz.new_virtual_factors([rz]);
+        assert!(pz.descend_to(b"x:"));
+        assert_eq!(pz.child_mask().iter().collect::<Vec<u8>>(), vec![b'a', b'b']);
+        assert!(pz.descend_to(b"apple"));
+        assert_eq!(pz.get_value(), Some(&1));
+        pz.reset();
+        assert!(!pz.descend_to(b"y:BANANA"));
+
+        pz.reset();
+        let mut paths = vec![];
+        while pz.to_next_val() {
+            paths.push(pz.path().to_vec());
+        }
+        assert_eq!(paths, vec![b"x:".to_vec(), b"x:apple".to_vec(), b"x:banana".to_vec(), b"y:".to_vec(), b"y:apple".to_vec(), b"y:banana".to_vec()]);
+    }
+
+    crate::zipper::zipper_moving_tests::zipper_moving_tests!(remap_zipper,
+        |keys: &[&[u8]]| {
+            let mut btm = BytesTrieMap::new();
+            keys.iter().for_each(|k| { btm.insert(k, ()); });
+            btm
+        },
+        |btm: &mut BytesTrieMap<()>, path: &[u8]| -> _ {
+            RemapZipper::new(btm.read_zipper_at_path(path), identity_map())
+    });
+
+    crate::zipper::zipper_iteration_tests::zipper_iteration_tests!(remap_zipper,
+        |keys: &[&[u8]]| {
+            let mut btm = BytesTrieMap::new();
+            keys.iter().for_each(|k| { btm.insert(k, ()); });
+            btm
+        },
+        |btm: &mut BytesTrieMap<()>, path: &[u8]| -> _ {
+            RemapZipper::new(btm.read_zipper_at_path(path), identity_map())
+    });
+}
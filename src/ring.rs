@@ -1,6 +1,12 @@
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::cmp::Ordering;
 use std::hash::Hash;
+use std::sync::Arc;
+
+/// Derive macros for [Lattice] and [DistributiveLattice], which operate on each field of a struct
+#[cfg(feature = "derive")]
+pub use pathmap_derive::{Lattice, DistributiveLattice};
 
 /// The result of an algebraic operation on elements in a partial lattice
 ///
@@ -845,6 +851,32 @@ impl<V: DistributiveLattice> DistributiveLattice for Box<V> {
     }
 }
 
+// =-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-=
+// =-*   `Arc<V>`                                                                                     *-=
+// NOTE: Two `Arc`s pointing at the same allocation are mutual identities for join and meet, so the inner
+// operation is skipped entirely.  This is the common case for values shared between versions of a map.
+
+impl<V: Lattice> Lattice for Arc<V> {
+    fn pjoin(&self, other: &Self) -> AlgebraicResult<Self> {
+        if Arc::ptr_eq(self, other) {
+            return AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT)
+        }
+        self.as_ref().pjoin(other.as_ref()).map(|result| Arc::new(result))
+    }
+    fn pmeet(&self, other: &Self) -> AlgebraicResult<Self> {
+        if Arc::ptr_eq(self, other) {
+            return AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT)
+        }
+        self.as_ref().pmeet(other.as_ref()).map(|result| Arc::new(result))
+    }
+}
+
+impl<V: DistributiveLattice> DistributiveLattice for Arc<V> {
+    fn psubtract(&self, other: &Self) -> AlgebraicResult<Self> {
+        self.as_ref().psubtract(other.as_ref()).map(|result| Arc::new(result))
+    }
+}
+
 // =-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-=
 // =-*   `&V`                                                                                         *-=
 
@@ -971,6 +1003,271 @@ impl Lattice for bool {
     // fn bottom() -> Self { false }
 }
 
+// =-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-=
+// =-*   Signed integers                                                                              *-=
+// Signed integers form a chain, where join is `max` and meet is `min`.  A chain has no complements, so
+// there is no `DistributiveLattice` impl.
+
+/// Internal function to convert the ordering between `self` and `other` into the result of a `max` operation
+#[inline]
+fn max_lattice_result<V>(ordering: Ordering) -> AlgebraicResult<V> {
+    match ordering {
+        Ordering::Greater => AlgebraicResult::Identity(SELF_IDENT),
+        Ordering::Less => AlgebraicResult::Identity(COUNTER_IDENT),
+        Ordering::Equal => AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT),
+    }
+}
+
+/// Internal function to convert the ordering between `self` and `other` into the result of a `min` operation
+#[inline]
+fn min_lattice_result<V>(ordering: Ordering) -> AlgebraicResult<V> {
+    max_lattice_result(ordering.reverse())
+}
+
+macro_rules! signed_int_lattice {
+    ( $( $t:ty ),+ ) => { $(
+        impl Lattice for $t {
+            fn pjoin(&self, other: &$t) -> AlgebraicResult<$t> { max_lattice_result(self.cmp(other)) }
+            fn pmeet(&self, other: &$t) -> AlgebraicResult<$t> { min_lattice_result(self.cmp(other)) }
+        }
+    )+ }
+}
+
+signed_int_lattice!(i8, i16, i32, i64, i128, isize);
+
+// =-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-=
+// =-*   `f32` & `f64`                                                                                *-=
+// Floats follow the same `max` / `min` chain as signed integers, ordered by `total_cmp` so `-0.0` is
+// below `0.0`.  A `NaN` of any sign behaves as the bottom element, and is therefore treated like an
+// absent value: it is the identity for join and annihilates in meet.
+
+macro_rules! float_lattice {
+    ( $( $t:ty ),+ ) => { $(
+        impl Lattice for $t {
+            fn pjoin(&self, other: &$t) -> AlgebraicResult<$t> {
+                match (self.is_nan(), other.is_nan()) {
+                    (true, true) => AlgebraicResult::None,
+                    (true, false) => AlgebraicResult::Identity(COUNTER_IDENT),
+                    (false, true) => AlgebraicResult::Identity(SELF_IDENT),
+                    (false, false) => max_lattice_result(self.total_cmp(other)),
+                }
+            }
+            fn pmeet(&self, other: &$t) -> AlgebraicResult<$t> {
+                if self.is_nan() || other.is_nan() {
+                    AlgebraicResult::None
+                } else {
+                    min_lattice_result(self.total_cmp(other))
+                }
+            }
+        }
+    )+ }
+}
+
+float_lattice!(f32, f64);
+
+// =-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-=
+// =-*   Tuples                                                                                       *-=
+// Tuples are combined component-wise.  A tuple can't represent a missing component, so a `None` result
+// from any component of a join or meet makes the result of the whole tuple `None`.  That meet doesn't
+// distribute over join once there is more than one component, so no `psubtract` could satisfy the partition
+// law, and only the 1-tuple, which is equivalent to its component, implements `DistributiveLattice`.
+
+macro_rules! tuple_lattice_op {
+    ( $self:ident, $other:ident, $op:ident, $( $v:ident $idx:tt ),+ ) => {{
+        let mut mask = SELF_IDENT | COUNTER_IDENT;
+        $(
+            let $v = $self.$idx.$op(&$other.$idx);
+            match &$v {
+                AlgebraicResult::None => return AlgebraicResult::None,
+                AlgebraicResult::Identity(component_mask) => mask &= component_mask,
+                AlgebraicResult::Element(_) => mask = 0,
+            }
+        )+
+        if mask > 0 {
+            return AlgebraicResult::Identity(mask)
+        }
+        AlgebraicResult::Element(( $( $v.into_option([&$self.$idx, &$other.$idx]).unwrap(), )+ ))
+    }}
+}
+
+macro_rules! tuple_lattice {
+    ( $( $t:ident $v:ident $idx:tt ),+ ) => {
+        impl< $( $t: Lattice + Clone ),+ > Lattice for ( $( $t, )+ ) {
+            fn pjoin(&self, other: &Self) -> AlgebraicResult<Self> {
+                tuple_lattice_op!(self, other, pjoin, $( $v $idx ),+)
+            }
+            fn pmeet(&self, other: &Self) -> AlgebraicResult<Self> {
+                tuple_lattice_op!(self, other, pmeet, $( $v $idx ),+)
+            }
+        }
+    }
+}
+
+impl<A: DistributiveLattice + Clone> DistributiveLattice for (A,) {
+    fn psubtract(&self, other: &Self) -> AlgebraicResult<Self> {
+        self.0.psubtract(&other.0).map(|a| (a,))
+    }
+}
+
+tuple_lattice!(A a 0);
+tuple_lattice!(A a 0, B b 1);
+tuple_lattice!(A a 0, B b 1, C c 2);
+tuple_lattice!(A a 0, B b 1, C c 2, D d 3);
+tuple_lattice!(A a 0, B b 1, C c 2, D d 3, E e 4);
+tuple_lattice!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5);
+tuple_lattice!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6);
+tuple_lattice!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7);
+
+// =-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-=
+// =-*   `SortedVecSet<K>`                                                                            *-=
+// The keys are kept sorted in ascending order without duplicates, so the operations are linear merges.
+
+/// A set of keys stored in a `Vec`, sorted in ascending order without duplicates
+///
+/// The order is established whenever a `SortedVecSet` is constructed, so the lattice operations can
+/// merge two sets in linear time.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SortedVecSet<K>(Vec<K>);
+
+impl<K> Default for SortedVecSet<K> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<K: Ord> From<Vec<K>> for SortedVecSet<K> {
+    fn from(mut keys: Vec<K>) -> Self {
+        keys.sort();
+        keys.dedup();
+        Self(keys)
+    }
+}
+
+impl<K: Ord> FromIterator<K> for SortedVecSet<K> {
+    fn from_iter<I: IntoIterator<Item=K>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<K>>())
+    }
+}
+
+impl<K> core::ops::Deref for SortedVecSet<K> {
+    type Target = [K];
+    fn deref(&self) -> &[K] {
+        &self.0
+    }
+}
+
+impl<K> SortedVecSet<K> {
+    /// Creates a new empty set
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+    /// Returns the keys in the set, in ascending order
+    pub fn as_slice(&self) -> &[K] {
+        &self.0
+    }
+    /// Consumes the set, and returns its keys in ascending order
+    pub fn into_vec(self) -> Vec<K> {
+        self.0
+    }
+}
+
+impl<K: Ord> SortedVecSet<K> {
+    /// Returns `true` if the set contains `key`, otherwise returns `false`
+    pub fn contains(&self, key: &K) -> bool {
+        self.0.binary_search(key).is_ok()
+    }
+    /// Adds `key` to the set.  Returns `true` if it was added, or `false` if it was already present
+    pub fn insert(&mut self, key: K) -> bool {
+        match self.0.binary_search(&key) {
+            Ok(_) => false,
+            Err(idx) => { self.0.insert(idx, key); true },
+        }
+    }
+    /// Removes `key` from the set.  Returns `true` if it was present, otherwise returns `false`
+    pub fn remove(&mut self, key: &K) -> bool {
+        match self.0.binary_search(key) {
+            Ok(idx) => { self.0.remove(idx); true },
+            Err(_) => false,
+        }
+    }
+}
+
+/// Internal function to make an `AlgebraicResult` from a merged sorted set.  Only valid for operations where
+/// the result is either a superset of both args (join) or a subset of both args (meet)
+#[inline]
+fn sorted_set_result<K>(result: Vec<K>, self_len: usize, other_len: usize) -> AlgebraicResult<SortedVecSet<K>> {
+    if result.len() == 0 {
+        return AlgebraicResult::None
+    }
+    let mut mask = 0;
+    if result.len() == self_len {
+        mask |= SELF_IDENT;
+    }
+    if result.len() == other_len {
+        mask |= COUNTER_IDENT;
+    }
+    if mask > 0 {
+        AlgebraicResult::Identity(mask)
+    } else {
+        AlgebraicResult::Element(SortedVecSet(result))
+    }
+}
+
+impl<K: Ord + Clone> Lattice for SortedVecSet<K> {
+    fn pjoin(&self, other: &Self) -> AlgebraicResult<Self> {
+        let (a, b) = (&self.0, &other.0);
+        let mut result = Vec::with_capacity(a.len().max(b.len()));
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                Ordering::Less => { result.push(a[i].clone()); i += 1; },
+                Ordering::Greater => { result.push(b[j].clone()); j += 1; },
+                Ordering::Equal => { result.push(a[i].clone()); i += 1; j += 1; },
+            }
+        }
+        result.extend_from_slice(&a[i..]);
+        result.extend_from_slice(&b[j..]);
+        sorted_set_result(result, a.len(), b.len())
+    }
+    fn pmeet(&self, other: &Self) -> AlgebraicResult<Self> {
+        let (a, b) = (&self.0, &other.0);
+        let mut result = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                Ordering::Less => { i += 1; },
+                Ordering::Greater => { j += 1; },
+                Ordering::Equal => { result.push(a[i].clone()); i += 1; j += 1; },
+            }
+        }
+        sorted_set_result(result, a.len(), b.len())
+    }
+}
+
+impl<K: Ord + Clone> DistributiveLattice for SortedVecSet<K> {
+    fn psubtract(&self, other: &Self) -> AlgebraicResult<Self> {
+        let other = &other.0;
+        let mut result = vec![];
+        let mut j = 0;
+        for key in self.0.iter() {
+            while j < other.len() && other[j] < *key {
+                j += 1;
+            }
+            if j < other.len() && other[j] == *key {
+                continue
+            }
+            result.push(key.clone());
+        }
+        if result.len() == 0 {
+            AlgebraicResult::None
+        } else if result.len() == self.0.len() {
+            AlgebraicResult::Identity(SELF_IDENT)
+        } else {
+            AlgebraicResult::Element(SortedVecSet(result))
+        }
+    }
+}
+
 // =-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-==-**-=
 // =-*   `SetLattice<K>`, including `HashMap<K, V>`, `HashSet<K>`, etc.                               *-=
 
@@ -1259,6 +1556,53 @@ impl<'a, K> Iterator for HashSetIterWrapper<'a, K> {
 set_lattice!(HashSet<K>);
 set_dist_lattice!(HashSet<K>);
 
+impl<K: Clone + Ord, V: Clone + Lattice> SetLattice for BTreeMap<K, V> {
+    type K = K;
+    type V = V;
+    type Iter<'a> = std::collections::btree_map::Iter<'a, K, V> where K: 'a, V: 'a;
+    fn with_capacity(_capacity: usize) -> Self { Self::new() }
+    fn len(&self) -> usize { self.len() }
+    fn is_empty(&self) -> bool { self.is_empty() }
+    fn contains_key(&self, key: &Self::K) -> bool { self.contains_key(key) }
+    fn insert(&mut self, key: Self::K, val: Self::V) { self.insert(key, val); }
+    fn get(&self, key: &Self::K) -> Option<&Self::V> { self.get(key) }
+    fn replace(&mut self, key: &Self::K, val: Self::V) { *self.get_mut(key).unwrap() = val }
+    fn remove(&mut self, key: &Self::K) { self.remove(key); }
+    fn iter<'a>(&'a self) -> Self::Iter<'a> { self.iter() }
+    fn shrink_to_fit(&mut self) { /* BTreeMap doesn't hold excess capacity */ }
+}
+
+set_lattice!(BTreeMap<K, V>);
+set_dist_lattice!(BTreeMap<K, V>);
+
+impl<K: Clone + Ord> SetLattice for BTreeSet<K> {
+    type K = K;
+    type V = ();
+    type Iter<'a> = BTreeSetIterWrapper<'a, K> where K: 'a;
+    fn with_capacity(_capacity: usize) -> Self { Self::new() }
+    fn len(&self) -> usize { self.len() }
+    fn is_empty(&self) -> bool { self.is_empty() }
+    fn contains_key(&self, key: &Self::K) -> bool { self.contains(key) }
+    fn insert(&mut self, key: Self::K, _val: Self::V) { self.insert(key); }
+    fn get(&self, key: &Self::K) -> Option<&Self::V> { self.get(key).map(|_| &()) }
+    fn replace(&mut self, key: &Self::K, _val: Self::V) { debug_assert!(self.contains(key)); /* a noop since we can assume the key already exists */ }
+    fn remove(&mut self, key: &Self::K) { self.remove(key); }
+    fn iter<'a>(&'a self) -> Self::Iter<'a> { BTreeSetIterWrapper(self.iter()) }
+    fn shrink_to_fit(&mut self) { /* BTreeSet doesn't hold excess capacity */ }
+}
+
+pub struct BTreeSetIterWrapper<'a, K> (std::collections::btree_set::Iter<'a, K>);
+
+impl<'a, K> Iterator for BTreeSetIterWrapper<'a, K> {
+    type Item = (&'a K, &'a());
+    fn next(&mut self) -> Option<(&'a K, &'a())> {
+        self.0.next().map(|key| (key, &()))
+    }
+}
+
+set_lattice!(BTreeSet<K>);
+set_dist_lattice!(BTreeSet<K>);
+
 #[test]
 fn set_lattice_join_test1() {
     let mut a = HashSet::new();
@@ -1481,6 +1825,126 @@ fn set_lattice_meet_test2() {
     assert_eq!(meet_result.identity_mask().unwrap(), COUNTER_IDENT);
 }
 
+#[test]
+fn signed_int_lattice_test() {
+    assert_eq!((-5i32).pjoin(&3), AlgebraicResult::Identity(COUNTER_IDENT));
+    assert_eq!((-5i32).pmeet(&3), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!(7i64.pjoin(&7), AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT));
+    assert_eq!(isize::MIN.pmeet(&isize::MAX), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!(i8::MAX.pjoin(&i8::MIN), AlgebraicResult::Identity(SELF_IDENT));
+}
+
+#[test]
+fn float_lattice_test() {
+    assert_eq!(1.5f64.pjoin(&-2.0), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!(1.5f64.pmeet(&-2.0), AlgebraicResult::Identity(COUNTER_IDENT));
+    assert_eq!(0.25f32.pjoin(&0.25), AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT));
+    assert_eq!((-0.0f64).pjoin(&0.0), AlgebraicResult::Identity(COUNTER_IDENT));
+
+    //NaN is the bottom element
+    assert_eq!(f64::NAN.pjoin(&1.0), AlgebraicResult::Identity(COUNTER_IDENT));
+    assert_eq!(1.0f64.pjoin(&f64::NAN), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!(f32::NAN.pjoin(&f32::NAN), AlgebraicResult::None);
+    assert_eq!(f64::NEG_INFINITY.pmeet(&f64::NAN), AlgebraicResult::None);
+    assert_eq!(Some(f64::NAN).pjoin(&Some(2.0)), AlgebraicResult::Identity(COUNTER_IDENT));
+}
+
+#[test]
+fn tuple_lattice_test() {
+    let a = (true, 3i32, false);
+    let b = (false, 5i32, false);
+    assert_eq!(a.pjoin(&b), AlgebraicResult::Element((true, 5, false)));
+    assert_eq!(a.pmeet(&b), AlgebraicResult::Element((false, 3, false)));
+    assert_eq!(a.pjoin(&a), AlgebraicResult::Identity(SELF_IDENT));
+
+    let c = (true, 7i32, true);
+    assert_eq!(c.pjoin(&a), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!(a.pmeet(&c), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!((1i32, 2i64).pjoin(&(3, 4)), AlgebraicResult::Identity(COUNTER_IDENT));
+    assert_eq!((1i32, 2i64).pmeet(&(1, 2)), AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT));
+
+    //A `None` component annihilates the whole tuple
+    let d = (Some(()), 1i32);
+    let e = (None, 1i32);
+    assert_eq!(d.pmeet(&e), AlgebraicResult::None);
+    assert_eq!(d.pjoin(&e), AlgebraicResult::Identity(SELF_IDENT));
+
+    let set = |keys: &[u8]| SortedVecSet::from(keys.to_vec());
+    let f = (set(&[1, 2, 3]), set(&[4]));
+    let g = (set(&[2]), set(&[5]));
+    assert_eq!(f.pjoin(&g), AlgebraicResult::Element((set(&[1, 2, 3]), set(&[4, 5]))));
+    assert_eq!(f.pmeet(&g), AlgebraicResult::None);
+
+    //A 1-tuple subtracts like its component
+    assert_eq!((set(&[1, 2, 3]),).psubtract(&(set(&[2]),)), AlgebraicResult::Element((set(&[1, 3]),)));
+    assert_eq!((set(&[1]),).psubtract(&(set(&[2]),)), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!((set(&[1]),).psubtract(&(set(&[1]),)), AlgebraicResult::None);
+}
+
+#[test]
+fn sorted_vec_set_lattice_test() {
+    //Construction sorts and dedups the keys
+    let a = SortedVecSet::from(vec![7, 3, 5, 1, 3]);
+    assert_eq!(a.as_slice(), &[1, 3, 5, 7]);
+    let b: SortedVecSet<i32> = [9, 2, 7, 3].into_iter().collect();
+    assert_eq!(a.pjoin(&b), AlgebraicResult::Element(SortedVecSet::from(vec![1, 2, 3, 5, 7, 9])));
+    assert_eq!(a.pmeet(&b), AlgebraicResult::Element(SortedVecSet::from(vec![3, 7])));
+    assert_eq!(a.psubtract(&b), AlgebraicResult::Element(SortedVecSet::from(vec![1, 5])));
+
+    let c = SortedVecSet::from(vec![3, 7]);
+    assert_eq!(a.pjoin(&c), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!(c.pjoin(&a), AlgebraicResult::Identity(COUNTER_IDENT));
+    assert_eq!(a.pmeet(&c), AlgebraicResult::Identity(COUNTER_IDENT));
+    assert_eq!(a.pmeet(&a), AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT));
+    assert_eq!(c.psubtract(&a), AlgebraicResult::None);
+    assert_eq!(a.psubtract(&SortedVecSet::from(vec![4, 8])), AlgebraicResult::Identity(SELF_IDENT));
+    assert_eq!(SortedVecSet::from(vec![2, 4]).pmeet(&SortedVecSet::from(vec![1, 3])), AlgebraicResult::None);
+
+    let mut d = SortedVecSet::new();
+    assert!(d.insert(4) && d.insert(2) && !d.insert(4));
+    assert!(d.contains(&2) && !d.contains(&3));
+    assert!(d.remove(&2) && !d.remove(&2));
+    assert_eq!(d.into_vec(), vec![4]);
+}
+
+#[test]
+fn btree_set_lattice_test() {
+    let a: BTreeSet<&str> = ["A", "B", "C"].into_iter().collect();
+    let b: BTreeSet<&str> = ["B", "C", "D"].into_iter().collect();
+
+    let joined = a.pjoin(&b).unwrap([&a, &b]);
+    assert_eq!(joined.len(), 4);
+    assert_eq!(joined.pjoin(&a), AlgebraicResult::Identity(SELF_IDENT));
+
+    let meet = a.pmeet(&b).unwrap([&a, &b]);
+    assert_eq!(meet.into_iter().collect::<Vec<_>>(), vec!["B", "C"]);
+
+    let subtracted = a.psubtract(&b).unwrap([&a, &b]);
+    assert_eq!(subtracted.into_iter().collect::<Vec<_>>(), vec!["A"]);
+    assert_eq!(a.psubtract(&a), AlgebraicResult::None);
+
+    let mut c: BTreeMap<&str, BTreeSet<u8>> = BTreeMap::new();
+    c.insert("x", [1].into_iter().collect());
+    let mut d: BTreeMap<&str, BTreeSet<u8>> = BTreeMap::new();
+    d.insert("x", [2].into_iter().collect());
+    d.insert("y", [3].into_iter().collect());
+    let joined = c.pjoin(&d).unwrap([&c, &d]);
+    assert_eq!(joined.len(), 2);
+    assert_eq!(joined.get("x").unwrap().len(), 2);
+    assert_eq!(c.pmeet(&d), AlgebraicResult::None);
+}
+
+#[test]
+fn arc_lattice_test() {
+    let set = |keys: &[u8]| Arc::new(SortedVecSet::from(keys.to_vec()));
+    let a = set(&[1, 2]);
+    let b = set(&[2, 3]);
+    assert_eq!(a.pjoin(&a.clone()), AlgebraicResult::Identity(SELF_IDENT | COUNTER_IDENT));
+    assert_eq!(a.pjoin(&b), AlgebraicResult::Element(set(&[1, 2, 3])));
+    assert_eq!(a.pmeet(&b), AlgebraicResult::Element(set(&[2])));
+    assert_eq!(a.psubtract(&b), AlgebraicResult::Element(set(&[1])));
+}
+
 //GOAT, do a test for the HashMap impl of psubtract
 //GOAT, do an impl of SetLattice for Vec as an indexed set
 
@@ -0,0 +1,217 @@
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{Allocator, GlobalAlloc};
+use crate::trie_map::BytesTrieMap;
+use crate::zipper::ReadZipperOwned;
+
+/// The most recently published contents of a [SharedPathMap]
+struct Published<V: Clone + Send + Sync, A: Allocator> {
+    generation: u64,
+    map: Arc<BytesTrieMap<V, A>>,
+}
+
+/// A [BytesTrieMap] that is edited by a single writer while readers on other threads see consistent snapshots
+///
+/// The writer makes changes to a private working map, and [publish](SharedPathMapWriter::publish) makes the
+/// working map's current contents visible to readers by swapping in a new root.  Readers never observe a
+/// partially applied batch of changes, and a snapshot is unaffected by anything published after it was
+/// taken.  The working map shares all unchanged nodes with the published snapshots, so each publication
+/// only costs the nodes that changed since the previous one.
+///
+/// The published root is swapped under a [RwLock], rather than with an atomic pointer, because the
+/// generation and the map must change together.  The lock is only held to clone or replace an `Arc`, and
+/// the writer prepares each batch and builds the new root before taking it, so readers are never held up
+/// by the work of a batch.  They can only be held up by another thread's brief hold of the lock.
+///
+/// ```
+/// # use pathmap::shared_map::SharedPathMap;
+/// let shared = SharedPathMap::new();
+/// let mut writer = shared.writer().unwrap();
+/// writer.map_mut().insert("a", 1);
+/// assert_eq!(shared.snapshot().get("a"), None);
+/// writer.publish();
+///
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| assert_eq!(shared.snapshot().get("a"), Some(&1)));
+/// });
+/// ```
+pub struct SharedPathMap<V: Clone + Send + Sync, A: Allocator = GlobalAlloc> {
+    published: RwLock<Published<V, A>>,
+    writer_active: AtomicBool,
+}
+
+/// The single writer of a [SharedPathMap], created with [SharedPathMap::writer]
+///
+/// Changes made through the writer are private until they are [published](SharedPathMapWriter::publish).
+/// Unpublished changes are discarded when the writer is dropped.
+pub struct SharedPathMapWriter<'a, V: Clone + Send + Sync, A: Allocator = GlobalAlloc> {
+    shared: &'a SharedPathMap<V, A>,
+    working: BytesTrieMap<V, A>,
+}
+
+impl<V: Clone + Send + Sync + Unpin> Default for SharedPathMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, A: Allocator> From<BytesTrieMap<V, A>> for SharedPathMap<V, A> {
+    fn from(map: BytesTrieMap<V, A>) -> Self {
+        Self {
+            published: RwLock::new(Published { generation: 0, map: Arc::new(map) }),
+            writer_active: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin> SharedPathMap<V> {
+    /// Creates a new `SharedPathMap` with empty contents
+    pub fn new() -> Self {
+        Self::from(BytesTrieMap::new())
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, A: Allocator> SharedPathMap<V, A> {
+    /// Creates a new `SharedPathMap` with empty contents, in the specified allocator
+    pub fn new_in(alloc: A) -> Self {
+        Self::from(BytesTrieMap::new_in(alloc))
+    }
+    /// Returns a snapshot of the most recently published contents
+    pub fn snapshot(&self) -> Arc<BytesTrieMap<V, A>> {
+        self.published.read().unwrap().map.clone()
+    }
+    /// Returns a [ReadZipperOwned] over a snapshot of the most recently published contents
+    pub fn read_zipper(&self) -> ReadZipperOwned<V, A> where V: 'static, A: 'static {
+        (*self.snapshot()).clone().into_read_zipper(&[])
+    }
+    /// Returns the number of times the contents have been published
+    ///
+    /// A reader can compare generations to find out whether a snapshot it holds is out of date.
+    pub fn generation(&self) -> u64 {
+        self.published.read().unwrap().generation
+    }
+    /// Returns the writer for the map, or `None` if a writer already exists
+    ///
+    /// The writer's working map starts out with the most recently published contents.
+    pub fn writer(&self) -> Option<SharedPathMapWriter<'_, V, A>> {
+        if self.writer_active.swap(true, Ordering::Acquire) {
+            return None
+        }
+        let working = (*self.snapshot()).clone();
+        Some(SharedPathMapWriter { shared: self, working })
+    }
+    /// Consumes the `SharedPathMap`, and returns the most recently published contents
+    pub fn into_map(self) -> BytesTrieMap<V, A> {
+        let map = self.published.into_inner().unwrap().map;
+        Arc::try_unwrap(map).unwrap_or_else(|map| (*map).clone())
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin, A: Allocator> SharedPathMapWriter<'_, V, A> {
+    /// Returns a reference to the working map, including any unpublished changes
+    pub fn map(&self) -> &BytesTrieMap<V, A> {
+        &self.working
+    }
+    /// Returns a mutable reference to the working map, to make changes that will be visible to readers after
+    /// the next [publish](SharedPathMapWriter::publish)
+    pub fn map_mut(&mut self) -> &mut BytesTrieMap<V, A> {
+        &mut self.working
+    }
+    /// Makes the current contents of the working map visible to readers, and returns the new generation
+    pub fn publish(&mut self) -> u64 {
+        //The new root is built before taking the lock, so the lock is only held to swap it in
+        let map = Arc::new(self.working.clone());
+        let mut published = self.shared.published.write().unwrap();
+        published.generation += 1;
+        published.map = map;
+        published.generation
+    }
+    /// Discards any unpublished changes, by replacing the working map with the most recently published contents
+    pub fn revert(&mut self) {
+        self.working = (*self.shared.snapshot()).clone();
+    }
+}
+
+impl<V: Clone + Send + Sync, A: Allocator> Drop for SharedPathMapWriter<'_, V, A> {
+    fn drop(&mut self) {
+        self.shared.writer_active.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::zipper::*;
+    use super::*;
+
+    #[test]
+    fn shared_map_writer_test() {
+        let shared = SharedPathMap::from(BytesTrieMap::single("a", 1));
+        let mut writer = shared.writer().unwrap();
+        assert!(shared.writer().is_none());
+        assert_eq!(writer.map().get("a"), Some(&1));
+
+        writer.map_mut().insert("b", 2);
+        let before = shared.snapshot();
+        assert_eq!(shared.generation(), 0);
+        assert_eq!(writer.publish(), 1);
+        assert_eq!(shared.generation(), 1);
+        assert_eq!(before.get("b"), None);
+        assert_eq!(shared.snapshot().get("b"), Some(&2));
+
+        //Reverting and dropping the writer both discard unpublished changes
+        writer.map_mut().remove("a");
+        writer.revert();
+        assert_eq!(writer.map().get("a"), Some(&1));
+        writer.map_mut().insert("c", 3);
+        drop(writer);
+        let mut writer = shared.writer().unwrap();
+        assert_eq!(writer.map().get("c"), None);
+        writer.map_mut().insert("c", 3);
+        writer.publish();
+        drop(writer);
+
+        let mut rz = shared.read_zipper();
+        let mut paths = vec![];
+        while rz.to_next_val() {
+            paths.push(rz.path().to_vec());
+        }
+        assert_eq!(paths, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+        assert_eq!(shared.into_map().val_count(), 3);
+    }
+
+    #[test]
+    fn shared_map_readers_test() {
+        const BATCHES: usize = 50;
+        const BATCH_SIZE: usize = 100;
+        let shared = SharedPathMap::<usize>::new();
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    //Each snapshot contains only whole batches, and `count` always agrees with the contents
+                    loop {
+                        let snapshot = shared.snapshot();
+                        let count = snapshot.get(b"count").copied().unwrap_or(0);
+                        assert_eq!(count % BATCH_SIZE, 0);
+                        assert_eq!(snapshot.val_count(), count + (count > 0) as usize);
+                        if count == BATCHES * BATCH_SIZE {
+                            break
+                        }
+                    }
+                });
+            }
+
+            let mut writer = shared.writer().unwrap();
+            for batch in 0..BATCHES {
+                for i in 0..BATCH_SIZE {
+                    writer.map_mut().insert(format!("key:{batch}:{i}"), i);
+                }
+                writer.map_mut().insert(b"count", (batch + 1) * BATCH_SIZE);
+                writer.publish();
+            }
+        });
+        assert_eq!(shared.generation(), BATCHES as u64);
+    }
+}
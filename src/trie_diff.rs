@@ -0,0 +1,601 @@
+use core::marker::PhantomData;
+
+use crate::Allocator;
+use crate::trie_map::BytesTrieMap;
+use crate::utils::{BitMask, ByteMask};
+use crate::zipper::*;
+use crate::zipper::zipper_priv::ZipperPriv;
+
+/// A difference between two versions of a trie, produced by [ZipperDiff]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffEntry<'a, V> {
+    /// A value exists at `path` in the new version, but not in the old version
+    Added { path: Vec<u8>, value: &'a V },
+    /// A value exists at `path` in the old version, but not in the new version
+    Removed { path: Vec<u8>, value: &'a V },
+    /// The values at `path` in the two versions are not equal
+    Changed { path: Vec<u8>, old: &'a V, new: &'a V },
+}
+
+impl<'a, V> DiffEntry<'a, V> {
+    /// Returns the path of the entry
+    pub fn path(&self) -> &[u8] {
+        match self {
+            Self::Added { path, .. } => path,
+            Self::Removed { path, .. } => path,
+            Self::Changed { path, .. } => path,
+        }
+    }
+    /// Returns the value in the new version, or `None` if the value was removed
+    pub fn new_value(&self) -> Option<&'a V> {
+        match self {
+            Self::Added { value, .. } => Some(*value),
+            Self::Removed { .. } => None,
+            Self::Changed { new, .. } => Some(*new),
+        }
+    }
+    /// Returns the value in the old version, or `None` if the value was added
+    pub fn old_value(&self) -> Option<&'a V> {
+        match self {
+            Self::Added { .. } => None,
+            Self::Removed { value, .. } => Some(*value),
+            Self::Changed { old, .. } => Some(*old),
+        }
+    }
+}
+
+/// A change found by a [ZipperDiff] that reports whole branches, used to build a [TriePatch]
+enum DiffChange<'a, V> {
+    /// A difference between the values at one path
+    Value(DiffEntry<'a, V>),
+    /// The branch at the path, including any value at the path, exists only in the new version.  The new
+    /// zipper is left focused on the branch
+    AddedBranch(Vec<u8>),
+    /// The branch at the path, including any value at the path, exists only in the old version
+    RemovedBranch(Vec<u8>),
+}
+
+/// An [Iterator] over the differences between the subtries below the focus of two zippers, in path order
+///
+/// The zippers are moved in lockstep over the union of both subtries.  Wherever both zippers focus on the
+/// same node, which is the case for the parts of a map that are untouched since it was cloned, the subtrie
+/// below is skipped without being traversed.  Therefore the cost of a diff between two versions is
+/// proportional to the size of the change, rather than to the size of the maps.
+///
+/// Paths in the [DiffEntry]s are relative to the focus of the zippers when the `ZipperDiff` was created.
+pub struct ZipperDiff<'a, V, A, NewZ, OldZ> {
+    new_z: NewZ,
+    old_z: OldZ,
+    path: Vec<u8>,
+    /// The bytes remaining to visit at each level, including the focus
+    stack: Vec<ByteMask>,
+    started: bool,
+    /// Reports a branch that exists in only one version as a single change, instead of value by value
+    whole_branches: bool,
+    _v: PhantomData<(&'a V, A)>,
+}
+
+impl<'a, V, A, NewZ, OldZ> ZipperDiff<'a, V, A, NewZ, OldZ>
+    where
+    V: Clone + Send + Sync + PartialEq + 'a,
+    A: Allocator,
+    NewZ: ZipperMoving + ZipperReadOnlyValues<'a, V> + ZipperSubtries<V, A>,
+    OldZ: ZipperMoving + ZipperReadOnlyValues<'a, V> + ZipperSubtries<V, A>,
+{
+    /// Creates a new `ZipperDiff` to compare the subtrie below the focus of `new_z` against the subtrie
+    /// below the focus of `old_z`
+    pub fn new(new_z: NewZ, old_z: OldZ) -> Self {
+        Self {
+            new_z,
+            old_z,
+            path: vec![],
+            stack: vec![],
+            started: false,
+            whole_branches: false,
+            _v: PhantomData,
+        }
+    }
+    /// Returns `true` if both zippers are focused on the same node, so the subtries below the focus are identical
+    fn focus_is_shared(&self) -> bool {
+        match (self.new_z.try_borrow_focus(), self.old_z.try_borrow_focus()) {
+            (Some(new_node), Some(old_node)) => core::ptr::addr_eq(new_node, old_node),
+            _ => false
+        }
+    }
+    /// Compares the values at the focus, and pushes the children to visit
+    fn visit_focus(&mut self) -> Option<DiffChange<'a, V>> {
+        if self.whole_branches && !self.path.is_empty() {
+            match (self.new_z.path_exists(), self.old_z.path_exists()) {
+                (true, false) => {
+                    self.stack.push(ByteMask::EMPTY);
+                    return Some(DiffChange::AddedBranch(self.path.clone()))
+                },
+                (false, true) => {
+                    self.stack.push(ByteMask::EMPTY);
+                    return Some(DiffChange::RemovedBranch(self.path.clone()))
+                },
+                _ => {}
+            }
+        }
+        let children = if self.focus_is_shared() {
+            ByteMask::EMPTY
+        } else {
+            self.new_z.child_mask() | self.old_z.child_mask()
+        };
+        self.stack.push(children);
+        let entry = match (self.new_z.get_value(), self.old_z.get_value()) {
+            (Some(new), Some(old)) => {
+                if core::ptr::eq(new, old) || new == old {
+                    None
+                } else {
+                    Some(DiffEntry::Changed { path: self.path.clone(), old, new })
+                }
+            },
+            (Some(value), None) => Some(DiffEntry::Added { path: self.path.clone(), value }),
+            (None, Some(value)) => Some(DiffEntry::Removed { path: self.path.clone(), value }),
+            (None, None) => None
+        };
+        entry.map(DiffChange::Value)
+    }
+    /// Advances to the next change, in path order
+    fn next_change(&mut self) -> Option<DiffChange<'a, V>> {
+        if !self.started {
+            self.started = true;
+            if let Some(change) = self.visit_focus() {
+                return Some(change)
+            }
+        }
+        loop {
+            let mask = self.stack.last_mut()?;
+            match mask.iter().next() {
+                Some(byte) => {
+                    mask.clear_bit(byte);
+                    self.new_z.descend_to_byte(byte);
+                    self.old_z.descend_to_byte(byte);
+                    self.path.push(byte);
+                    if let Some(change) = self.visit_focus() {
+                        return Some(change)
+                    }
+                },
+                None => {
+                    self.stack.pop();
+                    if self.stack.is_empty() {
+                        return None
+                    }
+                    self.new_z.ascend_byte();
+                    self.old_z.ascend_byte();
+                    self.path.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, V, A, NewZ, OldZ> Iterator for ZipperDiff<'a, V, A, NewZ, OldZ>
+    where
+    V: Clone + Send + Sync + PartialEq + 'a,
+    A: Allocator,
+    NewZ: ZipperMoving + ZipperReadOnlyValues<'a, V> + ZipperSubtries<V, A>,
+    OldZ: ZipperMoving + ZipperReadOnlyValues<'a, V> + ZipperSubtries<V, A>,
+{
+    type Item = DiffEntry<'a, V>;
+
+    fn next(&mut self) -> Option<DiffEntry<'a, V>> {
+        //Without `whole_branches`, every change is a `Value`
+        loop {
+            if let DiffChange::Value(entry) = self.next_change()? {
+                return Some(entry)
+            }
+        }
+    }
+}
+
+/// A compact description of the changes that transform one version of a trie into another
+///
+/// A `TriePatch` is made of removed subtries, replaced subtries, and individual values that were set or
+/// removed.  It is produced by [TriePatch::diff], from the same traversal as [ZipperDiff], and can be
+/// shipped to another process with [encode](TriePatch::encode) and [decode](TriePatch::decode).  Applying
+/// the patch from `old` to `new` onto `old` results in a trie equal to `new`.
+///
+/// ```
+/// # use pathmap::trie_map::BytesTrieMap;
+/// # use pathmap::trie_diff::TriePatch;
+/// let old = BytesTrieMap::from_iter([("apple", 1), ("banana", 2)]);
+/// let mut new = old.clone();
+/// new.insert("cherry", 3);
+/// new.remove("apple");
+///
+/// let patch = TriePatch::diff(&old, &new);
+/// let mut replica = old.clone();
+/// patch.apply_to_map(&mut replica);
+/// assert_eq!(replica.iter().collect::<Vec<_>>(), new.iter().collect::<Vec<_>>());
+/// ```
+#[derive(Clone)]
+pub struct TriePatch<V: Clone + Send + Sync> {
+    /// Paths where the entire subtrie, including the value at the path, is removed
+    removed: Vec<Vec<u8>>,
+    /// Paths where a new subtrie is grafted.  The maps don't contain the values at the graft paths
+    replaced: Vec<(Vec<u8>, BytesTrieMap<V>)>,
+    /// Paths where a value is set, or removed if the value is `None`
+    values: Vec<(Vec<u8>, Option<V>)>,
+}
+
+const PATCH_MAGIC: &[u8; 8] = b"PMPATCH1";
+
+impl<V: Clone + Send + Sync + Unpin + PartialEq> TriePatch<V> {
+    /// Creates a `TriePatch` that transforms `old` into `new`, so that applying `diff(old, new)` to `old`
+    /// results in `new`
+    ///
+    /// Like [BytesTrieMap::diff], subtries shared by both maps are skipped without being traversed.
+    pub fn diff(old: &BytesTrieMap<V>, new: &BytesTrieMap<V>) -> Self {
+        Self::diff_zippers(old.read_zipper(), new.read_zipper())
+    }
+    /// Creates a `TriePatch` that transforms the subtrie below the focus of `old_z` into the subtrie below
+    /// the focus of `new_z`
+    ///
+    /// A branch that exists in only one of the subtries is recorded as a single removed or replaced
+    /// subtrie, rather than value by value.
+    pub fn diff_zippers<'a, OldZ, NewZ>(old_z: OldZ, new_z: NewZ) -> Self
+        where
+        V: 'a,
+        OldZ: ZipperMoving + ZipperReadOnlyValues<'a, V> + ZipperSubtries<V>,
+        NewZ: ZipperMoving + ZipperReadOnlyValues<'a, V> + ZipperSubtries<V>,
+    {
+        let mut patch = Self { removed: vec![], replaced: vec![], values: vec![] };
+        let mut diff = ZipperDiff::new(new_z, old_z);
+        diff.whole_branches = true;
+        while let Some(change) = diff.next_change() {
+            match change {
+                DiffChange::Value(entry) => {
+                    let path = entry.path().to_vec();
+                    patch.values.push((path, entry.new_value().cloned()));
+                },
+                DiffChange::RemovedBranch(path) => patch.removed.push(path),
+                DiffChange::AddedBranch(path) => {
+                    if let Some(map) = diff.new_z.make_map() {
+                        patch.replaced.push((path.clone(), map));
+                    }
+                    if let Some(new) = diff.new_z.value() {
+                        patch.values.push((path, Some(new.clone())));
+                    }
+                },
+            }
+        }
+        patch
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin> TriePatch<V> {
+    /// Returns `true` if the patch contains no changes
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.replaced.is_empty() && self.values.is_empty()
+    }
+    /// Applies the patch to the subtrie below the focus of `wz`
+    ///
+    /// The zipper is returned to its original focus afterwards.
+    pub fn apply<Z: ZipperMoving + ZipperWriting<V>>(&self, wz: &mut Z) {
+        for path in self.removed.iter() {
+            wz.descend_to(path);
+            wz.remove_branches();
+            wz.remove_value();
+            wz.ascend(path.len());
+        }
+        for (path, map) in self.replaced.iter() {
+            wz.descend_to(path);
+            wz.graft_map(map.clone());
+            wz.ascend(path.len());
+        }
+        for (path, val) in self.values.iter() {
+            wz.descend_to(path);
+            match val {
+                Some(val) => { wz.set_value(val.clone()); },
+                None => { wz.remove_value(); },
+            }
+            wz.ascend(path.len());
+        }
+    }
+    /// Applies the patch to `map`.  See [apply](TriePatch::apply)
+    pub fn apply_to_map(&self, map: &mut BytesTrieMap<V>) {
+        self.apply(&mut map.write_zipper());
+    }
+    /// Writes the patch to `target`, using `value_f` to append the encoding of each value to a buffer.
+    /// Returns the number of bytes written
+    pub fn encode<W: std::io::Write, F: FnMut(&V, &mut Vec<u8>)>(&self, target: &mut W, mut value_f: F) -> std::io::Result<usize> {
+        let mut buf = Vec::with_capacity(1024);
+        let mut val_buf = Vec::new();
+        buf.extend_from_slice(PATCH_MAGIC);
+
+        push_varint(&mut buf, self.removed.len() as u64);
+        for path in self.removed.iter() {
+            push_bytes(&mut buf, path);
+        }
+
+        push_varint(&mut buf, self.replaced.len() as u64);
+        for (path, map) in self.replaced.iter() {
+            push_bytes(&mut buf, path);
+            push_varint(&mut buf, map.val_count() as u64);
+            for (sub_path, val) in map.iter() {
+                push_bytes(&mut buf, &sub_path);
+                val_buf.clear();
+                value_f(val, &mut val_buf);
+                push_bytes(&mut buf, &val_buf);
+            }
+        }
+
+        push_varint(&mut buf, self.values.len() as u64);
+        for (path, val) in self.values.iter() {
+            push_bytes(&mut buf, path);
+            match val {
+                Some(val) => {
+                    buf.push(1);
+                    val_buf.clear();
+                    value_f(val, &mut val_buf);
+                    push_bytes(&mut buf, &val_buf);
+                },
+                None => buf.push(0),
+            }
+        }
+
+        target.write_all(&buf)?;
+        Ok(buf.len())
+    }
+    /// Reads a patch written by [encode](TriePatch::encode) from `source`, using `value_f` to decode each
+    /// value
+    pub fn decode<R: std::io::Read, F: FnMut(&[u8]) -> V>(mut source: R, mut value_f: F) -> std::io::Result<Self> {
+        let mut buf = Vec::new();
+        source.read_to_end(&mut buf)?;
+        let mut reader = ByteReader::new(&buf, "TriePatch");
+        if reader.take(PATCH_MAGIC.len())? != PATCH_MAGIC {
+            return Err(invalid_data("missing TriePatch header"))
+        }
+
+        let removed_cnt = reader.varint()?;
+        let mut removed = Vec::new();
+        for _ in 0..removed_cnt {
+            removed.push(reader.bytes()?.to_vec());
+        }
+
+        let replaced_cnt = reader.varint()?;
+        let mut replaced = Vec::new();
+        for _ in 0..replaced_cnt {
+            let path = reader.bytes()?.to_vec();
+            let val_cnt = reader.varint()?;
+            let mut map = BytesTrieMap::new();
+            for _ in 0..val_cnt {
+                let sub_path = reader.bytes()?;
+                let val = value_f(reader.bytes()?);
+                map.insert(sub_path, val);
+            }
+            replaced.push((path, map));
+        }
+
+        let values_cnt = reader.varint()?;
+        let mut values = Vec::new();
+        for _ in 0..values_cnt {
+            let path = reader.bytes()?.to_vec();
+            let val = match reader.take(1)?[0] {
+                0 => None,
+                1 => Some(value_f(reader.bytes()?)),
+                _ => return Err(invalid_data("invalid TriePatch value tag")),
+            };
+            values.push((path, val));
+        }
+
+        if !reader.remaining().is_empty() {
+            return Err(invalid_data("trailing bytes after TriePatch"))
+        }
+        Ok(Self { removed, replaced, values })
+    }
+}
+
+pub(crate) fn invalid_data(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+pub(crate) fn push_varint(buf: &mut Vec<u8>, mut val: u64) {
+    loop {
+        let byte = (val & 0x7F) as u8;
+        val >>= 7;
+        if val == 0 {
+            buf.push(byte);
+            return
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+pub(crate) fn push_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    push_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// Internal cursor to decode the fields written by [push_varint] and [push_bytes]
+///
+/// `format` names the encoding being read, in error messages.
+pub(crate) struct ByteReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    format: &'static str,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(buf: &'a [u8], format: &'static str) -> Self {
+        Self { buf, pos: 0, format }
+    }
+    /// Returns the bytes that haven't been read yet
+    pub(crate) fn remaining(&self) -> &'a [u8] {
+        &self.buf[self.pos..]
+    }
+    pub(crate) fn take(&mut self, len: usize) -> std::io::Result<&'a [u8]> {
+        if self.buf.len() - self.pos < len {
+            return Err(invalid_data(&format!("truncated {}", self.format)))
+        }
+        let bytes = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+    pub(crate) fn varint(&mut self) -> std::io::Result<u64> {
+        let mut val = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.take(1)?[0];
+            if shift >= 64 {
+                return Err(invalid_data(&format!("varint overflow in {}", self.format)))
+            }
+            val |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(val)
+            }
+            shift += 7;
+        }
+    }
+    pub(crate) fn bytes(&mut self) -> std::io::Result<&'a [u8]> {
+        let len = self.varint()?;
+        self.take(len as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trie_map::BytesTrieMap;
+    use super::*;
+
+    #[test]
+    fn trie_diff_test1() {
+        let old = BytesTrieMap::from_iter([("arrow", 0), ("bow", 1), ("cannon", 2), ("roman", 3), ("romane", 4), ("romanus", 5)]);
+        let mut new = old.clone();
+        assert_eq!(new.diff(&old).count(), 0);
+
+        new.insert("romanesque", 6);
+        new.insert("bow", 10);
+        new.remove("romanus");
+        new.remove("arrow");
+        new.write_zipper().set_value(7);
+
+        let entries: Vec<_> = new.diff(&old).collect();
+        assert_eq!(entries, vec![
+            DiffEntry::Added { path: vec![], value: &7 },
+            DiffEntry::Removed { path: b"arrow".to_vec(), value: &0 },
+            DiffEntry::Changed { path: b"bow".to_vec(), old: &1, new: &10 },
+            DiffEntry::Added { path: b"romanesque".to_vec(), value: &6 },
+            DiffEntry::Removed { path: b"romanus".to_vec(), value: &5 },
+        ]);
+
+        //Diffing in the other direction swaps additions and removals
+        let reversed: Vec<_> = old.diff(&new).map(|entry| (entry.path().to_vec(), entry.old_value().copied(), entry.new_value().copied())).collect();
+        assert_eq!(reversed, vec![
+            (vec![], Some(7), None),
+            (b"arrow".to_vec(), None, Some(0)),
+            (b"bow".to_vec(), Some(10), Some(1)),
+            (b"romanesque".to_vec(), Some(6), None),
+            (b"romanus".to_vec(), None, Some(5)),
+        ]);
+    }
+
+    #[test]
+    fn trie_diff_unrelated_test() {
+        //Maps that were built independently share no nodes, but equal values are still not reported
+        let a = BytesTrieMap::from_iter([("one", 1), ("two", 2), ("three", 3)]);
+        let b = BytesTrieMap::from_iter([("one", 1), ("two", 22), ("four", 4)]);
+        let entries: Vec<_> = b.diff(&a).map(|entry| entry.path().to_vec()).collect();
+        assert_eq!(entries, vec![b"four".to_vec(), b"three".to_vec(), b"two".to_vec()]);
+
+        let empty = BytesTrieMap::new();
+        assert_eq!(a.diff(&empty).count(), 3);
+        assert_eq!(empty.diff(&a).count(), 3);
+    }
+
+    #[test]
+    fn trie_diff_zipper_test() {
+        let mut old = BytesTrieMap::new();
+        for i in 0..1000u32 {
+            old.insert(format!("data:{i}"), i);
+            old.insert(format!("meta:{i}"), i);
+        }
+        let mut new = old.clone();
+        new.insert("data:500", 0);
+        new.insert("meta:1000", 1000);
+
+        let entries: Vec<_> = ZipperDiff::new(new.read_zipper_at_path(b"data:"), old.read_zipper_at_path(b"data:")).collect();
+        assert_eq!(entries, vec![DiffEntry::Changed { path: b"500".to_vec(), old: &500, new: &0 }]);
+
+        let entries: Vec<_> = ZipperDiff::new(new.read_zipper_at_path(b"meta:"), old.read_zipper_at_path(b"meta:")).collect();
+        assert_eq!(entries, vec![DiffEntry::Added { path: b"1000".to_vec(), value: &1000 }]);
+    }
+
+    fn assert_maps_eq(a: &BytesTrieMap<u32>, b: &BytesTrieMap<u32>) {
+        assert_eq!(a.iter().collect::<Vec<_>>(), b.iter().collect::<Vec<_>>());
+        assert_eq!(a.get(b""), b.get(b""));
+    }
+
+    fn edited_versions() -> (BytesTrieMap<u32>, BytesTrieMap<u32>) {
+        let mut old = BytesTrieMap::new();
+        for i in 0..500u32 {
+            old.insert(format!("key:{i}"), i);
+        }
+        old.insert("other:a", 1);
+        old.insert("other:b", 2);
+        let mut new = old.clone();
+        for i in (0..500u32).step_by(7) {
+            new.insert(format!("key:{i}"), i + 1000);
+        }
+        for i in (0..500u32).step_by(11) {
+            new.remove(format!("key:{i}"));
+        }
+        new.write_zipper_at_path(b"other:").remove_branches();
+        new.insert("new:x", 10);
+        new.insert("new:y", 11);
+        new.insert("key:2:suffix", 12);
+        new.write_zipper().set_value(99);
+        (old, new)
+    }
+
+    #[test]
+    fn trie_patch_apply_test() {
+        let (old, new) = edited_versions();
+        let patch = TriePatch::diff(&old, &new);
+        assert!(!patch.is_empty());
+        let mut patched = old.clone();
+        patch.apply_to_map(&mut patched);
+        assert_maps_eq(&patched, &new);
+
+        //The reverse patch restores the old version
+        let reverse = TriePatch::diff(&new, &old);
+        reverse.apply_to_map(&mut patched);
+        assert_maps_eq(&patched, &old);
+
+        assert!(TriePatch::diff(&old, &old.clone()).is_empty());
+    }
+
+    #[test]
+    fn trie_patch_encode_test() {
+        let (old, new) = edited_versions();
+        let patch = TriePatch::diff(&old, &new);
+        let mut encoded = vec![];
+        let len = patch.encode(&mut encoded, |val, buf| buf.extend_from_slice(&val.to_le_bytes())).unwrap();
+        assert_eq!(len, encoded.len());
+
+        let decoded = TriePatch::decode(&encoded[..], |bytes| u32::from_le_bytes(bytes.try_into().unwrap())).unwrap();
+        let mut patched = old.clone();
+        decoded.apply_to_map(&mut patched);
+        assert_maps_eq(&patched, &new);
+
+        assert!(TriePatch::decode(&encoded[..encoded.len()-1], |_| 0u32).is_err());
+        assert!(TriePatch::decode(&b"PMPATCH0"[..], |_| 0u32).is_err());
+    }
+
+    #[test]
+    fn trie_patch_zipper_test() {
+        let (old, new) = edited_versions();
+        let patch = TriePatch::diff_zippers(old.read_zipper_at_path(b"key:"), new.read_zipper_at_path(b"key:"));
+
+        let mut patched = old.clone();
+        let mut wz = patched.write_zipper_at_path(b"key:");
+        patch.apply(&mut wz);
+        assert_eq!(wz.path(), b"");
+        drop(wz);
+        let key_entries = |map: &BytesTrieMap<u32>| map.iter().filter(|(k, _)| k.starts_with(b"key:")).map(|(k, v)| (k, *v)).collect::<Vec<_>>();
+        assert_eq!(key_entries(&patched), key_entries(&new));
+        assert_eq!(patched.get(b"other:a"), Some(&1));
+    }
+}
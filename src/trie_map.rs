@@ -3,8 +3,11 @@ use std::ptr::slice_from_raw_parts;
 use num_traits::{PrimInt, zero};
 use crate::{Allocator, GlobalAlloc, global_alloc};
 use crate::morphisms::{new_map_from_ana_in, Catamorphism, TrieBuilder};
+use crate::trie_diff::{DiffEntry, ZipperDiff};
 use crate::trie_node::*;
 use crate::zipper::*;
+use crate::zipper::zipper_priv::ZipperPriv;
+use crate::utils::ByteMaskIter;
 use crate::ring::{AlgebraicResult, AlgebraicStatus, COUNTER_IDENT, SELF_IDENT, Lattice, LatticeRef, DistributiveLattice, DistributiveLatticeRef, Quantale};
 
 #[cfg(not(miri))]
@@ -347,6 +350,16 @@ impl<V: Clone + Send + Sync + Unpin, A: Allocator> BytesTrieMap<V, A> {
         self.read_zipper().into_iter()
     }
 
+    /// Returns an iterator over the differences between `old` and `self`, in path order
+    ///
+    /// Subtries shared by both maps, for example because one map was cloned from the other, are skipped
+    /// without being traversed.  See [ZipperDiff] to compare the subtries below two zippers.
+    pub fn diff<'a>(&'a self, old: &'a Self) -> impl Iterator<Item=DiffEntry<'a, V>> + 'a
+        where V: PartialEq
+    {
+        ZipperDiff::new(self.read_zipper(), old.read_zipper())
+    }
+
     /// Returns `true` if the map contains a value at the specified key, otherwise returns `false`
     pub fn contains<K: AsRef<[u8]>>(&self, k: K) -> bool {
         let k = k.as_ref();
@@ -517,8 +530,117 @@ impl<V: Clone + Send + Sync + Unpin, A: Allocator> BytesTrieMap<V, A> {
 
         Self::new_with_root_in(subtracted_root_node, subtracted_root_val, self.alloc.clone())
     }
-}
 
+    /// Returns a new `BytesTrieMap` containing the right quotient `self / divisor`.  That is, every path `p`
+    /// for which there exists a path `s` leading to a value in `divisor`, such that `p·s` leads to a value in
+    /// `self`
+    ///
+    /// The value at each resulting path `p` is the join of all values in `self` at the paths `p·s`.
+    ///
+    /// This is the inverse of concatenation on the suffix side.  For example, `{"ab", "abc", "xc"} / {"c"}`
+    /// is `{"ab", "x"}`.  `self` is traversed once, while keeping track of the position in `divisor` reached
+    /// by each suffix of the current path, so the cost is proportional to the size of `self` plus the number
+    /// of those positions that exist in `divisor`.
+    pub fn right_quotient(&self, divisor: &Self) -> Self where V: Lattice {
+        let mut result = Self::new_in(self.alloc.clone());
+        if self.is_empty() || divisor.is_empty() {
+            return result
+        }
+        let mut wz = result.write_zipper();
+        let mut dividend_z = self.read_zipper();
+        let divisor_root = divisor.trie_ref_at_path(b"");
+
+        //Each frame holds the child bytes left to visit, the divisor positions reached by the suffixes of
+        // the path that start at each depth, and the joined value for the quotient path ending at this depth
+        let mut stack: Vec<(ByteMaskIter, Vec<(usize, TrieRef<V, A>)>, Option<V>)> = vec![];
+        let mut positions = vec![(0, divisor_root.clone())];
+        'outer: loop {
+            let depth = stack.len();
+            stack.push((dividend_z.child_mask().iter(), positions, None));
+            if let Some(val) = dividend_z.get_value() {
+                let starts: Vec<usize> = stack[depth].1.iter().filter(|(_, pos)| pos.is_value()).map(|(start, _)| *start).collect();
+                for start in starts {
+                    let quotient_val = &mut stack[start].2;
+                    *quotient_val = match quotient_val.take() {
+                        Some(mut joined) => { joined.join_into(val.clone()); Some(joined) },
+                        None => Some(val.clone())
+                    };
+                }
+            }
+            loop {
+                let frame = stack.last_mut().unwrap();
+                match frame.0.next() {
+                    Some(byte) => {
+                        positions = frame.1.iter().filter_map(|(start, pos)| {
+                            let pos = pos.trie_ref_at_path([byte]);
+                            pos.path_exists().then_some((*start, pos))
+                        }).collect();
+                        positions.push((depth + 1, divisor_root.clone()));
+                        dividend_z.descend_to_byte(byte);
+                        wz.descend_to_byte(byte);
+                        break
+                    },
+                    None => {
+                        let (_, _, quotient_val) = stack.pop().unwrap();
+                        if let Some(val) = quotient_val {
+                            wz.set_value(val);
+                        }
+                        if stack.is_empty() {
+                            break 'outer
+                        }
+                        dividend_z.ascend_byte();
+                        wz.ascend_byte();
+                    }
+                }
+            }
+        }
+        drop(wz);
+        result
+    }
+
+    /// Returns a new `BytesTrieMap` containing the left quotient `divisor \ self`.  That is, every path `s`
+    /// for which there exists a path `p` leading to a value in `divisor`, such that `p·s` leads to a value in
+    /// `self`
+    ///
+    /// This is the prefix-side counterpart to [right_quotient](Self::right_quotient), and the natural
+    /// inverse of [insert_prefix](ZipperWriting::insert_prefix).  For example, `{"ab", "abc", "xc"}` divided
+    /// on the left by `{"a", "x"}` is `{"b", "bc", "c"}`.  Where several prefixes lead to the same suffix,
+    /// the values are joined.
+    pub fn left_quotient(&self, divisor: &Self) -> Self where V: Lattice {
+        let mut result = Self::new_in(self.alloc.clone());
+        if self.is_empty() || divisor.is_empty() {
+            return result
+        }
+        let mut dividend_z = self.read_zipper();
+        let mut divisor_z = divisor.read_zipper();
+        let mut stack: Vec<ByteMaskIter> = vec![];
+        'outer: loop {
+            if divisor_z.is_value() {
+                let remainder = Self::new_with_root_in(dividend_z.get_focus().into_option(), dividend_z.value().cloned(), self.alloc.clone());
+                result.join_into(remainder);
+            }
+            stack.push((dividend_z.child_mask() & divisor_z.child_mask()).iter());
+            loop {
+                match stack.last_mut().unwrap().next() {
+                    Some(byte) => {
+                        dividend_z.descend_to_byte(byte);
+                        divisor_z.descend_to_byte(byte);
+                        break
+                    },
+                    None => {
+                        stack.pop();
+                        if stack.is_empty() {
+                            break 'outer
+                        }
+                        dividend_z.ascend_byte();
+                        divisor_z.ascend_byte();
+                    }
+                }
+            }
+        }
+        result
+    }
+}
 
 impl<V: Clone + Send + Sync + Unpin> BytesTrieMap<V> {
     /// Returns a [crate::old_cursor::PathMapCursor] to traverse all key-value pairs within the map. This
@@ -1227,6 +1349,94 @@ mod tests {
         assert_eq!(map.get(b"start:0003:hello"), Some(&3));
         assert_eq!(map.get(b"start:0003:goodbye"), Some(&3));
     }
+
+    /// Tests maps nested as the values in another map
+    #[test]
+    fn map_nested_lattice_test() {
+        let mut a: BytesTrieMap<BytesTrieMap<()>> = BytesTrieMap::new();
+        a.insert("x", BytesTrieMap::single("1", ()));
+        a.insert("y", BytesTrieMap::single("2", ()));
+        let mut b: BytesTrieMap<BytesTrieMap<()>> = BytesTrieMap::new();
+        b.insert("x", BytesTrieMap::single("3", ()));
+
+        let joined = a.join(&b);
+        assert_eq!(joined.val_count(), 2);
+        assert_eq!(joined.get("x").unwrap().val_count(), 2);
+        assert_eq!(joined.get("y").unwrap().val_count(), 1);
+
+        //The inner maps are disjoint, so the meet removes the outer path as well
+        let meet = a.meet(&b);
+        assert!(meet.is_empty());
+        let meet = joined.meet(&b);
+        assert_eq!(meet.val_count(), 1);
+        assert!(meet.get("x").unwrap().contains("3"));
+
+        let subtracted = joined.subtract(&b);
+        assert_eq!(subtracted.val_count(), 2);
+        assert_eq!(subtracted.get("x").unwrap().val_count(), 1);
+        assert!(subtracted.get("x").unwrap().contains("1"));
+        assert!(joined.subtract(&joined).is_empty());
+    }
+
+    #[test]
+    fn map_right_quotient_test() {
+        let a: BytesTrieMap<()> = ["ab", "abc", "xc", "xyz", "q"].into_iter().map(|k| (k, ())).collect();
+        let b: BytesTrieMap<()> = ["c", "yz"].into_iter().map(|k| (k, ())).collect();
+
+        let quotient = a.right_quotient(&b);
+        let mut paths: Vec<Vec<u8>> = quotient.iter().map(|(k, _)| k).collect();
+        paths.sort();
+        assert_eq!(paths, vec![b"ab".to_vec(), b"x".to_vec()]);
+
+        //A divisor containing the empty path retains every path in the dividend
+        let mut b2 = b.clone();
+        b2.insert([], ());
+        let quotient = a.right_quotient(&b2);
+        assert_eq!(quotient.val_count(), 6);
+        assert!(quotient.contains("xyz"));
+        assert!(quotient.contains("x"));
+        assert!(quotient.contains("ab"));
+
+        //No shared suffixes
+        let b3: BytesTrieMap<()> = ["zz"].into_iter().map(|k| (k, ())).collect();
+        assert!(a.right_quotient(&b3).is_empty());
+        assert!(a.right_quotient(&BytesTrieMap::new()).is_empty());
+
+        //Suffixes that overlap along the same path are each matched
+        let a4: BytesTrieMap<()> = ["aaaa", "ba"].into_iter().map(|k| (k, ())).collect();
+        let b4: BytesTrieMap<()> = ["a", "aa", "aaaaa"].into_iter().map(|k| (k, ())).collect();
+        let mut paths: Vec<Vec<u8>> = a4.right_quotient(&b4).iter().map(|(k, _)| k).collect();
+        paths.sort();
+        assert_eq!(paths, vec![b"aa".to_vec(), b"aaa".to_vec(), b"b".to_vec()]);
+    }
+
+    #[test]
+    fn map_left_quotient_test() {
+        let a: BytesTrieMap<()> = ["ab", "abc", "xc", "xyz", "q"].into_iter().map(|k| (k, ())).collect();
+        let b: BytesTrieMap<()> = ["a", "x", "xy", "nope"].into_iter().map(|k| (k, ())).collect();
+
+        let quotient = a.left_quotient(&b);
+        let mut paths: Vec<Vec<u8>> = quotient.iter().map(|(k, _)| k).collect();
+        paths.sort();
+        assert_eq!(paths, vec![b"b".to_vec(), b"bc".to_vec(), b"c".to_vec(), b"yz".to_vec(), b"z".to_vec()]);
+
+        //Dividing by a prefix whose full path is a value yields the empty path
+        let b2: BytesTrieMap<()> = ["q"].into_iter().map(|k| (k, ())).collect();
+        let quotient = a.left_quotient(&b2);
+        assert_eq!(quotient.val_count(), 1);
+        assert_eq!(quotient.get([]), Some(&()));
+
+        //`left_quotient` undoes `insert_prefix`
+        let mut prefixed = a.clone();
+        let mut wz = prefixed.write_zipper();
+        wz.insert_prefix(b"pre:");
+        drop(wz);
+        let quotient = prefixed.left_quotient(&BytesTrieMap::single("pre:", ()));
+        assert_eq!(quotient.val_count(), a.val_count());
+        for (path, _) in a.iter() {
+            assert!(quotient.contains(path));
+        }
+    }
 }
 
 //GOAT, Consider refactor of zipper traits.  `WriteZipper` -> `PathWriter`.  Zipper is split into the zipper
@@ -0,0 +1,422 @@
+use std::collections::VecDeque;
+
+use crate::{Allocator, GlobalAlloc};
+use crate::ring::{AlgebraicStatus, Lattice};
+use crate::trie_map::BytesTrieMap;
+use crate::utils::ByteMask;
+use crate::zipper::*;
+
+/// Wraps a [write zipper](ZipperWriting), and records a journal of its changes so they can be undone and
+/// redone
+///
+/// Before each journaled operation, the value at the zipper's focus is recorded, along with the subtrie below
+/// the focus if the operation changes it.  Recording a subtrie is cheap because the journal shares its nodes
+/// with the trie.  [undo](UndoZipper::undo) restores
+/// the most recently recorded state, at the path where it was recorded, regardless of where the zipper's
+/// focus is now.
+///
+/// The journal can be bounded by the number of entries, and by an estimate of the memory they retain.
+/// When a bound is exceeded, the oldest entries are discarded.  Entries that can be redone count against
+/// the memory bound too, and the ones furthest from being redone are discarded first.  An operation whose
+/// own entry exceeds the memory bound can't be undone, and clears the journal.
+///
+/// ```
+/// # use pathmap::trie_map::BytesTrieMap;
+/// # use pathmap::zipper::*;
+/// let mut map = BytesTrieMap::<usize>::new();
+/// let mut z = UndoZipper::new(map.write_zipper());
+/// z.descend_to(b"title");
+/// z.set_value(1);
+/// z.set_value(2);
+/// assert!(z.undo());
+/// assert_eq!(z.value(), Some(&1));
+/// assert!(z.redo());
+/// assert_eq!(z.value(), Some(&2));
+/// ```
+pub struct UndoZipper<Z, V: Clone + Send + Sync, A: Allocator = GlobalAlloc> {
+    z: Z,
+    undo: VecDeque<JournalEntry<V, A>>,
+    redo: VecDeque<JournalEntry<V, A>>,
+    max_entries: usize,
+    max_bytes: usize,
+    undo_bytes: usize,
+    redo_bytes: usize,
+}
+
+/// The state of a subtrie, recorded in the journal
+struct JournalEntry<V: Clone + Send + Sync, A: Allocator> {
+    /// The path to the subtrie, relative to the zipper's root
+    path: Vec<u8>,
+    /// The recorded subtrie, or `None` if the operation only changed the value
+    subtrie: Option<Option<BytesTrieMap<V, A>>>,
+    value: Option<V>,
+    /// The estimated memory retained by the entry, or 0 if the journal isn't bounded by memory
+    bytes: usize,
+}
+
+impl<Z, V, A> UndoZipper<Z, V, A>
+    where
+    Z: ZipperWriting<V, A> + ZipperMoving + ZipperSubtries<V, A>,
+    V: Clone + Send + Sync + Unpin,
+    A: Allocator,
+{
+    /// Creates a new `UndoZipper` with an unbounded journal
+    pub fn new(z: Z) -> Self {
+        Self::new_with_limits(z, usize::MAX, usize::MAX)
+    }
+    /// Creates a new `UndoZipper` with a journal that holds at most `max_entries` undoable operations, and
+    /// retains at most an estimated `max_bytes` of memory, counting the entries that can be redone
+    ///
+    /// The memory retained by an entry is estimated from the size of its path, and the size of every value
+    /// it records.  Only the values in a recorded subtrie up to the `max_bytes` bound are counted, so the
+    /// estimate costs at most that many steps.  This overestimates the real cost when the recorded subtrie
+    /// still shares nodes with the trie.  Pass `usize::MAX` for `max_bytes` to skip the estimate.
+    pub fn new_with_limits(z: Z, max_entries: usize, max_bytes: usize) -> Self {
+        Self {
+            z,
+            undo: VecDeque::new(),
+            redo: VecDeque::new(),
+            max_entries,
+            max_bytes,
+            undo_bytes: 0,
+            redo_bytes: 0,
+        }
+    }
+    /// Consumes the `UndoZipper`, discarding the journal, and returns the wrapped zipper
+    pub fn into_inner(self) -> Z {
+        self.z
+    }
+    /// Returns the number of operations that can be undone
+    pub fn undo_count(&self) -> usize {
+        self.undo.len()
+    }
+    /// Returns the number of operations that can be redone
+    pub fn redo_count(&self) -> usize {
+        self.redo.len()
+    }
+    /// Discards all entries in the journal
+    pub fn clear_journal(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.undo_bytes = 0;
+        self.redo_bytes = 0;
+    }
+    /// Reverts the most recent journaled operation that hasn't been undone.  Returns `false` if there is
+    /// nothing to undo
+    ///
+    /// The zipper's focus is unchanged.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop_back() {
+            Some(entry) => {
+                self.undo_bytes -= entry.bytes;
+                let replaced = self.restore(entry);
+                self.push_redo(replaced);
+                true
+            },
+            None => false
+        }
+    }
+    /// Re-applies the most recently undone operation.  Returns `false` if there is nothing to redo
+    ///
+    /// Any journaled operation clears the operations available to redo.  The zipper's focus is unchanged.
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop_back() {
+            Some(entry) => {
+                self.redo_bytes -= entry.bytes;
+                let replaced = self.restore(entry);
+                self.push_undo(replaced);
+                true
+            },
+            None => false
+        }
+    }
+    /// Journaled version of [ZipperWriting::set_value]
+    pub fn set_value(&mut self, val: V) -> Option<V> {
+        self.record(false);
+        self.z.set_value(val)
+    }
+    /// Journaled version of [ZipperWriting::remove_value]
+    pub fn remove_value(&mut self) -> Option<V> {
+        self.record(false);
+        self.z.remove_value()
+    }
+    /// Journaled version of [ZipperWriting::graft]
+    pub fn graft<RZ: ZipperSubtries<V, A>>(&mut self, read_zipper: &RZ) {
+        self.record(true);
+        self.z.graft(read_zipper)
+    }
+    /// Journaled version of [ZipperWriting::graft_map]
+    pub fn graft_map(&mut self, map: BytesTrieMap<V, A>) {
+        self.record(true);
+        self.z.graft_map(map)
+    }
+    /// Journaled version of [ZipperWriting::join]
+    pub fn join<RZ: ZipperSubtries<V, A>>(&mut self, read_zipper: &RZ) -> AlgebraicStatus where V: Lattice {
+        self.record(true);
+        self.z.join(read_zipper)
+    }
+    /// Journaled version of [ZipperWriting::remove_branches]
+    pub fn remove_branches(&mut self) -> bool {
+        self.record(true);
+        self.z.remove_branches()
+    }
+    /// Records the state at the focus as a new undoable entry
+    fn record(&mut self, with_subtrie: bool) {
+        let entry = self.capture(with_subtrie);
+        self.redo.clear();
+        self.redo_bytes = 0;
+        self.push_undo(entry);
+    }
+    fn capture(&self, with_subtrie: bool) -> JournalEntry<V, A> {
+        let path = self.z.path().to_vec();
+        let subtrie = with_subtrie.then(|| self.z.make_map());
+        let value = self.z.value().cloned();
+        let bytes = if self.max_bytes == usize::MAX {
+            0
+        } else {
+            let val_size = core::mem::size_of::<V>().max(1);
+            let fixed_bytes = core::mem::size_of::<JournalEntry<V, A>>() + path.len() + value.is_some() as usize * val_size;
+            //Counting stops once the entry is known to exceed the bound by itself
+            let limit = self.max_bytes.saturating_sub(fixed_bytes) / val_size + 1;
+            let mut subtrie_val_cnt = 0;
+            if let Some(Some(map)) = &subtrie {
+                let mut rz = map.read_zipper();
+                while subtrie_val_cnt < limit && rz.to_next_val() {
+                    subtrie_val_cnt += 1;
+                }
+            }
+            fixed_bytes.saturating_add(subtrie_val_cnt * val_size)
+        };
+        JournalEntry { path, subtrie, value, bytes }
+    }
+    fn push_undo(&mut self, entry: JournalEntry<V, A>) {
+        if entry.bytes > self.max_bytes {
+            self.undo.clear();
+            self.undo_bytes = 0;
+            return
+        }
+        self.undo_bytes += entry.bytes;
+        self.undo.push_back(entry);
+        while self.undo.len() > self.max_entries {
+            self.discard_oldest_undo();
+        }
+        //The new entry fits by itself, so it's never discarded
+        while self.journal_bytes() > self.max_bytes {
+            if !(self.discard_oldest_redo() || (self.undo.len() > 1 && self.discard_oldest_undo())) {
+                break
+            }
+        }
+    }
+    fn push_redo(&mut self, entry: JournalEntry<V, A>) {
+        //The remaining redo entries were recorded after this one, so they can't be redone without it
+        if entry.bytes > self.max_bytes {
+            self.redo.clear();
+            self.redo_bytes = 0;
+            return
+        }
+        self.redo_bytes += entry.bytes;
+        self.redo.push_back(entry);
+        while self.journal_bytes() > self.max_bytes {
+            if !((self.redo.len() > 1 && self.discard_oldest_redo()) || self.discard_oldest_undo()) {
+                break
+            }
+        }
+    }
+    /// Discards the redo entry that would be redone last.  Returns `false` if there are no redo entries
+    fn discard_oldest_redo(&mut self) -> bool {
+        match self.redo.pop_front() {
+            Some(oldest) => { self.redo_bytes -= oldest.bytes; true },
+            None => false
+        }
+    }
+    /// Discards the undo entry that would be undone last.  Returns `false` if there are no undo entries
+    fn discard_oldest_undo(&mut self) -> bool {
+        match self.undo.pop_front() {
+            Some(oldest) => { self.undo_bytes -= oldest.bytes; true },
+            None => false
+        }
+    }
+    /// Returns the estimated memory retained by both the undo and redo entries
+    fn journal_bytes(&self) -> usize {
+        self.undo_bytes.saturating_add(self.redo_bytes)
+    }
+    /// Restores the state recorded in `entry`, and returns the state it replaced
+    fn restore(&mut self, entry: JournalEntry<V, A>) -> JournalEntry<V, A> {
+        let focus = self.z.path().to_vec();
+        self.z.reset();
+        self.z.descend_to(&entry.path);
+        let replaced = self.capture(entry.subtrie.is_some());
+        match entry.subtrie {
+            Some(Some(map)) => self.z.graft_map(map),
+            Some(None) => { self.z.remove_branches(); },
+            None => {}
+        }
+        let _ = match entry.value {
+            Some(val) => self.z.set_value(val),
+            None => self.z.remove_value()
+        };
+        self.z.reset();
+        self.z.descend_to(focus);
+        replaced
+    }
+}
+
+impl<Z: Zipper, V: Clone + Send + Sync, A: Allocator> Zipper for UndoZipper<Z, V, A> {
+    fn path_exists(&self) -> bool { self.z.path_exists() }
+    fn is_value(&self) -> bool { self.z.is_value() }
+    fn child_count(&self) -> usize { self.z.child_count() }
+    fn child_mask(&self) -> ByteMask { self.z.child_mask() }
+}
+
+impl<Z: ZipperValues<V>, V: Clone + Send + Sync, A: Allocator> ZipperValues<V> for UndoZipper<Z, V, A> {
+    fn value(&self) -> Option<&V> { self.z.value() }
+}
+
+impl<Z: ZipperMoving, V: Clone + Send + Sync, A: Allocator> ZipperMoving for UndoZipper<Z, V, A> {
+    crate::zipper::forward_zipper_moving!();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trie_map::BytesTrieMap;
+    use crate::zipper::*;
+
+    fn contents(map: &BytesTrieMap<usize>) -> Vec<(Vec<u8>, usize)> {
+        map.iter().map(|(path, val)| (path, *val)).collect()
+    }
+
+    #[test]
+    fn undo_zipper_test1() {
+        let mut map = BytesTrieMap::<usize>::from_iter([("doc:a", 1), ("doc:b", 2), ("doc:c", 3)]);
+        let original = contents(&map);
+        let src = BytesTrieMap::<usize>::from_iter([("x", 10), ("y", 11)]);
+
+        let mut z = UndoZipper::new(map.write_zipper_at_path(b"doc:"));
+        z.descend_to(b"a");
+        assert_eq!(z.set_value(100), Some(1));
+        z.reset();
+        z.descend_to(b"b");
+        assert_eq!(z.remove_value(), Some(2));
+        z.reset();
+        z.descend_to(b"new:");
+        z.graft(&src.read_zipper());
+        z.reset();
+        z.descend_to(b"c");
+        z.join(&src.read_zipper());
+        z.reset();
+        assert!(z.remove_branches());
+        assert_eq!(z.val_count(), 0);
+        assert_eq!(z.undo_count(), 5);
+
+        //Undo everything, checking the focus doesn't move
+        assert!(z.undo());
+        assert_eq!(z.val_count(), 6);
+        z.descend_to(b"new:");
+        assert!(z.undo());
+        assert!(z.undo());
+        assert_eq!(z.path(), b"new:");
+        assert!(!z.path_exists());
+        assert!(z.undo());
+        assert!(z.undo());
+        assert!(!z.undo());
+        assert_eq!(z.redo_count(), 5);
+        drop(z);
+        assert_eq!(contents(&map), original);
+
+        //Redo some of it, then make a new change that clears the redo entries
+        let mut z = UndoZipper::new(map.write_zipper_at_path(b"doc:"));
+        assert!(!z.redo());
+        z.descend_to(b"a");
+        z.set_value(100);
+        z.set_value(200);
+        assert!(z.undo());
+        assert!(z.undo());
+        assert!(z.redo());
+        assert_eq!(z.value(), Some(&100));
+        z.set_value(300);
+        assert_eq!(z.redo_count(), 0);
+        assert!(!z.redo());
+        assert!(z.undo());
+        assert_eq!(z.value(), Some(&100));
+        drop(z);
+        assert_eq!(map.get(b"doc:a"), Some(&100));
+    }
+
+    #[test]
+    fn undo_zipper_limits_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        let mut z = UndoZipper::new_with_limits(map.write_zipper(), 3, usize::MAX);
+        for i in 0..10 {
+            z.set_value(i);
+        }
+        assert_eq!(z.undo_count(), 3);
+        while z.undo() {}
+        assert_eq!(z.value(), Some(&6));
+
+        let mut z = UndoZipper::new_with_limits(z.into_inner(), usize::MAX, 1024);
+        z.descend_to(b"big");
+        for i in 0..1000usize {
+            z.descend_to(i.to_be_bytes());
+            z.set_value(i);
+            z.ascend(core::mem::size_of::<usize>());
+        }
+        //Only the most recent entries fit within the memory estimate
+        assert!(z.undo_count() < 1000);
+        assert!(z.undo_count() > 0);
+        z.clear_journal();
+        assert!(!z.undo());
+
+        //An entry that exceeds the memory bound by itself can't be undone, and clears the journal
+        let mut z = UndoZipper::new_with_limits(z.into_inner(), usize::MAX, 1024);
+        z.reset();
+        z.set_value(2000);
+        assert_eq!(z.undo_count(), 1);
+        z.descend_to(b"big");
+        assert!(z.remove_branches());
+        assert_eq!(z.undo_count(), 0);
+        assert!(!z.undo());
+        z.reset();
+        z.set_value(3000);
+        assert!(z.undo());
+        assert_eq!(z.value(), Some(&2000));
+        drop(z);
+        assert_eq!(map.val_count(), 0);
+        assert_eq!(map.get(b""), Some(&2000));
+    }
+
+    #[test]
+    fn undo_zipper_redo_limits_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        let mut z = UndoZipper::new_with_limits(map.write_zipper(), usize::MAX, 1024);
+        for (i, key) in [b"a", b"b", b"c"].into_iter().enumerate() {
+            z.descend_to(key);
+            z.set_value(i);
+            z.reset();
+        }
+        assert_eq!(z.undo_count(), 3);
+
+        //Undoing a large graft moves the grafted subtrie into the redo journal, which counts against the
+        // memory bound, so the oldest undo entries are discarded to make room
+        let src = BytesTrieMap::<usize>::from_iter((0..100usize).map(|i| (i.to_be_bytes(), i)));
+        z.descend_to(b"big");
+        z.graft(&src.read_zipper());
+        assert_eq!(z.undo_count(), 4);
+        assert!(z.undo());
+        assert_eq!(z.val_count(), 0);
+        assert_eq!(z.redo_count(), 1);
+        assert!(z.undo_count() < 3);
+        assert!(z.redo());
+        assert_eq!(z.val_count(), 100);
+        assert_eq!(z.redo_count(), 0);
+
+        //A graft whose undone state exceeds the memory bound by itself can't be redone
+        let src = BytesTrieMap::<usize>::from_iter((0..200usize).map(|i| (i.to_be_bytes(), i)));
+        z.graft(&src.read_zipper());
+        assert!(z.undo());
+        assert_eq!(z.val_count(), 100);
+        assert_eq!(z.redo_count(), 0);
+        assert!(!z.redo());
+        drop(z);
+        assert_eq!(map.val_count(), 103);
+    }
+}
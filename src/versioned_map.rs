@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::trie_map::BytesTrieMap;
+use crate::trie_diff::DiffEntry;
+
+/// Identifies a version committed to a [VersionedPathMap]
+///
+/// Versions are numbered in the order they were committed, so a larger `VersionId` is always newer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VersionId(u64);
+
+impl VersionId {
+    /// Returns the sequence number of the version
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl core::fmt::Display for VersionId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "v{}", self.0)
+    }
+}
+
+/// Information about a version, returned by [VersionedPathMap::versions]
+#[derive(Clone, Debug)]
+pub struct VersionInfo<'a> {
+    /// The version's identifier
+    pub id: VersionId,
+    /// The nearest retained version this version was derived from, or `None` if there is none
+    pub parent: Option<VersionId>,
+    /// The message supplied when the version was committed
+    pub message: &'a str,
+}
+
+struct VersionRecord<V: Clone + Send + Sync> {
+    map: Arc<BytesTrieMap<V>>,
+    parent: Option<VersionId>,
+    message: String,
+}
+
+/// A [BytesTrieMap] with a history of committed versions
+///
+/// Changes are made to a working map, and [commit](VersionedPathMap::commit) records a snapshot of the
+/// working map as a new version.  Snapshots share all unchanged nodes with each other and with the
+/// working map, so the cost of each version is proportional to the amount of change since the previous
+/// version.
+///
+/// Any version can be inspected with [version](VersionedPathMap::version), or restored into the working map
+/// with [checkout](VersionedPathMap::checkout).  Old versions are released with [gc](VersionedPathMap::gc),
+/// which won't remove a version while a snapshot of it is held elsewhere.
+///
+/// ```
+/// # use pathmap::versioned_map::VersionedPathMap;
+/// let mut vmap = VersionedPathMap::new();
+/// vmap.working_mut().insert("config:mode", "fast");
+/// let v1 = vmap.commit("initial config");
+/// vmap.working_mut().insert("config:mode", "safe");
+/// let v2 = vmap.commit("switch to safe mode");
+///
+/// assert_eq!(vmap.version(v1).unwrap().get("config:mode"), Some(&"fast"));
+/// assert_eq!(vmap.version_diff(v2).unwrap().count(), 1);
+/// vmap.checkout(v1);
+/// assert_eq!(vmap.working().get("config:mode"), Some(&"fast"));
+/// ```
+pub struct VersionedPathMap<V: Clone + Send + Sync> {
+    working: BytesTrieMap<V>,
+    versions: BTreeMap<VersionId, VersionRecord<V>>,
+    head: Option<VersionId>,
+    next_id: u64,
+}
+
+impl<V: Clone + Send + Sync + Unpin> Default for VersionedPathMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin> From<BytesTrieMap<V>> for VersionedPathMap<V> {
+    fn from(map: BytesTrieMap<V>) -> Self {
+        Self {
+            working: map,
+            versions: BTreeMap::new(),
+            head: None,
+            next_id: 0,
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + Unpin> VersionedPathMap<V> {
+    /// Creates a new `VersionedPathMap` with an empty working map and no versions
+    pub fn new() -> Self {
+        Self::from(BytesTrieMap::new())
+    }
+    /// Returns a reference to the working map
+    pub fn working(&self) -> &BytesTrieMap<V> {
+        &self.working
+    }
+    /// Returns a mutable reference to the working map, to make changes that will be recorded by the next
+    /// [commit](VersionedPathMap::commit)
+    pub fn working_mut(&mut self) -> &mut BytesTrieMap<V> {
+        &mut self.working
+    }
+    /// Returns the version that was most recently committed or checked out, or `None` if there is no version
+    pub fn head(&self) -> Option<VersionId> {
+        self.head
+    }
+    /// Records a snapshot of the working map as a new version, and makes it the head
+    pub fn commit<S: Into<String>>(&mut self, message: S) -> VersionId {
+        let id = VersionId(self.next_id);
+        self.next_id += 1;
+        let record = VersionRecord {
+            map: Arc::new(self.working.clone()),
+            parent: self.head,
+            message: message.into(),
+        };
+        self.versions.insert(id, record);
+        self.head = Some(id);
+        id
+    }
+    /// Replaces the working map with the contents of the version `id`, and makes it the head.  Returns
+    /// `false` if the version doesn't exist
+    ///
+    /// Uncommitted changes in the working map are discarded.
+    pub fn checkout(&mut self, id: VersionId) -> bool {
+        match self.versions.get(&id) {
+            Some(record) => {
+                self.working = (*record.map).clone();
+                self.head = Some(id);
+                true
+            },
+            None => false
+        }
+    }
+    /// Returns a snapshot of the version `id`, or `None` if the version doesn't exist
+    ///
+    /// The version will not be removed by [gc](VersionedPathMap::gc) while the returned snapshot is held.
+    pub fn version(&self, id: VersionId) -> Option<Arc<BytesTrieMap<V>>> {
+        self.versions.get(&id).map(|record| record.map.clone())
+    }
+    /// Returns an iterator over the retained versions, from oldest to newest
+    pub fn versions(&self) -> impl Iterator<Item=VersionInfo<'_>> + '_ {
+        self.versions.iter().map(|(id, record)| VersionInfo {
+            id: *id,
+            parent: record.parent,
+            message: &record.message,
+        })
+    }
+    /// Returns the number of retained versions
+    pub fn version_count(&self) -> usize {
+        self.versions.len()
+    }
+    /// Returns the differences introduced by version `id`, relative to its parent version, or `None` if the
+    /// version doesn't exist
+    ///
+    /// A version without a parent is compared against an empty map.
+    pub fn version_diff(&self, id: VersionId) -> Option<Box<dyn Iterator<Item=DiffEntry<'_, V>> + '_>>
+        where V: PartialEq
+    {
+        let record = self.versions.get(&id)?;
+        match record.parent.and_then(|parent| self.versions.get(&parent)) {
+            Some(parent) => Some(Box::new(record.map.diff(&parent.map))),
+            None => Some(Box::new(record.map.iter().map(|(path, value)| DiffEntry::Added { path, value }))),
+        }
+    }
+    /// Returns the differences between versions `old` and `new`, or `None` if either version doesn't exist
+    pub fn diff_versions(&self, old: VersionId, new: VersionId) -> Option<impl Iterator<Item=DiffEntry<'_, V>> + '_>
+        where V: PartialEq
+    {
+        let old = self.versions.get(&old)?;
+        let new = self.versions.get(&new)?;
+        Some(new.map.diff(&old.map))
+    }
+    /// Returns the differences between the head version and the working map.  If there is no head, the
+    /// working map is compared against an empty map
+    pub fn uncommitted_changes(&self) -> Box<dyn Iterator<Item=DiffEntry<'_, V>> + '_>
+        where V: PartialEq
+    {
+        match self.head.and_then(|head| self.versions.get(&head)) {
+            Some(head) => Box::new(self.working.diff(&head.map)),
+            None => Box::new(self.working.iter().map(|(path, value)| DiffEntry::Added { path, value })),
+        }
+    }
+    /// Removes versions that are older than the `keep_latest` most recent versions, and returns the number
+    /// of versions removed
+    ///
+    /// The head version is always kept, as is any version with a snapshot held outside the
+    /// `VersionedPathMap`.  The nodes of a removed version are freed when they aren't shared with any
+    /// remaining version.  A version whose parent is removed is reparented to the nearest retained ancestor.
+    pub fn gc(&mut self, keep_latest: usize) -> usize {
+        let kept: Vec<VersionId> = self.versions.keys().rev().take(keep_latest).copied().collect();
+        let removable: Vec<VersionId> = self.versions.iter()
+            .filter(|(id, record)| {
+                !kept.contains(id) &&
+                    Some(**id) != self.head &&
+                    Arc::strong_count(&record.map) == 1
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in removable.iter() {
+            let removed = self.versions.remove(id).unwrap();
+            for record in self.versions.values_mut() {
+                if record.parent == Some(*id) {
+                    record.parent = removed.parent;
+                }
+            }
+        }
+        removable.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versioned_map_test1() {
+        let mut vmap = VersionedPathMap::new();
+        assert_eq!(vmap.head(), None);
+        vmap.working_mut().insert("a", 1);
+        vmap.working_mut().insert("b", 2);
+        let v0 = vmap.commit("first");
+        vmap.working_mut().insert("c", 3);
+        vmap.working_mut().remove("a");
+        let v1 = vmap.commit("second");
+        vmap.working_mut().insert("b", 20);
+        let v2 = vmap.commit("third");
+        assert!(v0 < v1 && v1 < v2);
+        assert_eq!(vmap.head(), Some(v2));
+        assert_eq!(vmap.uncommitted_changes().count(), 0);
+
+        let versions: Vec<_> = vmap.versions().map(|info| (info.id, info.parent, info.message.to_string())).collect();
+        assert_eq!(versions, vec![
+            (v0, None, "first".to_string()),
+            (v1, Some(v0), "second".to_string()),
+            (v2, Some(v1), "third".to_string()),
+        ]);
+
+        let diff: Vec<_> = vmap.version_diff(v0).unwrap().map(|entry| entry.path().to_vec()).collect();
+        assert_eq!(diff, vec![b"a".to_vec(), b"b".to_vec()]);
+        let diff: Vec<_> = vmap.version_diff(v1).unwrap().collect();
+        assert_eq!(diff, vec![
+            DiffEntry::Removed { path: b"a".to_vec(), value: &1 },
+            DiffEntry::Added { path: b"c".to_vec(), value: &3 },
+        ]);
+        let diff: Vec<_> = vmap.diff_versions(v0, v2).unwrap().collect();
+        assert_eq!(diff, vec![
+            DiffEntry::Removed { path: b"a".to_vec(), value: &1 },
+            DiffEntry::Changed { path: b"b".to_vec(), old: &2, new: &20 },
+            DiffEntry::Added { path: b"c".to_vec(), value: &3 },
+        ]);
+
+        //Check out an old version, and branch from it
+        vmap.working_mut().insert("uncommitted", 0);
+        assert_eq!(vmap.uncommitted_changes().count(), 1);
+        assert!(vmap.checkout(v0));
+        assert_eq!(vmap.working().get("a"), Some(&1));
+        assert_eq!(vmap.working().get("uncommitted"), None);
+        vmap.working_mut().insert("d", 4);
+        let v3 = vmap.commit("branch");
+        assert_eq!(vmap.versions().last().unwrap().parent, Some(v0));
+        assert_eq!(vmap.version(v2).unwrap().get("b"), Some(&20));
+        assert_eq!(vmap.version(v3).unwrap().val_count(), 3);
+        assert!(!vmap.checkout(VersionId(100)));
+        assert!(vmap.version_diff(VersionId(100)).is_none());
+    }
+
+    #[test]
+    fn versioned_map_gc_test() {
+        let mut vmap = VersionedPathMap::new();
+        let mut ids = vec![];
+        for i in 0..10u64 {
+            vmap.working_mut().insert(i.to_be_bytes(), i);
+            ids.push(vmap.commit(format!("insert {i}")));
+        }
+
+        //A held snapshot prevents its version from being collected
+        let held = vmap.version(ids[2]).unwrap();
+        assert_eq!(vmap.gc(3), 6);
+        let remaining: Vec<_> = vmap.versions().map(|info| info.id).collect();
+        assert_eq!(remaining, vec![ids[2], ids[7], ids[8], ids[9]]);
+        assert_eq!(vmap.versions().nth(1).unwrap().parent, Some(ids[2]));
+        assert_eq!(held.val_count(), 3);
+        drop(held);
+
+        //The diff of a reparented version spans all the removed versions
+        assert_eq!(vmap.version_diff(ids[7]).unwrap().count(), 5);
+
+        //The head is kept, even when it's not among the latest versions
+        assert!(vmap.checkout(ids[2]));
+        assert_eq!(vmap.gc(1), 2);
+        let remaining: Vec<_> = vmap.versions().map(|info| info.id).collect();
+        assert_eq!(remaining, vec![ids[2], ids[9]]);
+        assert_eq!(vmap.versions().nth(1).unwrap().parent, Some(ids[2]));
+
+        assert_eq!(vmap.gc(0), 1);
+        assert_eq!(vmap.version_count(), 1);
+        assert_eq!(vmap.head(), Some(ids[2]));
+    }
+}
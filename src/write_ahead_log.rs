@@ -0,0 +1,504 @@
+//! A write-ahead log that records every change to a [BytesTrieMap], so the map can be recovered after a
+//! crash
+//!
+//! A [WalPathMap] appends a record describing each change to a log before applying the change to its map.
+//! The map is recovered by [recover], which replays the log on top of the most recent snapshot written by
+//! [write_snapshot] or [WalPathMap::compact].
+//!
+//! ## Format
+//!
+//! Snapshots and logs share a format: an 8-byte header, followed by a sequence of records.  Each record
+//! begins with the length of its payload and a CRC-32 checksum of the payload, both as little-endian `u32`s.
+//! The payload is a tag byte, the record's path prefixed by its length as a LEB128 varint, and for records
+//! that set a value, the encoded value.  A snapshot ends with a record that marks it as complete.
+//!
+//! A log that ends part-way through a record, as happens when the process is killed in the middle of a
+//! write, is recovered up to the last complete record.  The same applies when the final record's checksum
+//! doesn't match, because an interrupted write can leave the record's full length in place before its
+//! contents reach the disk.  A checksum that doesn't match on any other record is reported as an error.
+//! The log should be truncated to [ReplayStats::valid_len] before any more records are appended to it.
+
+use std::io::{Read, Write};
+
+use crate::trie_map::BytesTrieMap;
+use crate::trie_diff::{invalid_data, push_bytes, ByteReader};
+use crate::zipper::{ZipperValues, ZipperWriting};
+
+const WAL_MAGIC: &[u8; 8] = b"PMWALOG1";
+
+const TAG_END: u8 = 0;
+const TAG_SET: u8 = 1;
+const TAG_REMOVE: u8 = 2;
+const TAG_REMOVE_PREFIX: u8 = 3;
+
+/// Summary of a log replayed by [replay] or [recover]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    /// The number of records applied
+    pub records: usize,
+    /// `true` if the log ended with an incomplete or damaged record, which was ignored
+    pub torn_tail: bool,
+    /// The length in bytes of the log up to the end of the last record that was applied
+    pub valid_len: u64,
+}
+
+/// A [BytesTrieMap] that appends every change to a write-ahead log
+///
+/// The log is written before the change is applied, so the map never contains a change that isn't in the
+/// log.  `encode_f` appends the encoding of a value to a buffer, and must be the inverse of the function
+/// passed to [recover].
+///
+/// Records are written with [Write::write_all] and no buffering is added, so wrap the log in a
+/// [BufWriter](std::io::BufWriter) to batch writes.  [flush](WalPathMap::flush) only flushes the writer; to
+/// make a file-backed log durable, call [File::sync_data](std::fs::File::sync_data) through
+/// [log](WalPathMap::log).
+///
+/// ```
+/// # use pathmap::write_ahead_log::*;
+/// let mut log_file = vec![];
+/// let mut wal = WalPathMap::create(Default::default(), &mut log_file, |v: &u32, buf: &mut Vec<u8>| buf.extend(v.to_le_bytes())).unwrap();
+/// wal.insert(b"counter", 1).unwrap();
+/// wal.insert(b"counter", 2).unwrap();
+/// drop(wal);
+///
+/// let (map, _stats) = recover(None::<&[u8]>, &log_file[..], |bytes| u32::from_le_bytes(bytes.try_into().unwrap())).unwrap();
+/// assert_eq!(map.get(b"counter"), Some(&2));
+/// ```
+pub struct WalPathMap<V: Clone + Send + Sync, W, F> {
+    map: BytesTrieMap<V>,
+    log: W,
+    encode_f: F,
+    buf: Vec<u8>,
+    records: usize,
+}
+
+impl<V, W, F> WalPathMap<V, W, F>
+    where
+    V: Clone + Send + Sync + Unpin,
+    W: Write,
+    F: FnMut(&V, &mut Vec<u8>),
+{
+    /// Creates a new `WalPathMap` containing `map`, and writes the log header to `log`, which should be empty
+    ///
+    /// `map` must already be recoverable, i.e. either empty, or the contents of a snapshot.
+    pub fn create(map: BytesTrieMap<V>, mut log: W, encode_f: F) -> std::io::Result<Self> {
+        log.write_all(WAL_MAGIC)?;
+        Ok(Self::resume(map, log, encode_f))
+    }
+    /// Creates a `WalPathMap` that appends to an existing log
+    ///
+    /// `map` should be the result of [recover], and `log` should be positioned at the end of the log that
+    /// was recovered.  If the recovered log had a [torn tail](ReplayStats::torn_tail), it must first be
+    /// truncated to its [valid_len](ReplayStats::valid_len).
+    pub fn resume(map: BytesTrieMap<V>, log: W, encode_f: F) -> Self {
+        Self {
+            map,
+            log,
+            encode_f,
+            buf: Vec::new(),
+            records: 0,
+        }
+    }
+    /// Returns a reference to the map
+    pub fn map(&self) -> &BytesTrieMap<V> {
+        &self.map
+    }
+    /// Returns a reference to the log
+    pub fn log(&self) -> &W {
+        &self.log
+    }
+    /// Returns the number of records written to the log by this `WalPathMap` since it was created, resumed,
+    /// or compacted
+    pub fn records(&self) -> usize {
+        self.records
+    }
+    /// Consumes the `WalPathMap`, and returns the map and the log
+    pub fn into_parts(self) -> (BytesTrieMap<V>, W) {
+        (self.map, self.log)
+    }
+    /// Flushes the log
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.log.flush()
+    }
+    /// Logs and then sets the value at `path`, returning the value it replaced
+    pub fn insert<K: AsRef<[u8]>>(&mut self, path: K, val: V) -> std::io::Result<Option<V>> {
+        let path = path.as_ref();
+        self.buf.clear();
+        self.buf.push(TAG_SET);
+        push_bytes(&mut self.buf, path);
+        (self.encode_f)(&val, &mut self.buf);
+        self.append()?;
+        Ok(apply_set(&mut self.map, path, val))
+    }
+    /// Logs and then removes the value at `path`, returning the removed value
+    pub fn remove<K: AsRef<[u8]>>(&mut self, path: K) -> std::io::Result<Option<V>> {
+        let path = path.as_ref();
+        self.start_record(TAG_REMOVE, path);
+        self.append()?;
+        Ok(apply_remove(&mut self.map, path))
+    }
+    /// Logs and then removes every value at or below `prefix`.  Returns `true` if anything was removed
+    pub fn remove_prefix<K: AsRef<[u8]>>(&mut self, prefix: K) -> std::io::Result<bool> {
+        let prefix = prefix.as_ref();
+        self.start_record(TAG_REMOVE_PREFIX, prefix);
+        self.append()?;
+        Ok(apply_remove_prefix(&mut self.map, prefix))
+    }
+    /// Writes a snapshot of the map to `snapshot`, and replaces the log with `new_log`, which should be
+    /// empty.  Returns the replaced log
+    ///
+    /// The replaced log is still needed to recover the map until `snapshot` is complete and durable.  A
+    /// file-backed snapshot should be written to a temporary file and then renamed into place, so a crash
+    /// can't leave a partial snapshot in place of the last complete one.
+    pub fn compact<S: Write>(&mut self, snapshot: &mut S, mut new_log: W) -> std::io::Result<W> {
+        write_snapshot(&self.map, snapshot, &mut self.encode_f)?;
+        snapshot.flush()?;
+        new_log.write_all(WAL_MAGIC)?;
+        self.records = 0;
+        Ok(core::mem::replace(&mut self.log, new_log))
+    }
+    fn start_record(&mut self, tag: u8, path: &[u8]) {
+        self.buf.clear();
+        self.buf.push(tag);
+        push_bytes(&mut self.buf, path);
+    }
+    fn append(&mut self) -> std::io::Result<()> {
+        write_record(&mut self.log, &self.buf)?;
+        self.records += 1;
+        Ok(())
+    }
+}
+
+/// Writes a snapshot of `map` to `target`, using `encode_f` to append the encoding of each value to a
+/// buffer.  Returns the number of bytes written
+pub fn write_snapshot<V, S, F>(map: &BytesTrieMap<V>, target: &mut S, mut encode_f: F) -> std::io::Result<usize>
+    where
+    V: Clone + Send + Sync + Unpin,
+    S: Write,
+    F: FnMut(&V, &mut Vec<u8>),
+{
+    target.write_all(WAL_MAGIC)?;
+    let mut total = WAL_MAGIC.len();
+    let mut buf = Vec::new();
+    let root_val = map.read_zipper().value().cloned();
+    let values = root_val.as_ref().map(|val| (vec![], val)).into_iter().chain(map.iter());
+    for (path, val) in values {
+        buf.clear();
+        buf.push(TAG_SET);
+        push_bytes(&mut buf, &path);
+        encode_f(val, &mut buf);
+        total += write_record(target, &buf)?;
+    }
+    buf.clear();
+    buf.push(TAG_END);
+    push_bytes(&mut buf, &[]);
+    total += write_record(target, &buf)?;
+    Ok(total)
+}
+
+/// Applies the records in the log read from `source` to `map`, using `decode_f` to decode each value
+pub fn replay<V, R, F>(map: &mut BytesTrieMap<V>, source: R, mut decode_f: F) -> std::io::Result<ReplayStats>
+    where
+    V: Clone + Send + Sync + Unpin,
+    R: Read,
+    F: FnMut(&[u8]) -> V,
+{
+    replay_internal(map, source, &mut decode_f).map(|(stats, _complete)| stats)
+}
+
+/// Rebuilds a map by replaying the log read from `log` on top of the snapshot read from `snapshot`, or on
+/// top of an empty map if there is no snapshot
+///
+/// Returns an error if the snapshot is incomplete.  The returned [ReplayStats] describe the log.
+pub fn recover<V, S, R, F>(snapshot: Option<S>, log: R, mut decode_f: F) -> std::io::Result<(BytesTrieMap<V>, ReplayStats)>
+    where
+    V: Clone + Send + Sync + Unpin,
+    S: Read,
+    R: Read,
+    F: FnMut(&[u8]) -> V,
+{
+    let mut map = BytesTrieMap::new();
+    if let Some(snapshot) = snapshot {
+        let (_, complete) = replay_internal(&mut map, snapshot, &mut decode_f)?;
+        if !complete {
+            return Err(invalid_data("incomplete write-ahead log snapshot"))
+        }
+    }
+    let (stats, _) = replay_internal(&mut map, log, &mut decode_f)?;
+    Ok((map, stats))
+}
+
+/// Returns the stats, and `true` if the replay ended at an end-of-snapshot record
+fn replay_internal<V, R, F>(map: &mut BytesTrieMap<V>, mut source: R, decode_f: &mut F) -> std::io::Result<(ReplayStats, bool)>
+    where
+    V: Clone + Send + Sync + Unpin,
+    R: Read,
+    F: FnMut(&[u8]) -> V,
+{
+    let mut stats = ReplayStats::default();
+    let mut magic = [0u8; WAL_MAGIC.len()];
+    match read_full(&mut source, &mut magic)? {
+        0 => return Ok((stats, false)),
+        len if len < magic.len() => {
+            stats.torn_tail = true;
+            return Ok((stats, false))
+        },
+        _ => if &magic != WAL_MAGIC {
+            return Err(invalid_data("missing write-ahead log header"))
+        }
+    }
+    stats.valid_len = magic.len() as u64;
+
+    let mut payload = Vec::new();
+    loop {
+        let mut header = [0u8; 8];
+        match read_full(&mut source, &mut header)? {
+            0 => return Ok((stats, false)),
+            len if len < header.len() => {
+                stats.torn_tail = true;
+                return Ok((stats, false))
+            },
+            _ => {}
+        }
+        let payload_len = u32::from_le_bytes(header[..4].try_into().unwrap()) as u64;
+        let checksum = u32::from_le_bytes(header[4..].try_into().unwrap());
+        payload.clear();
+        (&mut source).take(payload_len).read_to_end(&mut payload)?;
+        if (payload.len() as u64) < payload_len {
+            stats.torn_tail = true;
+            return Ok((stats, false))
+        }
+        if crc32(&payload) != checksum {
+            //Only the final record can be damaged by an interrupted write
+            if read_full(&mut source, &mut [0u8; 1])? == 0 {
+                stats.torn_tail = true;
+                return Ok((stats, false))
+            }
+            return Err(invalid_data("checksum mismatch in write-ahead log record"))
+        }
+
+        let (tag, path, rest) = parse_payload(&payload)?;
+        stats.valid_len += (header.len() + payload.len()) as u64;
+        match tag {
+            TAG_END => return Ok((stats, true)),
+            TAG_SET => { apply_set(map, path, decode_f(rest)); },
+            TAG_REMOVE => { apply_remove(map, path); },
+            TAG_REMOVE_PREFIX => { apply_remove_prefix(map, path); },
+            _ => return Err(invalid_data("invalid write-ahead log record tag"))
+        }
+        stats.records += 1;
+    }
+}
+
+/// Writes a record containing `payload`, and returns the number of bytes written
+fn write_record<W: Write>(target: &mut W, payload: &[u8]) -> std::io::Result<usize> {
+    let mut record = Vec::with_capacity(payload.len() + 8);
+    record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    record.extend_from_slice(&crc32(payload).to_le_bytes());
+    record.extend_from_slice(payload);
+    target.write_all(&record)?;
+    Ok(record.len())
+}
+
+/// Splits a record payload into its tag, its path, and the remaining bytes
+fn parse_payload(payload: &[u8]) -> std::io::Result<(u8, &[u8], &[u8])> {
+    let mut reader = ByteReader::new(payload, "write-ahead log record");
+    let tag = reader.take(1)?[0];
+    let path = reader.bytes()?;
+    Ok((tag, path, reader.remaining()))
+}
+
+/// Reads into `buf` until it is full or the source is exhausted, and returns the number of bytes read
+fn read_full<R: Read>(source: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match source.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(len) => filled += len,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {},
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// The CRC-32 (ISO-HDLC) checksum of `bytes`
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn apply_set<V: Clone + Send + Sync + Unpin>(map: &mut BytesTrieMap<V>, path: &[u8], val: V) -> Option<V> {
+    map.write_zipper_at_path(path).set_value(val)
+}
+
+fn apply_remove<V: Clone + Send + Sync + Unpin>(map: &mut BytesTrieMap<V>, path: &[u8]) -> Option<V> {
+    map.write_zipper_at_path(path).remove_value()
+}
+
+fn apply_remove_prefix<V: Clone + Send + Sync + Unpin>(map: &mut BytesTrieMap<V>, path: &[u8]) -> bool {
+    let mut wz = map.write_zipper_at_path(path);
+    let removed_branches = wz.remove_branches();
+    let removed_val = wz.remove_value().is_some();
+    removed_branches || removed_val
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{File, OpenOptions};
+    use std::io::{BufReader, Seek, SeekFrom, Write};
+    use crate::trie_map::BytesTrieMap;
+    use super::*;
+
+    fn encode(val: &String, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(val.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> String {
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    fn contents(map: &BytesTrieMap<String>) -> Vec<(Vec<u8>, String)> {
+        map.iter().map(|(path, val)| (path, val.clone())).collect()
+    }
+
+    #[test]
+    fn wal_crc32_test() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn wal_recover_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("map.log");
+
+        let mut wal = WalPathMap::create(BytesTrieMap::new(), File::create(&log_path).unwrap(), encode).unwrap();
+        wal.insert(b"user:alice", "admin".to_string()).unwrap();
+        wal.insert(b"user:bob", "guest".to_string()).unwrap();
+        wal.insert(b"user:carol", "guest".to_string()).unwrap();
+        wal.insert(b"group:admins", "alice".to_string()).unwrap();
+        assert_eq!(wal.insert(b"user:bob", "admin".to_string()).unwrap(), Some("guest".to_string()));
+        assert_eq!(wal.remove(b"user:carol").unwrap(), Some("guest".to_string()));
+        assert_eq!(wal.remove(b"user:dave").unwrap(), None);
+        assert!(wal.remove_prefix(b"group:").unwrap());
+        wal.insert(b"", "root".to_string()).unwrap();
+        assert_eq!(wal.records(), 9);
+        wal.flush().unwrap();
+        let expected = contents(wal.map());
+        drop(wal);
+
+        let (map, stats) = recover(None::<File>, BufReader::new(File::open(&log_path).unwrap()), decode).unwrap();
+        assert_eq!(stats.records, 9);
+        assert!(!stats.torn_tail);
+        assert_eq!(contents(&map), expected);
+        assert_eq!(map.read_zipper().value(), Some(&"root".to_string()));
+
+        //Resume appending to the recovered log
+        let log = OpenOptions::new().append(true).open(&log_path).unwrap();
+        let mut wal = WalPathMap::resume(map, log, encode);
+        wal.insert(b"user:erin", "guest".to_string()).unwrap();
+        let expected = contents(wal.map());
+        drop(wal);
+        let (map, stats) = recover(None::<File>, File::open(&log_path).unwrap(), decode).unwrap();
+        assert_eq!(stats.records, 10);
+        assert_eq!(contents(&map), expected);
+    }
+
+    #[test]
+    fn wal_damaged_log_test() {
+        let mut log = vec![];
+        let mut wal = WalPathMap::create(BytesTrieMap::new(), &mut log, encode).unwrap();
+        wal.insert(b"a", "1".to_string()).unwrap();
+        wal.insert(b"b", "2".to_string()).unwrap();
+        drop(wal);
+        let complete_len = log.len();
+
+        //A partial record at the end of the log is ignored
+        let mut torn = log.clone();
+        let mut wal = WalPathMap::resume(BytesTrieMap::new(), &mut torn, encode);
+        wal.insert(b"c", "3".to_string()).unwrap();
+        drop(wal);
+        for len in complete_len + 1..torn.len() {
+            let (map, stats) = recover(None::<&[u8]>, &torn[..len], decode).unwrap();
+            assert_eq!(stats, ReplayStats { records: 2, torn_tail: true, valid_len: complete_len as u64 });
+            assert_eq!(map.val_count(), 2);
+        }
+        let (_, stats) = recover(None::<&[u8]>, &torn[..], decode).unwrap();
+        assert_eq!(stats, ReplayStats { records: 3, torn_tail: false, valid_len: torn.len() as u64 });
+
+        //A damaged final record is also a torn tail, and appending after truncating it recovers normally
+        let mut damaged = torn.clone();
+        let last = damaged.len() - 1;
+        damaged[last] ^= 0xFF;
+        let (map, stats) = recover(None::<&[u8]>, &damaged[..], decode).unwrap();
+        assert_eq!(stats, ReplayStats { records: 2, torn_tail: true, valid_len: complete_len as u64 });
+        damaged.truncate(stats.valid_len as usize);
+        let mut wal = WalPathMap::resume(map, &mut damaged, encode);
+        wal.insert(b"d", "4".to_string()).unwrap();
+        drop(wal);
+        let (map, stats) = recover(None::<&[u8]>, &damaged[..], decode).unwrap();
+        assert_eq!(stats.records, 3);
+        assert_eq!(map.get(b"d"), Some(&"4".to_string()));
+        assert_eq!(map.get(b"c"), None);
+
+        //A damaged record before the end of the log is an error
+        let mut corrupted = torn.clone();
+        corrupted[complete_len - 1] ^= 0xFF;
+        let err = recover(None::<&[u8]>, &corrupted[..], decode).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(recover(None::<&[u8]>, &b"NOTAWAL!"[..], decode).is_err());
+
+        //An empty log is an empty map
+        let (map, stats) = recover(None::<&[u8]>, &[][..], decode).unwrap();
+        assert_eq!(map.val_count(), 0);
+        assert_eq!(stats, ReplayStats::default());
+    }
+
+    #[test]
+    fn wal_compact_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let snapshot_path = dir.path().join("map.snapshot");
+        let log_path = dir.path().join("map.log");
+        let new_log_path = dir.path().join("map.log.new");
+
+        let mut wal = WalPathMap::create(BytesTrieMap::new(), File::create(&log_path).unwrap(), encode).unwrap();
+        for i in 0..100u32 {
+            wal.insert(i.to_be_bytes(), format!("value {i}")).unwrap();
+        }
+        for i in 0..50u32 {
+            wal.remove(i.to_be_bytes()).unwrap();
+        }
+        wal.insert(b"", "root".to_string()).unwrap();
+
+        let mut snapshot = File::create(&snapshot_path).unwrap();
+        let old_log = wal.compact(&mut snapshot, File::create(&new_log_path).unwrap()).unwrap();
+        drop(old_log);
+        std::fs::rename(&new_log_path, &log_path).unwrap();
+        assert_eq!(wal.records(), 0);
+        wal.insert(b"after", "compaction".to_string()).unwrap();
+        wal.remove(99u32.to_be_bytes()).unwrap();
+        let expected = contents(wal.map());
+        drop(wal);
+
+        let (map, stats) = recover(Some(File::open(&snapshot_path).unwrap()), File::open(&log_path).unwrap(), decode).unwrap();
+        assert_eq!(stats.records, 2);
+        assert_eq!(contents(&map), expected);
+        assert_eq!(map.read_zipper().value(), Some(&"root".to_string()));
+
+        //A truncated snapshot is an error
+        let mut snapshot = OpenOptions::new().read(true).write(true).open(&snapshot_path).unwrap();
+        let snapshot_len = snapshot.seek(SeekFrom::End(0)).unwrap();
+        snapshot.set_len(snapshot_len - 1).unwrap();
+        snapshot.flush().unwrap();
+        drop(snapshot);
+        assert!(recover(Some(File::open(&snapshot_path).unwrap()), File::open(&log_path).unwrap(), decode).is_err());
+    }
+}
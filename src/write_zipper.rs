@@ -156,6 +156,21 @@ pub trait ZipperWriting<V: Clone + Send + Sync, A: Allocator = GlobalAlloc>: Wri
     //GOAT, gotta document this much better and decide if a return of AlgebraicStatus is called for.  Probably.
     fn restricting<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> bool;
 
+    /// Replaces the subtrie below the zipper's focus with its right quotient by the subtrie below the focus
+    /// of `read_zipper`.  See [BytesTrieMap::right_quotient]
+    ///
+    /// The value at each zipper's focus is treated as the value at the empty path, so it participates in
+    /// the operation and may be replaced.  Returns [AlgebraicStatus::None] if the result is empty, otherwise
+    /// returns [AlgebraicStatus::Element].
+    fn right_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice;
+
+    /// Replaces the subtrie below the zipper's focus with its left quotient by the subtrie below the focus
+    /// of `read_zipper`.  See [BytesTrieMap::left_quotient]
+    ///
+    /// The value at each zipper's focus is treated as the value at the empty path, in the same way as
+    /// [Self::right_quotient].
+    fn left_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice;
+
     /// Removes all branches below the zipper's focus.  Does not affect the value if there is one.  Returns `true`
     /// if a branch was removed, otherwise returns `false`
     ///
@@ -213,6 +228,8 @@ impl<V: Clone + Send + Sync, Z, A: Allocator> ZipperWriting<V, A> for &mut Z whe
     fn subtract<RZ: ZipperSubtries<V, A>>(&mut self, read_zipper: &RZ) -> AlgebraicStatus where V: DistributiveLattice { (**self).subtract(read_zipper) }
     fn restrict<RZ: ZipperSubtries<V, A>>(&mut self, read_zipper: &RZ) -> AlgebraicStatus { (**self).restrict(read_zipper) }
     fn restricting<RZ: ZipperSubtries<V, A>>(&mut self, read_zipper: &RZ) -> bool { (**self).restricting(read_zipper) }
+    fn right_quotient<RZ: ZipperSubtries<V, A>>(&mut self, read_zipper: &RZ) -> AlgebraicStatus where V: Lattice { (**self).right_quotient(read_zipper) }
+    fn left_quotient<RZ: ZipperSubtries<V, A>>(&mut self, read_zipper: &RZ) -> AlgebraicStatus where V: Lattice { (**self).left_quotient(read_zipper) }
     fn remove_branches(&mut self) -> bool { (**self).remove_branches() }
     fn take_map(&mut self) -> Option<BytesTrieMap<V, A>> { (**self).take_map() }
     fn remove_unmasked_branches(&mut self, mask: ByteMask) { (**self).remove_unmasked_branches(mask) }
@@ -299,6 +316,10 @@ impl<V: Clone + Send + Sync + Unpin, A: Allocator> ZipperAbsolutePath for WriteZ
 }
 
 impl<'a, 'path, V: Clone + Send + Sync + Unpin, A: Allocator> WriteZipperTracked<'a, 'path, V, A> {
+    /// Internal method to take the tracker from the zipper, so the lock outlives it
+    pub(crate) fn take_tracker(&mut self) -> Option<ZipperTracker<TrackingWrite>> {
+        self._tracker.take()
+    }
     //GOAT, this method currently isn't called
     // /// Creates a new zipper, with a path relative to a node
     // pub(crate) fn new_with_node_and_path(root_node: &'a mut TrieNodeODRc<V>, path: &'k [u8], tracker: ZipperTracker) -> Self {
@@ -362,6 +383,8 @@ impl<'a, V: Clone + Send + Sync + Unpin, A: Allocator> ZipperWriting<V, A> for W
     fn subtract<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: DistributiveLattice { self.z.subtract(read_zipper) }
     fn restrict<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus { self.z.restrict(read_zipper) }
     fn restricting<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> bool { self.z.restricting(read_zipper) }
+    fn right_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice { self.z.right_quotient(read_zipper) }
+    fn left_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice { self.z.left_quotient(read_zipper) }
     fn remove_branches(&mut self) -> bool { self.z.remove_branches() }
     fn take_map(&mut self) -> Option<BytesTrieMap<V, A>> { self.z.take_map() }
     fn remove_unmasked_branches(&mut self, mask: ByteMask) { self.z.remove_unmasked_branches(mask) }
@@ -539,6 +562,8 @@ impl<'a, V: Clone + Send + Sync + Unpin, A: Allocator> ZipperWriting<V, A> for W
     fn subtract<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: DistributiveLattice { self.z.subtract(read_zipper) }
     fn restrict<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus { self.z.restrict(read_zipper) }
     fn restricting<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> bool { self.z.restricting(read_zipper) }
+    fn right_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice { self.z.right_quotient(read_zipper) }
+    fn left_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice { self.z.left_quotient(read_zipper) }
     fn remove_branches(&mut self) -> bool { self.z.remove_branches() }
     fn take_map(&mut self) -> Option<BytesTrieMap<V, A>> { self.z.take_map() }
     fn remove_unmasked_branches(&mut self, mask: ByteMask) { self.z.remove_unmasked_branches(mask) }
@@ -563,8 +588,11 @@ impl<V: Clone + Send + Sync + Unpin, A: Allocator> WriteZipperPriv<V, A> for Wri
 pub struct WriteZipperOwned<V: Clone + Send + Sync + 'static, A: Allocator + 'static = GlobalAlloc> {
     map: MaybeDangling<Box<BytesTrieMap<V, A>>>,
     z: WriteZipperCore<'static, 'static, V, A>,
+    /// Present when the zipper was taken from a [ZipperHead], to hold the region until it is replaced
+    _tracker: Option<ZipperTracker<TrackingWrite>>,
 }
 
+//NOTE: The clone is not tracked, because two zippers can't both hold the same exclusive region
 impl<V: 'static + Clone + Send + Sync + Unpin, A: Allocator> Clone for WriteZipperOwned<V, A> {
     fn clone(&self) -> Self {
         let new_map = (**self.map).clone();
@@ -655,7 +683,13 @@ impl <V: Clone + Send + Sync + Unpin, A: Allocator> WriteZipperOwned<V, A> {
             false => None
         };
         let core = WriteZipperCore::new_with_node_and_cloned_path_in(root_ref, root_val, &*path, path.len(), 0, alloc);
-        Self { map, z: core }
+        Self { map, z: core, _tracker: None }
+    }
+    /// Creates a new `WriteZipperOwned` from a `map`, which holds the exclusive region tracked by `tracker`
+    pub(crate) fn new_with_map_and_tracker(map: BytesTrieMap<V, A>, path: &[u8], tracker: ZipperTracker<TrackingWrite>) -> Self {
+        let mut new_zipper = Self::new_with_map(map, path);
+        new_zipper._tracker = Some(tracker);
+        new_zipper
     }
     /// Consumes the zipper and returns a map contained within the zipper
     pub fn into_map(self) -> BytesTrieMap<V, A> {
@@ -666,15 +700,29 @@ impl <V: Clone + Send + Sync + Unpin, A: Allocator> WriteZipperOwned<V, A> {
     /// Consumes the `WriteZipperOwned`, and returns a [ReadZipperOwned] in its place
     ///
     /// The returned read zipper will have the same root and focus as the the consumed write zipper.
+    ///
+    /// If the write zipper was taken from a [ZipperHead] with [take_owned_write_zipper_at_exclusive_path](crate::zipper::ZipperCreation::take_owned_write_zipper_at_exclusive_path),
+    /// the read zipper holds its region as a reader until it is dropped.  The contents are never returned
+    /// to the trie, so the region is removed, the same as if the write zipper had been dropped.  Use
+    /// [replace_owned_write_zipper](crate::zipper::ZipperCreation::replace_owned_write_zipper) instead
+    /// to keep the contents.
     pub fn into_read_zipper(mut self) -> ReadZipperOwned<V, A> {
         let descended_path = &self.z.key.prefix_buf[self.z.key.origin_path.len()..].to_vec();
         let root_prefix_len = self.root_prefix_path().len();
         let path = self.take_root_prefix_path();
+        let tracker = self.take_tracker();
         let map = self.into_map();
-        let mut new_zipper = ReadZipperOwned::new_with_map(map, &path[..root_prefix_len]);
+        let mut new_zipper = match tracker {
+            Some(tracker) => ReadZipperOwned::new_with_map_and_tracker(map, &path[..root_prefix_len], tracker.into_reader()),
+            None => ReadZipperOwned::new_with_map(map, &path[..root_prefix_len]),
+        };
         new_zipper.descend_to(descended_path);
         new_zipper
     }
+    /// Internal method to take the tracker from the zipper, so the lock outlives it
+    pub(crate) fn take_tracker(&mut self) -> Option<ZipperTracker<TrackingWrite>> {
+        self._tracker.take()
+    }
     /// Internal method to access `WriteZipperCore` inside `WriteZipperOwned`
     pub(crate) fn core(&mut self) -> &mut WriteZipperCore<'static, 'static, V, A> {
         &mut self.z
@@ -702,6 +750,8 @@ impl<V: Clone + Send + Sync + Unpin, A: Allocator> ZipperWriting<V, A> for Write
     fn subtract<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: DistributiveLattice { self.z.subtract(read_zipper) }
     fn restrict<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus { self.z.restrict(read_zipper) }
     fn restricting<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> bool { self.z.restricting(read_zipper) }
+    fn right_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice { self.z.right_quotient(read_zipper) }
+    fn left_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice { self.z.left_quotient(read_zipper) }
     fn remove_branches(&mut self) -> bool { self.z.remove_branches() }
     fn take_map(&mut self) -> Option<BytesTrieMap<V, A>> { self.z.take_map() }
     fn remove_unmasked_branches(&mut self, mask: ByteMask) { self.z.remove_unmasked_branches(mask) }
@@ -1563,6 +1613,42 @@ impl <'a, 'path, V: Clone + Send + Sync + Unpin, A: Allocator> WriteZipperCore<'
             None => false
         }
     }
+    /// See [WriteZipper::right_quotient]
+    pub fn right_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice {
+        let (self_map, divisor_map) = self.quotient_operand_maps(read_zipper);
+        let quotient = self_map.right_quotient(&divisor_map);
+        drop(self_map);
+        self.graft_quotient(quotient)
+    }
+    /// See [WriteZipper::left_quotient]
+    pub fn left_quotient<Z: ZipperSubtries<V, A>>(&mut self, read_zipper: &Z) -> AlgebraicStatus where V: Lattice {
+        let (self_map, divisor_map) = self.quotient_operand_maps(read_zipper);
+        let quotient = self_map.left_quotient(&divisor_map);
+        drop(self_map);
+        self.graft_quotient(quotient)
+    }
+    /// Internal method to make temporary maps sharing the subtries at the focus of `self` and `read_zipper`,
+    /// including the values at each focus
+    fn quotient_operand_maps<Z: ZipperSubtries<V, A>>(&self, read_zipper: &Z) -> (BytesTrieMap<V, A>, BytesTrieMap<V, A>) {
+        let self_map = BytesTrieMap::new_with_root_in(self.get_focus().into_option(), self.get_value().cloned(), self.alloc.clone());
+        let divisor_map = BytesTrieMap::new_with_root_in(read_zipper.get_focus().into_option(), read_zipper.value().cloned(), self.alloc.clone());
+        (self_map, divisor_map)
+    }
+    /// Internal method to replace the focus, including the value at the focus, with the result of a quotient op
+    fn graft_quotient(&mut self, quotient: BytesTrieMap<V, A>) -> AlgebraicStatus {
+        let (root_node, root_val) = quotient.into_root();
+        let is_empty = root_node.is_none() && root_val.is_none();
+        self.graft_internal(root_node);
+        let _ = match root_val {
+            Some(val) => self.set_value(val),
+            None => self.remove_value()
+        };
+        if is_empty {
+            AlgebraicStatus::None
+        } else {
+            AlgebraicStatus::Element
+        }
+    }
     /// See [WriteZipper::remove_branches]
     pub fn remove_branches(&mut self) -> bool {
         let node_key = self.key.node_key();
@@ -2296,6 +2382,38 @@ mod tests {
         assert_eq!(map.get(b"b:round"), None);
     }
 
+    #[test]
+    fn write_zipper_quotient_test() {
+        let a_keys = ["in:ab", "in:abc", "in:xc", "in:xyz", "other"];
+        let suffix_keys = ["divisor:c", "divisor:yz", "prefix:x"];
+        let mut a: BytesTrieMap<()> = a_keys.iter().map(|k| (k, ())).collect();
+        let d: BytesTrieMap<()> = suffix_keys.iter().map(|k| (k, ())).collect();
+
+        let mut wz = a.write_zipper_at_path(b"in:");
+        let result = wz.right_quotient(&d.read_zipper_at_path(b"divisor:"));
+        assert_eq!(result, AlgebraicStatus::Element);
+        assert_eq!(wz.val_count(), 2);
+        drop(wz);
+        assert!(a.contains("in:ab"));
+        assert!(a.contains("in:x"));
+        assert!(!a.contains("in:abc"));
+        assert!(a.contains("other"));
+
+        let mut wz = a.write_zipper_at_path(b"in:");
+        let result = wz.left_quotient(&d.read_zipper_at_path(b"prefix:"));
+        assert_eq!(result, AlgebraicStatus::Element);
+        drop(wz);
+        assert_eq!(a.get("in:"), Some(&()));
+        assert_eq!(a.val_count(), 2);
+
+        let mut wz = a.write_zipper_at_path(b"in:");
+        let result = wz.right_quotient(&d.read_zipper_at_path(b"nothing:"));
+        assert_eq!(result, AlgebraicStatus::None);
+        drop(wz);
+        assert_eq!(a.val_count(), 1);
+        assert!(a.contains("other"));
+    }
+
     #[test]
     fn write_zipper_meet_test1() {
         let a_keys = ["12345", "1aaaa", "1bbbb", "1cccc", "1dddd"];
@@ -36,6 +36,14 @@ pub use crate::write_zipper::*;
 pub use crate::trie_ref::*;
 pub use crate::zipper_head::*;
 pub use crate::product_zipper::ProductZipper;
+pub use crate::overlay_zipper::OverlayZipper;
+pub use crate::filter_zipper::{FilterZipper, BranchFilter, PathPredicate};
+pub use crate::remap_zipper::RemapZipper;
+pub use crate::lazy_zipper::LazyZipper;
+pub use crate::zipper_join::ZipperJoin;
+pub use crate::undo_zipper::UndoZipper;
+pub use crate::observed_zipper::{ObservedZipper, ChangeNotifier, ChangeEvent, ChangeKind, SubscriptionId};
+pub use crate::zipper_tracking::{ActiveRegion, RegionKind};
 
 use crate::zipper_tracking::*;
 
@@ -393,6 +401,32 @@ pub trait ZipperMoving: Zipper {
     }
 }
 
+/// Implements every [ZipperMoving] method by forwarding it to the zipper in the `z` field, for wrapper
+/// zippers that don't change how the focus moves.  Invoke it inside the `impl ZipperMoving` block.
+macro_rules! forward_zipper_moving {
+    () => {
+        fn at_root(&self) -> bool { self.z.at_root() }
+        fn reset(&mut self) { self.z.reset() }
+        fn path(&self) -> &[u8] { self.z.path() }
+        fn val_count(&self) -> usize { self.z.val_count() }
+        fn descend_to<K: AsRef<[u8]>>(&mut self, k: K) -> bool { self.z.descend_to(k) }
+        fn descend_to_existing<K: AsRef<[u8]>>(&mut self, k: K) -> usize { self.z.descend_to_existing(k) }
+        fn descend_to_value<K: AsRef<[u8]>>(&mut self, k: K) -> usize { self.z.descend_to_value(k) }
+        fn descend_to_byte(&mut self, k: u8) -> bool { self.z.descend_to_byte(k) }
+        fn descend_indexed_branch(&mut self, child_idx: usize) -> bool { self.z.descend_indexed_branch(child_idx) }
+        fn descend_first_byte(&mut self) -> bool { self.z.descend_first_byte() }
+        fn descend_until(&mut self) -> bool { self.z.descend_until() }
+        fn to_next_sibling_byte(&mut self) -> bool { self.z.to_next_sibling_byte() }
+        fn to_prev_sibling_byte(&mut self) -> bool { self.z.to_prev_sibling_byte() }
+        fn ascend(&mut self, steps: usize) -> bool { self.z.ascend(steps) }
+        fn ascend_byte(&mut self) -> bool { self.z.ascend_byte() }
+        fn ascend_until(&mut self) -> bool { self.z.ascend_until() }
+        fn ascend_until_branch(&mut self) -> bool { self.z.ascend_until_branch() }
+        fn to_next_step(&mut self) -> bool { self.z.to_next_step() }
+    }
+}
+pub(crate) use forward_zipper_moving;
+
 /// An interface to access values through a [Zipper] that cannot modify the trie.  Allows
 /// references with lifetimes that may outlive the zipper
 ///
@@ -877,6 +911,28 @@ impl<'a, 'path, V: Clone + Send + Sync + Unpin, A: Allocator> ReadZipperTracked<
         let core = ReadZipperCore::new_with_node_and_cloned_path_in(root_node, path, root_prefix_len, root_key_start, root_val, alloc);
         Self { z: core, _tracker: tracker }
     }
+    /// Returns the tracker that holds the zipper's read lock
+    pub(crate) fn tracker(&self) -> &ZipperTracker<TrackingRead> {
+        &self._tracker
+    }
+    /// Internal method to exchange the zipper's read lock for a write lock.  Returns the write tracker, along
+    /// with the zipper's root prefix path and focus path, or returns the zipper unchanged if there is a conflict
+    pub(crate) fn try_into_write_tracker(self) -> Result<(ZipperTracker<TrackingWrite>, Vec<u8>, Vec<u8>), (Self, Conflict)> {
+        let mut shell = core::mem::ManuallyDrop::new(self);
+        let tracker = unsafe{ core::ptr::read(&shell._tracker) };
+        match tracker.try_into_writer() {
+            Ok(tracker) => {
+                let root_prefix_path = shell.z.root_prefix_path().to_vec();
+                let focus_path = shell.z.path().to_vec();
+                unsafe{ core::ptr::drop_in_place(&mut shell.z) };
+                Ok((tracker, root_prefix_path, focus_path))
+            },
+            Err((tracker, conflict)) => {
+                unsafe{ core::ptr::write(&mut shell._tracker, tracker) };
+                Err((core::mem::ManuallyDrop::into_inner(shell), conflict))
+            }
+        }
+    }
 }
 
 //GOAT!!!! UNsound!!!!  I realized I drop the zipper_tracker here...  Which allows the iterator to
@@ -1086,12 +1142,16 @@ pub struct ReadZipperOwned<V: Clone + Send + Sync + 'static, A: Allocator + 'sta
     // WZNodePtr, although it's likely easier for the ReadZipperCore because we don't have to worry about
     // mutability and the constraints of the MutCursorRootedVec
     z: Box<ReadZipperCore<'static, 'static, V, A>>,
+    /// Present when the zipper was created by a [ZipperHead], to hold the region as a reader
+    _tracker: Option<ZipperTracker<TrackingRead>>,
 }
 
 impl<V: 'static + Clone + Send + Sync + Unpin, A: Allocator> Clone for ReadZipperOwned<V, A> {
     fn clone(&self) -> Self {
         let new_map = (**self.map).clone();
-        Self::new_with_map(new_map, self.root_prefix_path())
+        let mut new_zipper = Self::new_with_map(new_map, self.root_prefix_path());
+        new_zipper._tracker = self._tracker.clone();
+        new_zipper
     }
 }
 
@@ -1105,7 +1165,13 @@ impl<V: 'static + Clone + Send + Sync + Unpin, A: Allocator> ReadZipperOwned<V,
         let root_ref = unsafe{ &*(*map).root.get() }.as_ref().unwrap().borrow();
         let root_val = Option::as_ref( unsafe{ &*(*map).root_val.get() } );
         let core = ReadZipperCore::new_with_node_and_cloned_path_in(root_ref, path, path.len(), 0, root_val, alloc);
-        Self { map, z: Box::new(core) }
+        Self { map, z: Box::new(core), _tracker: None }
+    }
+    /// Creates a new `ReadZipperOwned` from a `map`, which holds the region tracked by `tracker`
+    pub(crate) fn new_with_map_and_tracker(map: BytesTrieMap<V, A>, path: &[u8], tracker: ZipperTracker<TrackingRead>) -> Self {
+        let mut new_zipper = Self::new_with_map(map, path);
+        new_zipper._tracker = Some(tracker);
+        new_zipper
     }
     /// Consumes the zipper and returns a map contained within the zipper
     pub fn into_map(self) -> BytesTrieMap<V, A> {
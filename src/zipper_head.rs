@@ -1,11 +1,14 @@
 
 use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::time::Duration;
 
 use crate::{Allocator, GlobalAlloc};
 use crate::trie_map::BytesTrieMap;
 use crate::trie_node::*;
 use crate::zipper::*;
 use crate::zipper::zipper_priv::ZipperPriv;
+use crate::write_zipper::write_zipper_priv::WriteZipperPriv;
 use crate::zipper_tracking::*;
 use crate::dense_byte_node::CellByteNode;
 
@@ -13,6 +16,13 @@ pub trait ZipperCreation<'trie, V: Clone + Send + Sync, A: Allocator = GlobalAll
     /// Creates a new read-only [Zipper] with the path specified from the `ZipperHead`
     fn read_zipper_at_path<'a, K: AsRef<[u8]>>(&'a self, path: K) -> Result<ReadZipperTracked<'a, 'static, V, A>, Conflict> where 'trie: 'a;
 
+    /// Same as [read_zipper_at_path](ZipperCreation::read_zipper_at_path), but waits for conflicting
+    /// [write zippers](ZipperWriting) to be dropped instead of failing
+    ///
+    /// Returns `Err(Conflict)` describing the last conflict if `timeout` elapses before the path becomes
+    /// available.  A `timeout` of `None` waits indefinitely.
+    fn read_zipper_at_path_blocking<'a, K: AsRef<[u8]>>(&'a self, path: K, timeout: Option<Duration>) -> Result<ReadZipperTracked<'a, 'static, V, A>, Conflict> where 'trie: 'a;
+
     /// A more efficient version of [read_zipper_at_path](ZipperCreation::read_zipper_at_path), where the returned
     /// zipper is constrained by the `'path` lifetime
     fn read_zipper_at_borrowed_path<'a, 'path>(&'a self, path: &'path[u8]) -> Result<ReadZipperTracked<'a, 'path, V, A>, Conflict> where 'trie: 'a;
@@ -26,46 +36,103 @@ pub trait ZipperCreation<'trie, V: Clone + Send + Sync, A: Allocator = GlobalAll
     /// where the returned zipper is constrained by the `'path` lifetime
     unsafe fn read_zipper_at_borrowed_path_unchecked<'a, 'path>(&'a self, path: &'path[u8]) -> ReadZipperUntracked<'a, 'path, V, A> where 'trie: 'a;
 
-    //GOAT-TrackedOwnedZippers: This is a proposed feature to create owned variants of zippers, but still
-    // track them using the ZipperHead infrastructure.  Creating owned zippers safely is easy to do and
-    // doesn't require a new API.  However, those zippers will operate outside the management of a tracker,
-    // and therefore you won't be able to use the tracker to protect regions of the trie.  Instead, conflicts
-    // will result in reading an old version of the trie, or writing to a new location that overwrites existing
-    // data when it's re-merged.  This isn't "corruption" at the pathmap level, but might be considered
-    // corruption by the calling code.
-    //
-    // /// Creates a new [ReadZipperOwned] with the path specified from the `ZipperHead`
-    // ///
-    // /// This method has the advantage that the returned zipper will have a `'static` lifetime, making it possible
-    // /// to safely send across async (tokio) threads, etc.  However, it has additional overhead vs. other
-    // /// read-zipper creation methods such as [read_zipper_at_path](ZipperCreation::read_zipper_at_path).
-    // fn owned_read_zipper_at_path<K: AsRef<[u8]>>(&self, path: K) -> Result<ReadZipperOwned<V>, Conflict>;
+    //NOTE: Owned zippers are tracked using the ZipperHead infrastructure, so the tracker protects their
+    // regions of the trie like any other zipper's.  Creating owned zippers outside the tracker is easy to
+    // do, but conflicts would then result in reading an old version of the trie, or writing to a new
+    // location that overwrites existing data when it's re-merged.  This isn't "corruption" at the pathmap
+    // level, but might be considered corruption by the calling code.
+
+    /// Creates a new [ReadZipperOwned] with the path specified from the `ZipperHead`
+    ///
+    /// This method has the advantage that the returned zipper will have a `'static` lifetime, making it possible
+    /// to safely send across async (tokio) threads, etc.  However, it has additional overhead vs. other
+    /// read-zipper creation methods such as [read_zipper_at_path](ZipperCreation::read_zipper_at_path).
+    ///
+    /// The returned zipper holds its region as a reader until it is dropped, so [write zippers](ZipperWriting)
+    /// may not be created there in the meantime.
+    fn owned_read_zipper_at_path<'a, K: AsRef<[u8]>>(&'a self, path: K) -> Result<ReadZipperOwned<V, A>, Conflict> where 'trie: 'a, V: 'static, A: 'static;
 
     /// Creates a new [write zippers](ZipperWriting) with the specified path from the `ZipperHead`
     fn write_zipper_at_exclusive_path<'a, K: AsRef<[u8]>>(&'a self, path: K) -> Result<WriteZipperTracked<'a, 'static, V, A>, Conflict> where 'trie: 'a;
 
+    /// Same as [write_zipper_at_exclusive_path](ZipperCreation::write_zipper_at_exclusive_path), but waits
+    /// for conflicting zippers to be dropped instead of failing
+    ///
+    /// Returns `Err(Conflict)` describing the last conflict if `timeout` elapses before the path becomes
+    /// available.  A `timeout` of `None` waits indefinitely, so the calling thread must not itself hold a
+    /// conflicting zipper.
+    fn write_zipper_at_exclusive_path_blocking<'a, K: AsRef<[u8]>>(&'a self, path: K, timeout: Option<Duration>) -> Result<WriteZipperTracked<'a, 'static, V, A>, Conflict> where 'trie: 'a;
+
     /// Creates a new [write zippers](ZipperWriting) with the specified path from the `ZipperHead`, where the
     /// caller guarantees that no existing zippers may access the specified path at any time before the
     /// write zipper is dropped
     unsafe fn write_zipper_at_exclusive_path_unchecked<'a, K: AsRef<[u8]>>(&'a self, path: K) -> WriteZipperUntracked<'a, 'static, V, A> where 'trie: 'a;
 
-    //GOAT-TrackedOwnedZippers
-    // /// Creates a [WriteZipperOwned] from the specified path by temporarily cutting the trie
-    // ///
-    // /// This method creates a `'static` lifetime write zipper, which is useful to send across async (tokio)
-    // /// threads, etc.  However, it comes with additional cost and requires the zipper to be replaced by calling
-    // /// [replace_owned_write_zipper](ZipperCreation::replace_owned_write_zipper).
-    // ///
-    // /// If the zipper is not replaced (and is dropped instead) the effect will be the same as calling both
-    // /// [WriteZipper::remove_branches], and [WriteZipper::remove_value].
-    // fn take_owned_write_zipper_at_exclusive_path<K: AsRef<[u8]>>(&self, path: K) -> Result<WriteZipperOwned<V>, Conflict>;
-
-    // /// Consumes a [WriteZipperOwned], and returns it to the trie from which it came
-    // ///
-    // /// This method is the inverse of [take_owned_write_zipper_at_exclusive_path](ZipperCreation::take_owned_write_zipper_at_exclusive_path).
-    // ///
-    // /// May panic if `zipper` did not originate from the `self` `ZipperHead`.
-    // fn replace_owned_write_zipper(&self, zipper: WriteZipperOwned<V>);
+    /// Creates a [WriteZipperOwned] from the specified path by temporarily cutting the trie
+    ///
+    /// This method creates a `'static` lifetime write zipper, which is useful to send across async (tokio)
+    /// threads, etc.  However, it comes with additional cost and requires the zipper to be replaced by calling
+    /// [replace_owned_write_zipper](ZipperCreation::replace_owned_write_zipper).
+    ///
+    /// If the zipper is not replaced (and is dropped instead) the effect will be the same as calling both
+    /// [ZipperWriting::remove_branches], and [ZipperWriting::remove_value].
+    fn take_owned_write_zipper_at_exclusive_path<'a, K: AsRef<[u8]>>(&'a self, path: K) -> Result<WriteZipperOwned<V, A>, Conflict> where 'trie: 'a, V: 'static, A: 'static;
+
+    /// Consumes a [WriteZipperOwned], and returns it to the trie from which it came
+    ///
+    /// This method is the inverse of [take_owned_write_zipper_at_exclusive_path](ZipperCreation::take_owned_write_zipper_at_exclusive_path).
+    ///
+    /// May panic if `zipper` did not originate from the `self` `ZipperHead`.
+    fn replace_owned_write_zipper<'a>(&'a self, zipper: WriteZipperOwned<V, A>) where 'trie: 'a, V: 'static, A: 'static;
+
+    /// Upgrades a [ReadZipperTracked] created by this `ZipperHead` into a [WriteZipperTracked] with the same
+    /// root, and focused at the same path
+    ///
+    /// The upgrade succeeds only if no other zipper conflicts with a writer at the zipper's root, including
+    /// other readers at the same path.  If it fails, the read zipper is returned unchanged along with the
+    /// [Conflict].  The exchange of locks is atomic, so no other zipper can be created in between.
+    ///
+    /// May panic if `zipper` did not originate from the `self` `ZipperHead`.
+    fn upgrade_read_zipper<'a, 'path>(&'a self, zipper: ReadZipperTracked<'a, 'path, V, A>) -> Result<WriteZipperTracked<'a, 'static, V, A>, (ReadZipperTracked<'a, 'path, V, A>, Conflict)> where 'trie: 'a;
+
+    /// Downgrades a [WriteZipperTracked] created by this `ZipperHead` into a [ReadZipperTracked] with the same
+    /// root, and focused at the same path
+    ///
+    /// This is the same as [WriteZipperTracked::into_read_zipper], and is provided to pair with
+    /// [upgrade_read_zipper](ZipperCreation::upgrade_read_zipper).  Other read zippers may be created within
+    /// the downgraded zipper's region as soon as this method returns, and threads waiting to read within the
+    /// region are woken.  Unlike [cleanup_write_zipper](ZipperCreation::cleanup_write_zipper), the root prefix
+    /// path is not pruned.
+    fn downgrade_write_zipper<'a>(&'a self, zipper: WriteZipperTracked<'a, 'static, V, A>) -> ReadZipperTracked<'a, 'static, V, A> where 'trie: 'a;
+
+    /// Creates a zipper for each of `requests` together, after checking all of them in a single step
+    ///
+    /// The returned zippers are in the same order as `requests`.  If any request conflicts with an existing
+    /// zipper, or with another of the `requests`, then no zippers are created and the returned [Conflict]
+    /// names every conflicting path.
+    fn acquire_many<'a>(&'a self, requests: &[PathRequest]) -> Result<Vec<AcquiredZipper<'a, V, A>>, Conflict> where 'trie: 'a;
+
+    /// Same as [acquire_many](ZipperCreation::acquire_many), but waits for conflicting zippers to be dropped
+    /// instead of failing
+    ///
+    /// No zippers are held while waiting, so several threads may wait for overlapping sets of paths without
+    /// deadlocking.  Returns `Err(Conflict)` immediately if the `requests` conflict with each other, or if
+    /// `timeout` elapses before all the paths become available.
+    fn acquire_many_blocking<'a>(&'a self, requests: &[PathRequest], timeout: Option<Duration>) -> Result<Vec<AcquiredZipper<'a, V, A>>, Conflict> where 'trie: 'a;
+
+    /// Creates a [WriteZipperTracked] for every existing path exactly `depth` bytes long, together
+    ///
+    /// The zippers are returned in path order.  The paths are found without taking a lock at the root, so
+    /// zippers elsewhere in the trie don't conflict.  However the nodes above `depth` can't be read while a
+    /// write zipper holds a path shorter than `depth`, so such a write zipper is reported as a [Conflict], as
+    /// are any zippers within the branches.
+    fn acquire_branches<'a>(&'a self, depth: usize) -> Result<Vec<WriteZipperTracked<'a, 'static, V, A>>, Conflict> where 'trie: 'a;
+
+    /// Returns every region of the trie held by zippers from this `ZipperHead`, ordered by path
+    ///
+    /// Useful for debugging lock leaks.  With the `zipper_tracking` feature enabled, each region also
+    /// includes the backtraces where its zippers were created, if backtraces are enabled (see [Conflict::backtraces]).
+    fn active_regions(&self) -> Vec<ActiveRegion>;
 
     /// Reclaims ownership of a write zipper that was provided by the `ZipperHead` to ensure the zipper's
     /// root prefix path is pruned
@@ -75,6 +142,14 @@ pub trait ZipperCreation<'trie, V: Clone + Send + Sync, A: Allocator = GlobalAll
     ///
     /// May panic if `zipper` did not originate from the `self` `ZipperHead`.
     fn cleanup_write_zipper<Z: ZipperWriting<V, A> + ZipperAbsolutePath>(&self, z: Z);
+
+    /// Begins a [ZipperTransaction], to make changes at several paths that take effect together when the
+    /// transaction is committed
+    fn transaction<'a>(&'a self) -> ZipperTransaction<'a, 'trie, Self, V, A>
+        where Self: Sized, V: Unpin + 'trie, A: 'trie, 'trie: 'a
+    {
+        ZipperTransaction::new(self)
+    }
 }
 
 trait ZipperCreationPriv<'trie, V, A: Allocator> {
@@ -236,16 +311,12 @@ impl<'trie, Z, V: 'trie + Clone + Send + Sync + Unpin, A: Allocator + 'trie> Zip
     fn read_zipper_at_path<'a, K: AsRef<[u8]>>(&'a self, path: K) -> Result<ReadZipperTracked<'a, 'static, V, A>, Conflict> where 'trie: 'a {
         let path = path.as_ref();
         let zipper_tracker = ZipperTracker::<TrackingRead>::new(self.tracker_paths().clone(), path)?;
-        self.with_inner_core_z(|z| {
-            z.focus_stack.advance_if_empty_twostep(|root| root, |root| root.make_mut());
-
-            let (root_node, root_val) = z.splitting_borrow_focus();
-            //SAFETY: See identical code in `read_zipper_at_borrowed_path` for more discussion
-            let root_node: &'trie dyn TrieNode<V, A> = unsafe{ core::mem::transmute(root_node) };
-            let root_val: Option<&'trie V> = root_val.map(|v| unsafe{ &*(v as *const _) } );
-
-            Ok(ReadZipperTracked::new_with_node_and_cloned_path_in(root_node, path.as_ref(), path.len(), 0, root_val, z.alloc.clone(), zipper_tracker))
-        })
+        Ok(read_zipper_with_tracker(self, path, zipper_tracker))
+    }
+    fn read_zipper_at_path_blocking<'a, K: AsRef<[u8]>>(&'a self, path: K, timeout: Option<Duration>) -> Result<ReadZipperTracked<'a, 'static, V, A>, Conflict> where 'trie: 'a {
+        let path = path.as_ref();
+        let zipper_tracker = ZipperTracker::<TrackingRead>::new_blocking(self.tracker_paths().clone(), path, timeout)?;
+        Ok(read_zipper_with_tracker(self, path, zipper_tracker))
     }
     unsafe fn read_zipper_at_path_unchecked<'a, K: AsRef<[u8]>>(&'a self, path: K) -> ReadZipperUntracked<'a, 'static, V, A> where 'trie: 'a {
         let path = path.as_ref();
@@ -273,14 +344,12 @@ impl<'trie, Z, V: 'trie + Clone + Send + Sync + Unpin, A: Allocator + 'trie> Zip
     fn write_zipper_at_exclusive_path<'a, K: AsRef<[u8]>>(&'a self, path: K) -> Result<WriteZipperTracked<'a, 'static, V, A>, Conflict> where 'trie: 'a {
         let path = path.as_ref();
         let zipper_tracker = ZipperTracker::<TrackingWrite>::new(self.tracker_paths().clone(), path)?;
-        self.with_inner_core_z(|z| {
-            let (zipper_root_node, zipper_root_val) = prepare_exclusive_write_path(z, path);
-            //SAFETY: See similar code in `read_zipper_at_borrowed_path` for more discussion
-            let zipper_root_node: &'trie mut TrieNodeODRc<V, A> = unsafe{ &mut *(zipper_root_node as *mut _) };
-            let zipper_root_val: &'trie mut Option<V> = unsafe{ &mut *(zipper_root_val as *mut _) };
-
-            Ok(WriteZipperTracked::new_with_node_and_cloned_path_internal_in(zipper_root_node, Some(zipper_root_val), path, path.len(), z.alloc.clone(), zipper_tracker))
-        })
+        Ok(write_zipper_with_tracker(self, path, zipper_tracker))
+    }
+    fn write_zipper_at_exclusive_path_blocking<'a, K: AsRef<[u8]>>(&'a self, path: K, timeout: Option<Duration>) -> Result<WriteZipperTracked<'a, 'static, V, A>, Conflict> where 'trie: 'a {
+        let path = path.as_ref();
+        let zipper_tracker = ZipperTracker::<TrackingWrite>::new_blocking(self.tracker_paths().clone(), path, timeout)?;
+        Ok(write_zipper_with_tracker(self, path, zipper_tracker))
     }
     unsafe fn write_zipper_at_exclusive_path_unchecked<'a, K: AsRef<[u8]>>(&'a self, path: K) -> WriteZipperUntracked<'a, 'static, V, A> where 'trie: 'a {
         let path = path.as_ref();
@@ -303,6 +372,120 @@ impl<'trie, Z, V: 'trie + Clone + Send + Sync + Unpin, A: Allocator + 'trie> Zip
             }
         })
     }
+    fn owned_read_zipper_at_path<'a, K: AsRef<[u8]>>(&'a self, path: K) -> Result<ReadZipperOwned<V, A>, Conflict> where 'trie: 'a, V: 'static, A: 'static {
+        let path = path.as_ref();
+        let zipper_tracker = ZipperTracker::<TrackingRead>::new(self.tracker_paths().clone(), path)?;
+        let alloc = self.with_inner_core_z(|z| z.alloc.clone());
+        //SAFETY: `zipper_tracker` holds the region for reading, so no write zipper can conflict
+        let source = unsafe{ self.read_zipper_at_path_unchecked(path) };
+        let mut map = BytesTrieMap::new_in(alloc);
+        let mut wz = map.write_zipper_at_path(path);
+        if let Some(subtrie) = source.make_map() {
+            wz.graft_map(subtrie);
+        }
+        if let Some(val) = source.value() {
+            wz.set_value(val.clone());
+        }
+        drop(wz);
+        Ok(ReadZipperOwned::new_with_map_and_tracker(map, path, zipper_tracker))
+    }
+    fn take_owned_write_zipper_at_exclusive_path<'a, K: AsRef<[u8]>>(&'a self, path: K) -> Result<WriteZipperOwned<V, A>, Conflict> where 'trie: 'a, V: 'static, A: 'static {
+        let path = path.as_ref();
+        let zipper_tracker = ZipperTracker::<TrackingWrite>::new(self.tracker_paths().clone(), path)?;
+        let mut source = write_zipper_with_tracker(self, path, zipper_tracker);
+        let subtrie = source.take_map();
+        let root_val = source.remove_value();
+        let zipper_tracker = source.take_tracker().unwrap();
+        self.cleanup_write_zipper(source);
+
+        let mut map = BytesTrieMap::new_in(self.with_inner_core_z(|z| z.alloc.clone()));
+        let mut wz = map.write_zipper_at_path(path);
+        if let Some(subtrie) = subtrie {
+            wz.graft_map(subtrie);
+        }
+        if let Some(val) = root_val {
+            wz.set_value(val);
+        }
+        drop(wz);
+        Ok(WriteZipperOwned::new_with_map_and_tracker(map, path, zipper_tracker))
+    }
+    fn replace_owned_write_zipper<'a>(&'a self, mut zipper: WriteZipperOwned<V, A>) where 'trie: 'a, V: 'static, A: 'static {
+        let zipper_tracker = zipper.take_tracker()
+            .unwrap_or_else(|| panic!("replace_owned_write_zipper called with an untracked zipper"));
+        assert!(self.tracker_paths().is_same(zipper_tracker.shared_paths()), "replace_owned_write_zipper called with a zipper from a different ZipperHead");
+        let path = zipper.root_prefix_path().to_vec();
+        let mut map = zipper.into_map();
+        let mut source = map.write_zipper_at_path(&path);
+        let subtrie = source.take_map();
+        let root_val = source.remove_value();
+        drop(source);
+
+        let mut target = write_zipper_with_tracker(self, &path, zipper_tracker);
+        match subtrie {
+            Some(subtrie) => target.graft_map(subtrie),
+            None => { target.remove_branches(); },
+        }
+        let _ = match root_val {
+            Some(val) => target.set_value(val),
+            None => target.remove_value()
+        };
+        self.cleanup_write_zipper(target);
+    }
+    fn upgrade_read_zipper<'a, 'path>(&'a self, zipper: ReadZipperTracked<'a, 'path, V, A>) -> Result<WriteZipperTracked<'a, 'static, V, A>, (ReadZipperTracked<'a, 'path, V, A>, Conflict)> where 'trie: 'a {
+        assert!(self.tracker_paths().is_same(zipper.tracker().shared_paths()), "upgrade_read_zipper called with a zipper from a different ZipperHead");
+        let (tracker, root_prefix_path, focus_path) = zipper.try_into_write_tracker()?;
+        let mut write_zipper = write_zipper_with_tracker(self, &root_prefix_path, tracker);
+        write_zipper.descend_to(focus_path);
+        Ok(write_zipper)
+    }
+    fn downgrade_write_zipper<'a>(&'a self, zipper: WriteZipperTracked<'a, 'static, V, A>) -> ReadZipperTracked<'a, 'static, V, A> where 'trie: 'a {
+        zipper.into_read_zipper()
+    }
+    fn acquire_many<'a>(&'a self, requests: &[PathRequest]) -> Result<Vec<AcquiredZipper<'a, V, A>>, Conflict> where 'trie: 'a {
+        self.tracker_paths().try_add_many(requests)?;
+        Ok(zippers_for_added_requests(self, requests))
+    }
+    fn acquire_many_blocking<'a>(&'a self, requests: &[PathRequest], timeout: Option<Duration>) -> Result<Vec<AcquiredZipper<'a, V, A>>, Conflict> where 'trie: 'a {
+        self.tracker_paths().add_many_blocking(requests, timeout)?;
+        Ok(zippers_for_added_requests(self, requests))
+    }
+    fn acquire_branches<'a>(&'a self, depth: usize) -> Result<Vec<WriteZipperTracked<'a, 'static, V, A>>, Conflict> where 'trie: 'a {
+        //The paths are found and locked while no other zipper can be made, so a write zipper that is
+        // registered in the meantime can't change the nodes above `depth` until the branches are locked
+        let paths = self.with_inner_core_z(|z| {
+            self.tracker_paths().check_for_writers_shorter_than(depth)?;
+            let mut paths = vec![];
+            if depth == 0 {
+                paths.push(vec![]);
+            } else {
+                z.focus_stack.advance_if_empty_twostep(|root| root, |root| root.make_mut());
+                let alloc = z.alloc.clone();
+                let (root_node, root_val) = z.splitting_borrow_focus();
+                #[cfg(debug_assertions)]
+                let mut rz = ReadZipperUntracked::new_with_node_and_path_in(root_node, &[], 0, 0, root_val, alloc, None);
+                #[cfg(not(debug_assertions))]
+                let mut rz = ReadZipperUntracked::new_with_node_and_path_in(root_node, &[], 0, 0, root_val, alloc);
+                if rz.descend_first_k_path(depth) {
+                    loop {
+                        paths.push(rz.path().to_vec());
+                        if !rz.to_next_k_path(depth) {
+                            break
+                        }
+                    }
+                }
+            }
+            let requests: Vec<PathRequest> = paths.iter().map(|path| PathRequest::Write(path)).collect();
+            self.tracker_paths().try_add_many(&requests)?;
+            Ok(paths)
+        })?;
+        Ok(paths.iter().map(|path| {
+            let tracker = ZipperTracker::<TrackingWrite>::for_added_lock(self.tracker_paths().clone(), path);
+            write_zipper_with_tracker(self, path, tracker)
+        }).collect())
+    }
+    fn active_regions(&self) -> Vec<ActiveRegion> {
+        self.tracker_paths().active_regions()
+    }
     fn cleanup_write_zipper<ChildZ: ZipperWriting<V, A> + ZipperAbsolutePath>(&self, mut z: ChildZ) {
         let origin_path = z.take_root_prefix_path();
         drop(z);
@@ -322,6 +505,231 @@ impl<'trie, Z, V: 'trie + Clone + Send + Sync + Unpin, A: Allocator + 'trie> Zip
     }
 }
 
+/// A group of changes at several paths within a [ZipperHead], that take effect together
+///
+/// Each path written by the transaction is locked with the `ZipperHead`'s tracker, in the same way as a
+/// path written by [write_zipper_at_exclusive_path](ZipperCreation::write_zipper_at_exclusive_path), so it
+/// will conflict with other zippers exactly as an ordinary write zipper would.  However the zipper returned
+/// by [write_zipper_at_exclusive_path](ZipperTransaction::write_zipper_at_exclusive_path) edits a
+/// copy-on-write snapshot of the subtrie at the path, and the trie itself is untouched until
+/// [commit](ZipperTransaction::commit) grafts all of the snapshots into place.  The locks are held until
+/// every snapshot has been grafted, so no other zipper can observe a partially-applied transaction.
+///
+/// If the transaction is [rolled back](ZipperTransaction::rollback), or dropped without being committed
+/// (including by a panic), the snapshots are discarded and the trie is left as it was.  The snapshots are
+/// all prepared for grafting before the trie is touched, so a commit is only left partially applied if the
+/// `Drop` impl of a value replaced by the commit panics.
+///
+/// ```
+/// # use pathmap::trie_map::BytesTrieMap;
+/// # use pathmap::zipper::*;
+/// let mut map = BytesTrieMap::<usize>::new();
+/// map.insert(b"from:acct", 100);
+/// let zipper_head = map.zipper_head();
+///
+/// let txn = zipper_head.transaction();
+/// let mut from_z = txn.write_zipper_at_exclusive_path(b"from:acct").unwrap();
+/// let mut to_z = txn.write_zipper_at_exclusive_path(b"to:acct").unwrap();
+/// *from_z.get_value_mut().unwrap() -= 30;
+/// to_z.set_value(30);
+/// drop((from_z, to_z));
+/// txn.commit();
+///
+/// drop(zipper_head);
+/// assert_eq!(map.get(b"from:acct"), Some(&70));
+/// assert_eq!(map.get(b"to:acct"), Some(&30));
+/// ```
+pub struct ZipperTransaction<'a, 'trie, H, V, A = GlobalAlloc>
+    where H: ZipperCreation<'trie, V, A>, V: Clone + Send + Sync + Unpin + 'trie, A: Allocator + 'trie, 'trie: 'a
+{
+    head: &'a H,
+    /// Each `StagedWrite` is a leaked `Box`, which is reclaimed when the transaction is committed or dropped.
+    /// Raw pointers are stored, rather than `Box`es, because the zippers returned by
+    /// [write_zipper_at_exclusive_path](ZipperTransaction::write_zipper_at_exclusive_path) hold `&mut`
+    /// borrows into the snapshots, and moving a `Box` when the `Vec` grows would invalidate them
+    staged: UnsafeCell<Vec<*mut StagedWrite<'a, V, A>>>,
+    _trie: PhantomData<&'trie ()>,
+}
+
+//SAFETY: The raw pointers in `staged` are uniquely owned by the transaction, so it can be sent to another
+// thread whenever its contents could be
+unsafe impl<'a, 'trie, H, V, A> Send for ZipperTransaction<'a, 'trie, H, V, A>
+    where H: ZipperCreation<'trie, V, A> + Sync, V: Clone + Send + Sync + Unpin + 'trie, A: Allocator + 'trie, 'trie: 'a,
+    WriteZipperTracked<'a, 'static, V, A>: Send, BytesTrieMap<V, A>: Send
+{}
+
+/// A path written by a [ZipperTransaction]
+struct StagedWrite<'a, V: Clone + Send + Sync, A: Allocator> {
+    /// Holds the lock on the path, and is only written when the transaction is committed
+    target: WriteZipperTracked<'a, 'static, V, A>,
+    snapshot: BytesTrieMap<V, A>,
+}
+
+impl<'a, 'trie, H, V, A> ZipperTransaction<'a, 'trie, H, V, A>
+    where H: ZipperCreation<'trie, V, A>, V: Clone + Send + Sync + Unpin + 'trie, A: Allocator + 'trie, 'trie: 'a
+{
+    fn new(head: &'a H) -> Self {
+        Self {
+            head,
+            staged: UnsafeCell::new(vec![]),
+            _trie: PhantomData,
+        }
+    }
+    /// Locks the specified path for the duration of the transaction, and returns a [write zipper](ZipperWriting)
+    /// to stage changes to the subtrie at the path
+    ///
+    /// Returns a [Conflict] if the path conflicts with any outstanding zipper from the `ZipperHead`, including
+    /// the paths already written by this transaction.
+    pub fn write_zipper_at_exclusive_path<K: AsRef<[u8]>>(&self, path: K) -> Result<WriteZipperUntracked<'_, 'static, V, A>, Conflict> {
+        let target = self.head.write_zipper_at_exclusive_path(path)?;
+        let root_node = target.make_map().and_then(|map| map.into_root().0);
+        let snapshot = BytesTrieMap::new_with_root_in(root_node, target.value().cloned(), target.alloc());
+
+        let staged_write = Box::into_raw(Box::new(StagedWrite { target, snapshot }));
+        //SAFETY: The `staged` Vec is only accessed through `&self` here, and the transaction isn't `Sync`
+        unsafe{ &mut *self.staged.get() }.push(staged_write);
+        //SAFETY: The leaked `StagedWrite` won't move, and it won't be reclaimed until the transaction is
+        // consumed, which can't happen while the returned zipper borrows `self`.  The lock held by `target`
+        // guarantees only one zipper is ever created for each snapshot.
+        let snapshot = unsafe{ &mut *core::ptr::addr_of_mut!((*staged_write).snapshot) };
+        Ok(snapshot.write_zipper())
+    }
+    /// Reclaims ownership of the staged writes, leaving the transaction empty
+    fn take_staged(&mut self) -> Vec<StagedWrite<'a, V, A>> {
+        core::mem::take(self.staged.get_mut()).into_iter()
+            //SAFETY: Every pointer came from `Box::into_raw`, and the zippers borrowing the snapshots are
+            // gone because we have `&mut self`
+            .map(|staged_write| *unsafe{ Box::from_raw(staged_write) })
+            .collect()
+    }
+    /// Consumes the transaction, applying the changes at every path it has written to the trie
+    pub fn commit(mut self) {
+        //Prepare every graft before touching the trie, so nothing that can fail happens part way through
+        let prepared: Vec<_> = self.take_staged().into_iter().map(|StagedWrite { target, snapshot }| {
+            let (root_node, root_val) = snapshot.into_root();
+            let graft = BytesTrieMap::new_with_root_in(root_node, None, target.alloc());
+            (target, graft, root_val)
+        }).collect();
+        let mut targets = Vec::with_capacity(prepared.len());
+        for (mut target, graft, root_val) in prepared {
+            target.graft_map(graft);
+            let _ = match root_val {
+                Some(val) => target.set_value(val),
+                None => target.remove_value()
+            };
+            targets.push(target);
+        }
+        for target in targets {
+            self.head.cleanup_write_zipper(target);
+        }
+    }
+    /// Consumes the transaction, discarding all of its changes
+    ///
+    /// This has the same effect as dropping the transaction.
+    pub fn rollback(self) {
+        drop(self)
+    }
+}
+
+impl<'a, 'trie, H, V, A> Drop for ZipperTransaction<'a, 'trie, H, V, A>
+    where H: ZipperCreation<'trie, V, A>, V: Clone + Send + Sync + Unpin + 'trie, A: Allocator + 'trie, 'trie: 'a
+{
+    fn drop(&mut self) {
+        for StagedWrite { target, .. } in self.take_staged() {
+            self.head.cleanup_write_zipper(target);
+        }
+    }
+}
+
+/// A request for one of the zippers created together by [ZipperCreation::acquire_many]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathRequest<'p> {
+    /// Requests a [ReadZipperTracked] at the path
+    Read(&'p [u8]),
+    /// Requests a [WriteZipperTracked] with exclusive access to the path
+    Write(&'p [u8]),
+}
+
+impl<'p> PathRequest<'p> {
+    /// Returns the requested path
+    pub fn path(&self) -> &'p [u8] {
+        match self {
+            Self::Read(path) | Self::Write(path) => path,
+        }
+    }
+}
+
+/// A zipper created by [ZipperCreation::acquire_many], corresponding to a [PathRequest]
+pub enum AcquiredZipper<'a, V: Clone + Send + Sync, A: Allocator = GlobalAlloc> {
+    /// The zipper for a [PathRequest::Read]
+    Read(ReadZipperTracked<'a, 'static, V, A>),
+    /// The zipper for a [PathRequest::Write]
+    Write(WriteZipperTracked<'a, 'static, V, A>),
+}
+
+impl<'a, V: Clone + Send + Sync + Unpin, A: Allocator> AcquiredZipper<'a, V, A> {
+    /// Returns the read zipper, or `None` if this is a write zipper
+    pub fn into_read(self) -> Option<ReadZipperTracked<'a, 'static, V, A>> {
+        match self {
+            Self::Read(z) => Some(z),
+            Self::Write(_) => None,
+        }
+    }
+    /// Returns the write zipper, or `None` if this is a read zipper
+    pub fn into_write(self) -> Option<WriteZipperTracked<'a, 'static, V, A>> {
+        match self {
+            Self::Read(_) => None,
+            Self::Write(z) => Some(z),
+        }
+    }
+}
+
+/// Internal function to make the zippers for `requests`, after all their locks have been added
+fn zippers_for_added_requests<'a, 'trie: 'a, Z, V, A>(zh: &'a Z, requests: &[PathRequest]) -> Vec<AcquiredZipper<'a, V, A>>
+    where Z: ZipperCreationPriv<'trie, V, A>, V: 'trie + Clone + Send + Sync + Unpin, A: Allocator + 'trie
+{
+    requests.iter().map(|request| match *request {
+        PathRequest::Read(path) => {
+            let tracker = ZipperTracker::<TrackingRead>::for_added_lock(zh.tracker_paths().clone(), path);
+            AcquiredZipper::Read(read_zipper_with_tracker(zh, path, tracker))
+        },
+        PathRequest::Write(path) => {
+            let tracker = ZipperTracker::<TrackingWrite>::for_added_lock(zh.tracker_paths().clone(), path);
+            AcquiredZipper::Write(write_zipper_with_tracker(zh, path, tracker))
+        },
+    }).collect()
+}
+
+/// Internal function to make a [ReadZipperTracked] at `path`, for a tracker that has already been registered
+fn read_zipper_with_tracker<'a, 'trie: 'a, Z, V, A>(zh: &'a Z, path: &[u8], zipper_tracker: ZipperTracker<TrackingRead>) -> ReadZipperTracked<'a, 'static, V, A>
+    where Z: ZipperCreationPriv<'trie, V, A>, V: 'trie + Clone + Send + Sync + Unpin, A: Allocator + 'trie
+{
+    zh.with_inner_core_z(|z| {
+        z.focus_stack.advance_if_empty_twostep(|root| root, |root| root.make_mut());
+
+        let (root_node, root_val) = z.splitting_borrow_focus();
+        //SAFETY: See identical code in `read_zipper_at_borrowed_path` for more discussion
+        let root_node: &'trie dyn TrieNode<V, A> = unsafe{ core::mem::transmute(root_node) };
+        let root_val: Option<&'trie V> = root_val.map(|v| unsafe{ &*(v as *const _) } );
+
+        ReadZipperTracked::new_with_node_and_cloned_path_in(root_node, path, path.len(), 0, root_val, z.alloc.clone(), zipper_tracker)
+    })
+}
+
+/// Internal function to make a [WriteZipperTracked] at `path`, for a tracker that has already been registered
+fn write_zipper_with_tracker<'a, 'trie: 'a, Z, V, A>(zh: &'a Z, path: &[u8], zipper_tracker: ZipperTracker<TrackingWrite>) -> WriteZipperTracked<'a, 'static, V, A>
+    where Z: ZipperCreationPriv<'trie, V, A>, V: 'trie + Clone + Send + Sync + Unpin, A: Allocator + 'trie
+{
+    zh.with_inner_core_z(|z| {
+        let (zipper_root_node, zipper_root_val) = prepare_exclusive_write_path(z, path);
+        //SAFETY: See similar code in `read_zipper_at_borrowed_path` for more discussion
+        let zipper_root_node: &'trie mut TrieNodeODRc<V, A> = unsafe{ &mut *(zipper_root_node as *mut _) };
+        let zipper_root_val: &'trie mut Option<V> = unsafe{ &mut *(zipper_root_val as *mut _) };
+
+        WriteZipperTracked::new_with_node_and_cloned_path_internal_in(zipper_root_node, Some(zipper_root_val), path, path.len(), z.alloc.clone(), zipper_tracker)
+    })
+}
+
 /// Ensures that the node at the specified path exists, and is a [CellByteNode]
 ///
 /// Discussion: This function is fairly complicated because we are only able to safely access the top
@@ -1188,4 +1596,348 @@ mod tests {
 
         drop(zh);
     }
+
+    #[test]
+    fn zipper_transaction_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        map.insert(b"a:0", 0);
+        map.insert(b"b:0", 10);
+        map.insert(b"c:0", 20);
+        let zipper_head = map.zipper_head();
+
+        //A committed transaction applies all of its changes
+        let txn = zipper_head.transaction();
+        let mut a_z = txn.write_zipper_at_exclusive_path(b"a:").unwrap();
+        let mut b_z = txn.write_zipper_at_exclusive_path(b"b:").unwrap();
+        let mut e_z = txn.write_zipper_at_exclusive_path(b"e").unwrap();
+        a_z.descend_to(b"1");
+        a_z.set_value(1);
+        b_z.descend_to(b"0");
+        assert_eq!(b_z.remove_value(), Some(10));
+        b_z.reset();
+        b_z.descend_to(b"2");
+        b_z.set_value(12);
+        e_z.set_value(5);
+
+        //The written paths are locked until the transaction ends, but other paths are available
+        assert!(zipper_head.read_zipper_at_path(b"a:").is_err());
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"b:0").is_err());
+        assert!(txn.write_zipper_at_exclusive_path(b"a:1").is_err());
+        let c_z = zipper_head.read_zipper_at_path(b"c:").unwrap();
+        assert_eq!(c_z.val_count(), 1);
+        drop(c_z);
+        drop((a_z, b_z, e_z));
+        txn.commit();
+        assert!(zipper_head.read_zipper_at_path(b"a:").is_ok());
+
+        //A rolled-back transaction has no effect
+        let txn = zipper_head.transaction();
+        let mut c_z = txn.write_zipper_at_exclusive_path(b"c:").unwrap();
+        c_z.descend_to(b"0");
+        c_z.set_value(99);
+        let mut d_z = txn.write_zipper_at_exclusive_path(b"d:").unwrap();
+        d_z.descend_to(b"0");
+        d_z.set_value(30);
+        drop((c_z, d_z));
+        txn.rollback();
+
+        //A transaction dropped by a panic has no effect
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let txn = zipper_head.transaction();
+            let mut a_z = txn.write_zipper_at_exclusive_path(b"a:").unwrap();
+            a_z.remove_branches();
+            panic!("failure in the middle of a transaction");
+        }));
+        assert!(result.is_err());
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"a:").is_ok());
+        drop(zipper_head);
+
+        let mut contents: Vec<_> = map.iter().map(|(path, val)| (path, *val)).collect();
+        contents.sort();
+        assert_eq!(contents, vec![
+            (b"a:0".to_vec(), 0),
+            (b"a:1".to_vec(), 1),
+            (b"b:2".to_vec(), 12),
+            (b"c:0".to_vec(), 20),
+            (b"e".to_vec(), 5),
+        ]);
+        assert!(!map.read_zipper_at_path(b"d").path_exists());
+    }
+
+    #[test]
+    fn acquire_many_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        map.insert(b"a:0", 0);
+        map.insert(b"b:0", 1);
+        map.insert(b"c:0", 2);
+        let zipper_head = map.zipper_head();
+
+        //Requests that conflict with each other are rejected
+        let err = zipper_head.acquire_many(&[PathRequest::Write(b"a:"), PathRequest::Read(b"a:0"), PathRequest::Write(b"b:")]).err().unwrap();
+        assert_eq!(err.paths().collect::<Vec<_>>(), vec![&b"a:"[..]]);
+
+        //Every conflict with an existing zipper is reported, and nothing is acquired
+        let held_a = zipper_head.read_zipper_at_path(b"a:").unwrap();
+        let held_c = zipper_head.write_zipper_at_exclusive_path(b"c:0").unwrap();
+        let err = zipper_head.acquire_many(&[PathRequest::Write(b"a:0"), PathRequest::Write(b"b:"), PathRequest::Read(b"c:")]).err().unwrap();
+        assert_eq!(err.paths().collect::<Vec<_>>(), vec![&b"a:"[..], &b"c:0"[..]]);
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"b:").is_ok());
+        drop((held_a, held_c));
+
+        //All the zippers are created together
+        let mut zippers = zipper_head.acquire_many(&[PathRequest::Write(b"a:"), PathRequest::Read(b"b:"), PathRequest::Read(b"b:0")]).unwrap().into_iter();
+        let mut a_z = zippers.next().unwrap().into_write().unwrap();
+        let b_z = zippers.next().unwrap().into_read().unwrap();
+        let b0_z = zippers.next().unwrap().into_read().unwrap();
+        assert!(zipper_head.read_zipper_at_path(b"a:0").is_err());
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"b:").is_err());
+        a_z.descend_to(b"1");
+        a_z.set_value(b_z.val_count() + b0_z.val_count());
+        drop((a_z, b_z, b0_z));
+
+        //A blocking acquisition waits for the zippers it conflicts with
+        let held_b = zipper_head.read_zipper_at_path(b"b:").unwrap();
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                thread::sleep(std::time::Duration::from_millis(50));
+                drop(held_b);
+            });
+            let zippers = zipper_head.acquire_many_blocking(&[PathRequest::Write(b"b:"), PathRequest::Write(b"c:")], None).unwrap();
+            assert_eq!(zippers.len(), 2);
+        });
+        drop(zipper_head);
+        assert_eq!(map.get(b"a:1"), Some(&2));
+    }
+
+    #[test]
+    fn upgrade_downgrade_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        map.insert(b"a:0", 0);
+        map.insert(b"a:1", 1);
+        let zipper_head = map.zipper_head();
+
+        //The upgrade fails while another reader overlaps, and the reader keeps its position
+        let mut reader = zipper_head.read_zipper_at_path(b"a:").unwrap();
+        reader.descend_to(b"1");
+        let other_reader = zipper_head.read_zipper_at_path(b"a:1").unwrap();
+        let (reader, conflict) = zipper_head.upgrade_read_zipper(reader).err().unwrap();
+        assert_eq!(conflict.paths().collect::<Vec<_>>(), vec![&b"a:1"[..]]);
+        assert_eq!(reader.path(), b"1");
+        assert_eq!(reader.value(), Some(&1));
+        drop(other_reader);
+
+        //Once the reader is alone, it upgrades in place
+        let mut writer = zipper_head.upgrade_read_zipper(reader).unwrap();
+        assert_eq!(writer.path(), b"1");
+        assert!(zipper_head.read_zipper_at_path(b"a:0").is_err());
+        writer.set_value(10);
+
+        //Downgrading lets other readers in, and keeps the position
+        let reader = zipper_head.downgrade_write_zipper(writer);
+        assert_eq!(reader.path(), b"1");
+        assert_eq!(reader.value(), Some(&10));
+        let mut other_reader = zipper_head.read_zipper_at_path(b"a:").unwrap();
+        other_reader.descend_to(b"1");
+        assert_eq!(other_reader.value(), Some(&10));
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"a:1").is_err());
+        drop((reader, other_reader));
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"a:1").is_ok());
+    }
+
+    #[test]
+    fn acquire_branches_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        map.insert(b"a:0", 0);
+        map.insert(b"a:1", 1);
+        map.insert(b"b:0", 2);
+        map.insert(b"c", 3);
+        let zipper_head = map.zipper_head();
+
+        //A write zipper within a branch conflicts, and so does one above the branches
+        let writer = zipper_head.write_zipper_at_exclusive_path(b"b:0").unwrap();
+        assert_eq!(zipper_head.acquire_branches(2).err().unwrap().path(), b"b:0");
+        drop(writer);
+        let writer = zipper_head.write_zipper_at_exclusive_path(b"c").unwrap();
+        assert_eq!(zipper_head.acquire_branches(2).err().unwrap().path(), b"c");
+        drop(writer);
+
+        //Readers outside the branches don't conflict, and the branches are locked together
+        let reader = zipper_head.read_zipper_at_path(b"c").unwrap();
+        let branches = zipper_head.acquire_branches(2).unwrap();
+        let paths: Vec<_> = branches.iter().map(|z| z.root_prefix_path().to_vec()).collect();
+        assert_eq!(paths, vec![b"a:".to_vec(), b"b:".to_vec()]);
+        assert!(zipper_head.read_zipper_at_path(b"a:1").is_err());
+        drop((branches, reader));
+        assert_eq!(zipper_head.acquire_branches(0).unwrap().len(), 1);
+        assert!(zipper_head.acquire_branches(5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn owned_writer_into_read_zipper_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        map.insert(b"a:0", 0);
+        map.insert(b"b:0", 1);
+        let zipper_head = map.zipper_head();
+        let mut writer = zipper_head.take_owned_write_zipper_at_exclusive_path(b"a:").unwrap();
+        writer.descend_to(b"1");
+        writer.set_value(10);
+
+        //The read zipper keeps the writer's contents and focus, and holds the region as a reader
+        let mut reader = writer.into_read_zipper();
+        assert_eq!(reader.value(), Some(&10));
+        reader.reset();
+        assert_eq!(reader.val_count(), 2);
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"a:0").is_err());
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"b:").is_ok());
+
+        //Dropping the read zipper releases the region, which has been removed from the trie
+        drop(reader);
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"a:0").is_ok());
+        drop(zipper_head);
+        assert_eq!(map.iter().map(|(path, val)| (path, *val)).collect::<Vec<_>>(), vec![(b"b:0".to_vec(), 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "different ZipperHead")]
+    fn upgrade_from_other_head_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        let mut other_map = BytesTrieMap::<usize>::new();
+        let zipper_head = map.zipper_head();
+        let other_head = other_map.zipper_head();
+        let reader = other_head.read_zipper_at_path(b"a:").unwrap();
+        let _ = zipper_head.upgrade_read_zipper(reader);
+    }
+
+    #[test]
+    fn tracked_owned_zippers_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        map.insert(b"a:0", 0);
+        map.insert(b"a:1", 1);
+        map.insert(b"b:0", 2);
+        map.insert(b"b:", 3);
+        map.insert(b"c:0", 4);
+        let zipper_head = map.zipper_head();
+
+        //An owned reader holds its region while it is on another thread
+        let mut reader = zipper_head.owned_read_zipper_at_path(b"a:").unwrap();
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"a:1").is_err());
+        let reader_thread = thread::spawn(move || {
+            assert_eq!(reader.origin_path(), b"a:");
+            reader.descend_to(b"1");
+            reader.value().cloned()
+        });
+        assert_eq!(reader_thread.join().unwrap(), Some(1));
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"a:1").is_ok());
+
+        //An owned writer is cut from the trie, and returned to it
+        let mut writer = zipper_head.take_owned_write_zipper_at_exclusive_path(b"b:").unwrap();
+        assert!(zipper_head.read_zipper_at_path(b"b:0").is_err());
+        let writer_thread = thread::spawn(move || {
+            assert_eq!(writer.value(), Some(&3));
+            writer.set_value(30);
+            writer.descend_to(b"1");
+            writer.set_value(31);
+            writer
+        });
+        let writer = writer_thread.join().unwrap();
+        zipper_head.replace_owned_write_zipper(writer);
+        assert!(zipper_head.read_zipper_at_path(b"b:0").is_ok());
+
+        //An owned writer that is dropped removes its region
+        let writer = zipper_head.take_owned_write_zipper_at_exclusive_path(b"c:").unwrap();
+        drop(writer);
+        assert!(zipper_head.write_zipper_at_exclusive_path(b"c:").is_ok());
+        drop(zipper_head);
+
+        let mut contents: Vec<_> = map.iter().map(|(path, val)| (path, *val)).collect();
+        contents.sort();
+        assert_eq!(contents, vec![
+            (b"a:0".to_vec(), 0),
+            (b"a:1".to_vec(), 1),
+            (b"b:".to_vec(), 30),
+            (b"b:0".to_vec(), 2),
+            (b"b:1".to_vec(), 31),
+        ]);
+    }
+
+    #[test]
+    fn active_regions_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        map.insert(b"a:0", 0);
+        map.insert(b"b:0", 1);
+        let zipper_head = map.zipper_head();
+        assert!(zipper_head.active_regions().is_empty());
+
+        let reader = zipper_head.read_zipper_at_path(b"a:").unwrap();
+        let cloned_reader = reader.clone();
+        let writer = zipper_head.write_zipper_at_exclusive_path(b"c:").unwrap();
+        let regions: Vec<_> = zipper_head.active_regions().into_iter().map(|region| (region.path, region.kind, region.count)).collect();
+        assert_eq!(regions, vec![
+            (b"a:".to_vec(), RegionKind::Read, 2),
+            (b"c:".to_vec(), RegionKind::Write, 1),
+        ]);
+
+        //The conflict describes the lock it conflicted with
+        let conflict = zipper_head.read_zipper_at_path(b"c:0").err().unwrap();
+        assert_eq!(conflict.path(), b"c:");
+        assert_eq!(conflict.kind(), RegionKind::Write);
+        assert_eq!(conflict.reader_count(), None);
+        #[cfg(feature = "zipper_tracking")]
+        assert_eq!(conflict.backtraces().len(), 1);
+
+        drop(reader);
+        let conflict = zipper_head.write_zipper_at_exclusive_path(b"a:0").err().unwrap();
+        assert_eq!(conflict.path(), b"a:");
+        assert_eq!(conflict.reader_count().map(|cnt| cnt.get()), Some(1));
+
+        let downgraded = writer.into_read_zipper();
+        let regions = zipper_head.active_regions();
+        assert_eq!(regions.len(), 2);
+        assert_eq!((&regions[1].path[..], regions[1].kind), (&b"c:"[..], RegionKind::Read));
+        #[cfg(feature = "zipper_tracking")]
+        assert_eq!(regions[1].backtraces.len(), 1);
+        drop((cloned_reader, downgraded));
+        assert!(zipper_head.active_regions().is_empty());
+
+        //A lock at the root is included
+        let root_reader = zipper_head.read_zipper_at_path(b"").unwrap();
+        let regions: Vec<_> = zipper_head.active_regions().into_iter().map(|region| (region.path, region.kind, region.count)).collect();
+        assert_eq!(regions, vec![(vec![], RegionKind::Read, 1)]);
+        drop(root_reader);
+    }
+
+    #[test]
+    fn blocking_zipper_creation_test() {
+        let mut map = BytesTrieMap::<usize>::new();
+        map.insert(b"a:0", 0);
+        map.insert(b"b:0", 1);
+        let zipper_head = map.zipper_head();
+
+        //A timeout elapses while the conflicting reader is held
+        let reader = zipper_head.read_zipper_at_path(b"a:").unwrap();
+        let timeout = std::time::Duration::from_millis(20);
+        let conflict = zipper_head.write_zipper_at_exclusive_path_blocking(b"a:0", Some(timeout));
+        assert!(conflict.is_err());
+        assert!(zipper_head.write_zipper_at_exclusive_path_blocking(b"b:", Some(timeout)).is_ok());
+
+        //A blocked writer proceeds after the reader is dropped on another thread
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                thread::sleep(std::time::Duration::from_millis(50));
+                assert_eq!(reader.val_count(), 1);
+                drop(reader);
+            });
+            let mut writer = zipper_head.write_zipper_at_exclusive_path_blocking(b"a:0", None).unwrap();
+            writer.set_value(10);
+
+            //And a blocked reader proceeds after the writer is dropped on another thread
+            scope.spawn(move || {
+                thread::sleep(std::time::Duration::from_millis(50));
+                drop(writer);
+            });
+            let mut reader = zipper_head.read_zipper_at_path_blocking(b"a:", None).unwrap();
+            reader.descend_to(b"0");
+            assert_eq!(reader.value(), Some(&10));
+        });
+    }
 }
@@ -0,0 +1,239 @@
+use core::marker::PhantomData;
+
+use crate::utils::{BitMask, ByteMask};
+use crate::zipper::*;
+
+/// Joins several zippers by moving them in lockstep, descending only into the bytes common to all of them
+///
+/// Each zipper participates in the join for a specified number of bytes below its focus, called its join
+/// depth.  At each depth, the `child_mask`s of the zippers whose join depth hasn't been reached are
+/// intersected, and a zipper stays at its join depth while the others continue.  Each match is a path with
+/// a length equal to the largest join depth, that exists in every zipper up to that zipper's join depth.
+///
+/// Zippers with different join depths, together with zippers over permuted indexes, allow a
+/// [leapfrog triejoin](https://arxiv.org/abs/1210.0481) to be composed one variable at a time.  After each
+/// match, [zipper](ZipperJoin::zipper) gives access to the zippers, positioned at the match, so that the
+/// join can continue on the remaining variables.
+///
+/// ```
+/// # use pathmap::trie_map::BytesTrieMap;
+/// # use pathmap::zipper::*;
+/// let a = BytesTrieMap::from_iter([("apple", 1), ("banana", 2), ("cherry", 3)]);
+/// let b = BytesTrieMap::from_iter([("apple", 10), ("cherry", 30), ("durian", 40)]);
+/// let join = ZipperJoin::new([a.read_zipper(), b.read_zipper()], 6);
+/// let matches: Vec<_> = join.collect();
+/// assert_eq!(matches, vec![(b"cherry".to_vec(), [Some(&3), Some(&30)])]);
+/// ```
+pub struct ZipperJoin<'a, V, Z, const N: usize> {
+    zippers: [Z; N],
+    depths: [usize; N],
+    max_depth: usize,
+    path: Vec<u8>,
+    /// The bytes remaining to visit at each depth above the focus
+    stack: Vec<ByteMask>,
+    started: bool,
+    /// `true` if the join is positioned at a match
+    at_match: bool,
+    _v: PhantomData<&'a V>,
+}
+
+impl<'a, V: 'a, Z: ZipperMoving + ZipperReadOnlyValues<'a, V>, const N: usize> ZipperJoin<'a, V, Z, N> {
+    /// Creates a new `ZipperJoin`, joining all zippers for `depth` bytes below their focus
+    pub fn new(zippers: [Z; N], depth: usize) -> Self {
+        Self::new_with_depths(zippers, [depth; N])
+    }
+    /// Creates a new `ZipperJoin`, joining each zipper for the corresponding number of bytes in `depths`
+    ///
+    /// The join proceeds from the focus of each zipper.  The zippers are returned to that focus when the join
+    /// is exhausted.
+    pub fn new_with_depths(zippers: [Z; N], depths: [usize; N]) -> Self {
+        let max_depth = depths.iter().copied().max().unwrap_or(0);
+        Self {
+            zippers,
+            depths,
+            max_depth,
+            path: Vec::with_capacity(max_depth),
+            stack: Vec::with_capacity(max_depth),
+            started: false,
+            at_match: false,
+            _v: PhantomData,
+        }
+    }
+    /// Advances the join to the next match, and returns `true`, or returns `false` if there are no more
+    /// matches
+    pub fn next_match(&mut self) -> bool {
+        if !self.started {
+            self.started = true;
+            if self.max_depth == 0 {
+                self.at_match = self.zippers.iter().all(|z| z.path_exists());
+                return self.at_match
+            }
+            let mask = self.common_mask();
+            self.stack.push(mask);
+        } else if self.at_match {
+            self.at_match = false;
+            if self.max_depth == 0 {
+                return false
+            }
+            self.ascend_byte();
+        }
+
+        loop {
+            let mask = match self.stack.last_mut() {
+                Some(mask) => mask,
+                None => return false
+            };
+            match mask.iter().next() {
+                Some(byte) => {
+                    mask.clear_bit(byte);
+                    self.descend_byte(byte);
+                    if self.path.len() == self.max_depth {
+                        self.at_match = true;
+                        return true
+                    }
+                    let mask = self.common_mask();
+                    self.stack.push(mask);
+                },
+                None => {
+                    self.stack.pop();
+                    if self.stack.is_empty() {
+                        return false
+                    }
+                    self.ascend_byte();
+                }
+            }
+        }
+    }
+    /// Returns the path of the current match, relative to the focus of the zippers when the join began
+    pub fn path(&self) -> &[u8] {
+        &self.path
+    }
+    /// Returns the values of each zipper at the current match
+    pub fn values(&self) -> [Option<&'a V>; N] {
+        core::array::from_fn(|idx| self.zippers[idx].get_value())
+    }
+    /// Returns a reference to the zipper at `idx`, positioned at the current match
+    ///
+    /// A zipper with a join depth shorter than the current path is positioned at its join depth.
+    pub fn zipper(&self, idx: usize) -> &Z {
+        &self.zippers[idx]
+    }
+    /// Consumes the `ZipperJoin` and returns the zippers
+    pub fn into_zippers(self) -> [Z; N] {
+        self.zippers
+    }
+    /// Returns the intersection of the `child_mask`s of every zipper that is still joining
+    fn common_mask(&self) -> ByteMask {
+        let depth = self.path.len();
+        let mut mask = ByteMask::FULL;
+        for (z, &join_depth) in self.zippers.iter().zip(self.depths.iter()) {
+            if join_depth > depth {
+                mask = mask & z.child_mask();
+            }
+        }
+        mask
+    }
+    fn descend_byte(&mut self, byte: u8) {
+        let depth = self.path.len();
+        for (z, &join_depth) in self.zippers.iter_mut().zip(self.depths.iter()) {
+            if join_depth > depth {
+                z.descend_to_byte(byte);
+            }
+        }
+        self.path.push(byte);
+    }
+    fn ascend_byte(&mut self) {
+        self.path.pop();
+        let depth = self.path.len();
+        for (z, &join_depth) in self.zippers.iter_mut().zip(self.depths.iter()) {
+            if join_depth > depth {
+                z.ascend_byte();
+            }
+        }
+    }
+}
+
+impl<'a, V: 'a, Z: ZipperMoving + ZipperReadOnlyValues<'a, V>, const N: usize> Iterator for ZipperJoin<'a, V, Z, N> {
+    type Item = (Vec<u8>, [Option<&'a V>; N]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_match() {
+            Some((self.path.clone(), self.values()))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trie_map::BytesTrieMap;
+    use crate::zipper::*;
+
+    #[test]
+    fn zipper_join_test1() {
+        let a = BytesTrieMap::from_iter([("arrow", 0), ("bow", 1), ("cannon", 2), ("roman", 3), ("romane", 4), ("romanus", 5)]);
+        let b = BytesTrieMap::from_iter([("bow", 10), ("cannon", 11), ("romane", 12), ("romanus", 13), ("rubens", 14)]);
+        let c = BytesTrieMap::from_iter([("arrow", 20), ("bow", 21), ("romane", 22), ("rubens", 23)]);
+
+        let join = ZipperJoin::new([a.read_zipper(), b.read_zipper(), c.read_zipper()], 3);
+        let matches: Vec<_> = join.collect();
+        assert_eq!(matches, vec![
+            (b"bow".to_vec(), [Some(&1), Some(&10), Some(&21)]),
+            (b"rom".to_vec(), [None, None, None]),
+        ]);
+
+        let mut join = ZipperJoin::new([a.read_zipper(), b.read_zipper(), c.read_zipper()], 6);
+        assert!(join.next_match());
+        assert_eq!(join.path(), b"romane");
+        assert_eq!(join.values(), [Some(&4), Some(&12), Some(&22)]);
+        assert!(!join.next_match());
+        assert!(!join.next_match());
+        for z in join.into_zippers() {
+            assert_eq!(z.path(), b"");
+        }
+
+        let join = ZipperJoin::new([a.read_zipper(), b.read_zipper()], 0);
+        assert_eq!(join.count(), 1);
+        let empty = BytesTrieMap::<usize>::new();
+        let join = ZipperJoin::new([a.read_zipper(), empty.read_zipper()], 2);
+        assert_eq!(join.count(), 0);
+    }
+
+    #[test]
+    fn zipper_join_depths_test() {
+        let ids = BytesTrieMap::from_iter([("ab", 1), ("cd", 2), ("ef", 3)]);
+        let rel = BytesTrieMap::from_iter([("abX", 10), ("abY", 11), ("cxZ", 12), ("efW", 13), ("zzz", 14)]);
+
+        let join = ZipperJoin::new_with_depths([ids.read_zipper(), rel.read_zipper()], [2, 3]);
+        let matches: Vec<_> = join.collect();
+        assert_eq!(matches, vec![
+            (b"abX".to_vec(), [Some(&1), Some(&10)]),
+            (b"abY".to_vec(), [Some(&1), Some(&11)]),
+            (b"efW".to_vec(), [Some(&3), Some(&13)]),
+        ]);
+    }
+
+    /// Joins `R(a, b)` with `S(b, c)` on `b`, using an index of `R` permuted to `(b, a)`
+    #[test]
+    fn zipper_join_permuted_test() {
+        let r = [(1u8, 2u8), (3, 2), (4, 5)];
+        let s = [(2u8, 7u8), (5, 8), (6, 9)];
+        let r_ba: BytesTrieMap<()> = r.iter().map(|&(a, b)| ([b, a], ())).collect();
+        let s_bc: BytesTrieMap<()> = s.iter().map(|&(b, c)| ([b, c], ())).collect();
+
+        let mut results = vec![];
+        let mut join = ZipperJoin::new([r_ba.read_zipper(), s_bc.read_zipper()], 1);
+        while join.next_match() {
+            let b = join.path()[0];
+            let mut a_z = join.zipper(0).fork_read_zipper();
+            while a_z.to_next_val() {
+                let mut c_z = join.zipper(1).fork_read_zipper();
+                while c_z.to_next_val() {
+                    results.push((a_z.path()[0], b, c_z.path()[0]));
+                }
+            }
+        }
+        assert_eq!(results, vec![(1, 2, 7), (3, 2, 7), (4, 5, 8)]);
+    }
+}
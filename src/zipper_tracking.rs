@@ -1,10 +1,14 @@
 use std::marker::PhantomData;
 use std::num::NonZero;
 use std::num::NonZeroU32;
-use std::sync::Arc;
-use std::sync::RwLock;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+#[cfg(feature = "zipper_tracking")]
+use std::backtrace::Backtrace;
 
 use crate::trie_map::BytesTrieMap;
+use crate::zipper_head::PathRequest;
 use crate::zipper::{ReadZipperUntracked, Zipper, ZipperAbsolutePath, ZipperForking, ZipperMoving, ZipperReadOnlyValues, ZipperWriting, ZipperIteration, ZipperReadOnlyIteration, };
 
 /// Marker to track an outstanding read zipper
@@ -48,6 +52,9 @@ use tracking_internal::TrackingMode;
 pub struct ZipperTracker<M: TrackingMode> {
     all_paths: SharedTrackerPaths,
     this_path: Vec<u8>,
+    /// Identifies the tracker's entry in [TrackerPaths::origins]
+    #[cfg(feature = "zipper_tracking")]
+    origin_id: u64,
     _is_tracking: PhantomData<M>,
 }
 
@@ -55,18 +62,42 @@ impl Clone for ZipperTracker<TrackingRead> {
     fn clone(&self) -> Self {
         self.all_paths
             .add_reader_unchecked(self.this_path.as_slice());
-        Self {
-            all_paths: self.all_paths.clone(),
-            this_path: self.this_path.clone(),
-            _is_tracking: PhantomData,
-        }
+        Self::for_added_lock(self.all_paths.clone(), &self.this_path)
     }
 }
 
+/// Whether a region of the trie is held by read zippers or by a write zipper
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RegionKind {
+    /// The region is held by one or more read zippers
+    Read,
+    /// The region is held by a write zipper
+    Write,
+}
+
+/// A region of the trie held by outstanding zippers, returned by [SharedTrackerPaths::active_regions]
+#[derive(Clone, Debug)]
+pub struct ActiveRegion {
+    /// The path to the root of the region
+    pub path: Vec<u8>,
+    /// Whether the region is held by read zippers or by a write zipper
+    pub kind: RegionKind,
+    /// The number of zippers holding the region
+    pub count: usize,
+    /// Where the tracker of each zipper holding the region was created
+    #[cfg(feature = "zipper_tracking")]
+    pub backtraces: Vec<Arc<Backtrace>>,
+}
+
 #[derive(Debug)]
 pub struct Conflict {
     with: IsTracking,
     at: Vec<u8>,
+    /// Further conflicts, when several paths were requested together
+    also: Vec<Conflict>,
+    /// Where the tracker of each zipper holding the conflicting lock was created
+    #[cfg(feature = "zipper_tracking")]
+    backtraces: Vec<Arc<Backtrace>>,
 }
 
 impl std::fmt::Display for Conflict {
@@ -77,17 +108,69 @@ impl std::fmt::Display for Conflict {
             IsTracking::ReadZipper(cnt) => write!(f, "read zipper (arity: {cnt:?})"),
         };
         let path = &self.at;
-        writeln!(f, " @ {path:?}")
+        let _ = writeln!(f, " @ {path:?}");
+        #[cfg(feature = "zipper_tracking")]
+        for backtrace in self.backtraces.iter() {
+            let _ = writeln!(f, "zipper created at:\n{backtrace}");
+        }
+        for conflict in self.also.iter() {
+            let _ = write!(f, "{conflict}");
+        }
+        Ok(())
     }
 }
 
 impl std::error::Error for Conflict {}
 
 impl Conflict {
+    /// Returns the path of every lock that caused the conflict
+    pub fn paths(&self) -> impl Iterator<Item=&[u8]> + '_ {
+        core::iter::once(&self.at[..]).chain(self.also.iter().map(|conflict| &conflict.at[..]))
+    }
+
+    /// Returns the path of the first lock that caused the conflict
+    pub fn path(&self) -> &[u8] {
+        &self.at
+    }
+
+    /// Returns whether the first conflicting lock is held by read zippers or by a write zipper
+    pub fn kind(&self) -> RegionKind {
+        match self.with {
+            IsTracking::WriteZipper => RegionKind::Write,
+            IsTracking::ReadZipper(_) => RegionKind::Read,
+        }
+    }
+
+    /// Returns the number of read zippers holding the first conflicting lock, or `None` if it is held
+    /// by a write zipper
+    pub fn reader_count(&self) -> Option<NonZeroU32> {
+        match self.with {
+            IsTracking::WriteZipper => None,
+            IsTracking::ReadZipper(cnt) => Some(cnt),
+        }
+    }
+
+    /// Returns the conflicts at other paths, when several paths were requested together
+    pub fn others(&self) -> &[Conflict] {
+        &self.also
+    }
+
+    /// Returns where the tracker of each zipper holding the first conflicting lock was created
+    ///
+    /// Backtraces are only captured when enabled by the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment
+    /// variables, as described for [Backtrace::capture].  Otherwise each backtrace is disabled.
+    #[cfg(feature = "zipper_tracking")]
+    pub fn backtraces(&self) -> &[Arc<Backtrace>] {
+        &self.backtraces
+    }
+
     fn write_conflict(path: &[u8]) -> Conflict {
         Conflict {
             with: IsTracking::WriteZipper,
             at: path.to_vec(),
+            also: vec![],
+            #[cfg(feature = "zipper_tracking")]
+            backtraces: vec![],
         }
     }
 
@@ -95,9 +178,19 @@ impl Conflict {
         Conflict {
             with: IsTracking::ReadZipper(cnt),
             at: path.to_vec(),
+            also: vec![],
+            #[cfg(feature = "zipper_tracking")]
+            backtraces: vec![],
         }
     }
 
+    /// Combines a non-empty list of conflicts into a single `Conflict`
+    fn combine(mut conflicts: Vec<Conflict>) -> Conflict {
+        let mut first = conflicts.remove(0);
+        first.also = conflicts;
+        first
+    }
+
     fn check_for_lock_along_path<'a, A: Clone + Send + Sync + Unpin>(
         path: &[u8],
         zipper: &'a mut ReadZipperUntracked<A>,
@@ -169,21 +262,168 @@ impl Conflict {
 ///
 /// Use [SharedTrackerPaths::default] to make a new registry
 //
-// NOTE for the future: We were considering using a lockless queue in place of the `RwLock` to guard
+// NOTE for the future: We were considering using a lockless queue in place of the `Mutex` to guard
 // this object, with the understanding that all new zipper creation would be serialized.  The idea is
 // that the zipper `Drop` implementations would enquque paths, and then the queue would be drained prior
 // to creating any new zippers.  So a queue with lockless enqueue means there is no critical section
 // even if the zippers move to different threads.  This is still a valid approach.  If, however,
-// `SharedTrackerPaths` is changed to be `!Sync` by replacing the `RwLock`, we should move the
+// `SharedTrackerPaths` is changed to be `!Sync` by replacing the `Mutex`, we should move the
 // `SharedTrackerPaths` inside the `Mutex` inside [ZipperHeadOwned](crate::zipper::ZipperHeadOwned) so
 // `ZipperHeadOwned` remains `Sync`
+//
+// Threads waiting for a path to become available wait on a `Condvar` keyed by the path of the lock they
+// conflicted with, and releasing a lock wakes the waiters keyed by its path.  A woken waiter re-checks its
+// request, and waits again on the next conflicting lock if there is one.
 #[derive(Clone, Default)]
-pub struct SharedTrackerPaths(Arc<RwLock<TrackerPaths>>);
+pub struct SharedTrackerPaths(Arc<Mutex<TrackerPaths>>);
 
 #[derive(Clone, Default)]
 struct TrackerPaths {
     read_paths: BytesTrieMap<NonZeroU32>,
     written_paths: BytesTrieMap<()>,
+    /// The condvar and the number of waiting threads, keyed by the path of the lock they are waiting on
+    waiters: HashMap<Vec<u8>, (Arc<Condvar>, usize)>,
+    /// Where each outstanding tracker was created, keyed by the tracker's `origin_id`
+    #[cfg(feature = "zipper_tracking")]
+    origins: HashMap<u64, TrackerOrigin>,
+    #[cfg(feature = "zipper_tracking")]
+    next_origin_id: u64,
+}
+
+#[cfg(feature = "zipper_tracking")]
+#[derive(Clone)]
+struct TrackerOrigin {
+    path: Vec<u8>,
+    kind: RegionKind,
+    backtrace: Arc<Backtrace>,
+}
+
+impl TrackerPaths {
+    fn register_waiter(&mut self, lock_path: &[u8]) -> Arc<Condvar> {
+        let (condvar, cnt) = self.waiters.entry(lock_path.to_vec()).or_default();
+        *cnt += 1;
+        condvar.clone()
+    }
+    fn unregister_waiter(&mut self, lock_path: &[u8]) {
+        if let Some((_, cnt)) = self.waiters.get_mut(lock_path) {
+            *cnt -= 1;
+            if *cnt == 0 {
+                self.waiters.remove(lock_path);
+            }
+        }
+    }
+    fn try_add_writer(&mut self, path: &[u8]) -> Result<(), Conflict> {
+        Conflict::check_for_write_conflict(path, &self.written_paths, Conflict::write_conflict).map_err(|c| self.with_backtraces(c))?;
+        Conflict::check_for_read_conflict(path, &self.read_paths, Conflict::read_conflict).map_err(|c| self.with_backtraces(c))?;
+        let mut writer = self.written_paths.write_zipper_at_path(path);
+        writer.set_value(());
+        Ok(())
+    }
+    fn try_add_reader(&mut self, path: &[u8]) -> Result<(), Conflict> {
+        Conflict::check_for_write_conflict(path, &self.written_paths, Conflict::write_conflict).map_err(|c| self.with_backtraces(c))?;
+        let mut writer = self.read_paths.write_zipper_at_path(path);
+        let value = writer.get_value_mut();
+        match value {
+            Some(cnt) => match cnt.checked_add(1) {
+                Some(new_cnt) => {
+                    *cnt = new_cnt;
+                    Ok(())
+                }
+                None => Err(Conflict::read_conflict(NonZero::<u32>::MAX, path)),
+            },
+            None => {
+                writer.set_value(NonZero::<u32>::MIN);
+                Ok(())
+            }
+        }
+    }
+    /// Adds a lock for each request that doesn't conflict, and returns which requests were added along
+    /// with the conflicts for the others
+    fn add_each(&mut self, requests: &[PathRequest]) -> (Vec<bool>, Vec<Conflict>) {
+        let mut conflicts = vec![];
+        let added = requests.iter().map(|request| {
+            let result = match request {
+                PathRequest::Read(path) => self.try_add_reader(path),
+                PathRequest::Write(path) => self.try_add_writer(path),
+            };
+            result.map_err(|conflict| conflicts.push(conflict)).is_ok()
+        }).collect();
+        (added, conflicts)
+    }
+    /// Adds a lock for every request, or none of them
+    fn try_add_many(&mut self, requests: &[PathRequest]) -> Result<(), Conflict> {
+        let (added, conflicts) = self.add_each(requests);
+        if conflicts.is_empty() {
+            return Ok(())
+        }
+        for (request, _) in requests.iter().zip(added).filter(|(_, added)| *added) {
+            match request {
+                PathRequest::Read(path) => { self.remove_reader(path); },
+                PathRequest::Write(path) => { self.remove_writer(path); },
+            }
+        }
+        Err(Conflict::combine(conflicts))
+    }
+    fn remove_reader(&mut self, path: &[u8]) -> bool {
+        self.wake_waiters(path);
+        let mut write_zipper = self.read_paths.write_zipper_at_path(path);
+        match write_zipper.get_value_mut() {
+            Some(cnt) => {
+                if *cnt == NonZero::<u32>::MIN {
+                    write_zipper.remove_value();
+                } else {
+                    *cnt = unsafe { NonZero::new_unchecked(cnt.get() - 1) };
+                };
+                true
+            }
+            None => false,
+        }
+    }
+    fn remove_writer(&mut self, path: &[u8]) -> bool {
+        self.wake_waiters(path);
+        self.written_paths.write_zipper_at_path(path).remove_value().is_some()
+    }
+    fn wake_waiters(&self, lock_path: &[u8]) {
+        if let Some((condvar, _)) = self.waiters.get(lock_path) {
+            condvar.notify_all();
+        }
+    }
+    /// Attaches the backtraces of the trackers holding the conflicting lock to `conflict`
+    #[cfg(feature = "zipper_tracking")]
+    fn with_backtraces(&self, mut conflict: Conflict) -> Conflict {
+        conflict.backtraces = self.backtraces(&conflict.at, conflict.kind());
+        conflict
+    }
+    #[cfg(not(feature = "zipper_tracking"))]
+    fn with_backtraces(&self, conflict: Conflict) -> Conflict {
+        conflict
+    }
+    #[cfg(feature = "zipper_tracking")]
+    fn backtraces(&self, path: &[u8], kind: RegionKind) -> Vec<Arc<Backtrace>> {
+        let mut origins: Vec<_> = self.origins.iter()
+            .filter(|(_, origin)| origin.kind == kind && origin.path == path)
+            .collect();
+        origins.sort_by_key(|(id, _)| **id);
+        origins.into_iter().map(|(_, origin)| origin.backtrace.clone()).collect()
+    }
+    /// Returns every region held by outstanding zippers, ordered by path
+    fn active_regions(&self) -> Vec<ActiveRegion> {
+        fn locks<V: Clone + Send + Sync + Unpin>(map: &BytesTrieMap<V>) -> impl Iterator<Item=(Vec<u8>, &V)> {
+            let root_lock = map.read_zipper().get_value().map(|lock| (vec![], lock));
+            root_lock.into_iter().chain(map.iter())
+        }
+        let mut regions: Vec<ActiveRegion> = locks(&self.written_paths)
+            .map(|(path, _)| (path, RegionKind::Write, 1))
+            .chain(locks(&self.read_paths).map(|(path, cnt)| (path, RegionKind::Read, cnt.get() as usize)))
+            .map(|(path, kind, count)| ActiveRegion {
+                #[cfg(feature = "zipper_tracking")]
+                backtraces: self.backtraces(&path, kind),
+                path, kind, count,
+            })
+            .collect();
+        regions.sort_by(|a, b| (&a.path, a.kind).cmp(&(&b.path, b.kind)));
+        regions
+    }
 }
 
 /// Represents the status of a specific path, returned by [SharedTrackerPaths::path_status]
@@ -202,12 +442,44 @@ impl SharedTrackerPaths {
     where
         F: FnOnce(&mut TrackerPaths) -> R,
     {
-        let mut guard = self.0.write().unwrap();
+        let mut guard = self.0.lock().unwrap();
         let r = f(&mut guard);
         drop(guard);
         r
     }
 
+    /// Calls `try_f` until it succeeds, waiting for the lock at the path of each [Conflict] to be released
+    /// before trying again.  Returns the most recent `Conflict` if `timeout` elapses first
+    fn with_paths_blocking<F>(&self, timeout: Option<Duration>, mut try_f: F) -> Result<(), Conflict>
+    where
+        F: FnMut(&mut TrackerPaths) -> Result<(), Conflict>,
+    {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut guard: MutexGuard<TrackerPaths> = self.0.lock().unwrap();
+        loop {
+            let conflict = match try_f(&mut guard) {
+                Ok(()) => return Ok(()),
+                Err(conflict) => conflict
+            };
+            let remaining = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if deadline <= now {
+                        return Err(conflict)
+                    }
+                    Some(deadline - now)
+                },
+                None => None
+            };
+            let condvar = guard.register_waiter(&conflict.at);
+            guard = match remaining {
+                Some(remaining) => condvar.wait_timeout(guard, remaining).unwrap().0,
+                None => condvar.wait(guard).unwrap(),
+            };
+            guard.unregister_waiter(&conflict.at);
+        }
+    }
+
     /// Returns the status of a specific path, which corresponds to whether a a request for
     /// a zipper at precisely the same instant would have succeeded or failed
     ///
@@ -230,38 +502,71 @@ impl SharedTrackerPaths {
     }
 
     fn try_add_writer(&self, path: &[u8]) -> Result<(), Conflict> {
-        let try_add_writer_internal = |all_paths: &mut TrackerPaths| {
-            Conflict::check_for_write_conflict(path, &all_paths.written_paths, Conflict::write_conflict)?;
-            Conflict::check_for_read_conflict(path, &all_paths.read_paths, Conflict::read_conflict)?;
-            let mut writer = all_paths.written_paths.write_zipper_at_path(path);
-            writer.set_value(());
-            Ok(())
-        };
-
-        self.with_paths(try_add_writer_internal)
+        self.with_paths(|all_paths| all_paths.try_add_writer(path))
     }
 
     fn try_add_reader(&self, path: &[u8]) -> Result<(), Conflict> {
-        let try_add_reader_internal = |all_paths: &mut TrackerPaths| {
-            Conflict::check_for_write_conflict(path, &all_paths.written_paths, Conflict::write_conflict)?;
-            let mut writer = all_paths.read_paths.write_zipper_at_path(path);
-            let value = writer.get_value_mut();
-            match value {
-                Some(cnt) => match cnt.checked_add(1) {
-                    Some(new_cnt) => {
-                        *cnt = new_cnt;
-                        Ok(())
-                    }
-                    None => Err(Conflict::read_conflict(NonZero::<u32>::MAX, path)),
-                },
-                None => {
-                    writer.set_value(NonZero::<u32>::MIN);
-                    Ok(())
-                }
+        self.with_paths(|all_paths| all_paths.try_add_reader(path))
+    }
+
+    /// Adds a lock for every request together, or returns a `Conflict` naming every conflicting path
+    ///
+    /// Requests that conflict with each other are reported without consulting the registry, because
+    /// waiting can't resolve them.
+    pub(crate) fn try_add_many(&self, requests: &[PathRequest]) -> Result<(), Conflict> {
+        Self::check_requests(requests)?;
+        self.with_paths(|all_paths| all_paths.try_add_many(requests))
+    }
+
+    /// Same as [try_add_many](Self::try_add_many), but waits for conflicting zippers to be released
+    pub(crate) fn add_many_blocking(&self, requests: &[PathRequest], timeout: Option<Duration>) -> Result<(), Conflict> {
+        Self::check_requests(requests)?;
+        self.with_paths_blocking(timeout, |all_paths| all_paths.try_add_many(requests))
+    }
+
+    /// Returns every region of the trie held by outstanding zippers, ordered by path
+    ///
+    /// As with any asynchronous operation, the regions may have changed by the time this method returns.
+    /// The regions of zippers created by unchecked methods are only included in debug builds.
+    pub fn active_regions(&self) -> Vec<ActiveRegion> {
+        self.with_paths(|all_paths| all_paths.active_regions())
+    }
+
+    /// Returns a `Conflict` if a write lock is held at a path shorter than `len`
+    pub(crate) fn check_for_writers_shorter_than(&self, len: usize) -> Result<(), Conflict> {
+        self.with_paths(|all_paths| {
+            let root_lock = all_paths.written_paths.get(b"").map(|_| vec![]);
+            let short_lock = root_lock.into_iter()
+                .chain(all_paths.written_paths.iter().map(|(path, _)| path))
+                .find(|path| path.len() < len);
+            match short_lock {
+                Some(path) => Err(all_paths.with_backtraces(Conflict::write_conflict(&path))),
+                None => Ok(())
             }
-        };
+        })
+    }
 
-        self.with_paths(try_add_reader_internal)
+    /// Returns `true` if `self` and `other` refer to the same registry
+    pub(crate) fn is_same(&self, other: &SharedTrackerPaths) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Checks that a set of requests don't conflict with each other
+    fn check_requests(requests: &[PathRequest]) -> Result<(), Conflict> {
+        let (_, conflicts) = TrackerPaths::default().add_each(requests);
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(Conflict::combine(conflicts))
+        }
+    }
+
+    fn add_writer_blocking(&self, path: &[u8], timeout: Option<Duration>) -> Result<(), Conflict> {
+        self.with_paths_blocking(timeout, |all_paths| all_paths.try_add_writer(path))
+    }
+
+    fn add_reader_blocking(&self, path: &[u8], timeout: Option<Duration>) -> Result<(), Conflict> {
+        self.with_paths_blocking(timeout, |all_paths| all_paths.try_add_reader(path))
     }
 
     /// Adds a new reader without checking to see whether it conflicts with existing writers
@@ -290,7 +595,7 @@ enum IsTracking {
 
 impl<M: TrackingMode> core::fmt::Debug for ZipperTracker<M> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let all_paths = self.all_paths.0.read().unwrap();
+        let all_paths = self.all_paths.0.lock().unwrap();
         let _ = writeln!(
             f,
             "ZipperTracker {{ type = {:?}, path = {:?}",
@@ -310,12 +615,50 @@ impl<M: TrackingMode> core::fmt::Debug for ZipperTracker<M> {
 }
 
 impl<M: TrackingMode> ZipperTracker<M> {
-    /// Destroy a `ZipperTracker` without invoking Drop code to release the lock
-    fn dismantle(self) -> (SharedTrackerPaths, Vec<u8>) {
+    /// Returns the registry in which the tracker's lock is held
+    pub(crate) fn shared_paths(&self) -> &SharedTrackerPaths {
+        &self.all_paths
+    }
+    /// Makes a `ZipperTracker` for a lock that has already been added to `shared_paths`
+    pub(crate) fn for_added_lock(shared_paths: SharedTrackerPaths, path: &[u8]) -> Self {
+        //The backtrace is captured before taking the lock, so other threads aren't held up while it's walked
+        #[cfg(feature = "zipper_tracking")]
+        let backtrace = Arc::new(Backtrace::capture());
+        #[cfg(feature = "zipper_tracking")]
+        let origin_id = shared_paths.with_paths(|paths| {
+            let origin_id = paths.next_origin_id;
+            paths.next_origin_id += 1;
+            let kind = if M::tracks_writes() { RegionKind::Write } else { RegionKind::Read };
+            paths.origins.insert(origin_id, TrackerOrigin { path: path.to_vec(), kind, backtrace });
+            origin_id
+        });
+        Self {
+            all_paths: shared_paths,
+            this_path: path.to_vec(),
+            #[cfg(feature = "zipper_tracking")]
+            origin_id,
+            _is_tracking: PhantomData,
+        }
+    }
+    /// Consumes the tracker without invoking Drop code to release the lock, and returns a tracker with a
+    /// different mode for the same path, after the lock has been exchanged
+    fn into_mode<N: TrackingMode>(self) -> ZipperTracker<N> {
         let tracker_shell = core::mem::ManuallyDrop::new(self);
         let all_paths = unsafe { core::ptr::read(&tracker_shell.all_paths) };
         let this_path = unsafe { core::ptr::read(&tracker_shell.this_path) };
-        (all_paths, this_path)
+        #[cfg(feature = "zipper_tracking")]
+        all_paths.with_paths(|paths| {
+            if let Some(origin) = paths.origins.get_mut(&tracker_shell.origin_id) {
+                origin.kind = if N::tracks_writes() { RegionKind::Write } else { RegionKind::Read };
+            }
+        });
+        ZipperTracker::<N> {
+            all_paths,
+            this_path,
+            #[cfg(feature = "zipper_tracking")]
+            origin_id: tracker_shell.origin_id,
+            _is_tracking: PhantomData,
+        }
     }
 }
 
@@ -323,11 +666,29 @@ impl ZipperTracker<TrackingRead> {
     /// Create a new `ZipperTracker` to track a read zipper
     pub fn new(shared_paths: SharedTrackerPaths, path: &[u8]) -> Result<Self, Conflict> {
         shared_paths.try_add_reader(path)?;
-        Ok(Self {
-            all_paths: shared_paths,
-            this_path: path.to_vec(),
-            _is_tracking: PhantomData,
-        })
+        Ok(Self::for_added_lock(shared_paths, path))
+    }
+    /// Same as [new](Self::new), but waits for conflicting zippers to be released, until `timeout` elapses
+    /// or indefinitely if `timeout` is `None`
+    pub fn new_blocking(shared_paths: SharedTrackerPaths, path: &[u8], timeout: Option<Duration>) -> Result<Self, Conflict> {
+        shared_paths.add_reader_blocking(path, timeout)?;
+        Ok(Self::for_added_lock(shared_paths, path))
+    }
+    /// Consumes the reader tracker, and returns a new writer tracker with the same path, if no other zipper
+    /// conflicts with a writer at the path.  Otherwise returns the reader tracker unchanged, with the `Conflict`
+    pub fn try_into_writer(self) -> Result<ZipperTracker<TrackingWrite>, (Self, Conflict)> {
+        //The exchange happens in one critical section, so no other zipper can be created in between
+        let result = self.all_paths.with_paths(|paths| {
+            paths.remove_reader(&self.this_path);
+            paths.try_add_writer(&self.this_path).map_err(|conflict| {
+                paths.try_add_reader(&self.this_path).unwrap();
+                conflict
+            })
+        });
+        match result {
+            Ok(()) => Ok(self.into_mode()),
+            Err(conflict) => Err((self, conflict))
+        }
     }
 }
 
@@ -335,22 +696,21 @@ impl ZipperTracker<TrackingWrite> {
     /// Create a new `ZipperTracker` to track a write zipper
     pub fn new(shared_paths: SharedTrackerPaths, path: &[u8]) -> Result<Self, Conflict> {
         shared_paths.try_add_writer(path)?;
-        Ok(Self {
-            all_paths: shared_paths,
-            this_path: path.to_vec(),
-            _is_tracking: PhantomData,
-        })
+        Ok(Self::for_added_lock(shared_paths, path))
+    }
+    /// Same as [new](Self::new), but waits for conflicting zippers to be released, until `timeout` elapses
+    /// or indefinitely if `timeout` is `None`
+    pub fn new_blocking(shared_paths: SharedTrackerPaths, path: &[u8], timeout: Option<Duration>) -> Result<Self, Conflict> {
+        shared_paths.add_writer_blocking(path, timeout)?;
+        Ok(Self::for_added_lock(shared_paths, path))
     }
     /// Consumes the writer tracker, and returns a new reader tracker with the same path
     pub fn into_reader(self) -> ZipperTracker<TrackingRead> {
-        let (all_paths, this_path) = self.dismantle();
         //We add the reader lock first before removing the writer, so there is no chance another thread will
         // grab a writer in between.
-        all_paths.add_reader_unchecked(&this_path);
-        Self::remove_lock(&all_paths, &this_path);
-        ZipperTracker::<TrackingRead> {
-            all_paths, this_path, _is_tracking: PhantomData
-        }
+        self.all_paths.add_reader_unchecked(&self.this_path);
+        Self::remove_lock(&self.all_paths, &self.this_path);
+        self.into_mode()
     }
 }
 
@@ -359,24 +719,9 @@ impl<M: TrackingMode> ZipperTracker<M> {
     fn remove_lock(all_paths: &SharedTrackerPaths, this_path: &[u8]) {
         let is_removed = all_paths.with_paths(|paths| {
             if M::tracks_reads() {
-                let mut write_zipper = paths.read_paths.write_zipper_at_path(this_path);
-                match write_zipper.get_value_mut() {
-                    Some(cnt) => {
-                        if *cnt == NonZero::<u32>::MIN {
-                            write_zipper.remove_value();
-                        } else {
-                            *cnt = unsafe { NonZero::new_unchecked(cnt.get() - 1) };
-                        };
-                        true
-                    }
-                    None => false,
-                }
+                paths.remove_reader(this_path)
             } else {
-                let removed = paths
-                    .written_paths
-                    .write_zipper_at_path(this_path)
-                    .remove_value();
-                removed.is_some()
+                paths.remove_writer(this_path)
             }
         });
         if !is_removed {
@@ -388,5 +733,7 @@ impl<M: TrackingMode> ZipperTracker<M> {
 impl<M: TrackingMode> Drop for ZipperTracker<M> {
     fn drop(&mut self) {
         Self::remove_lock(&self.all_paths, &self.this_path);
+        #[cfg(feature = "zipper_tracking")]
+        self.all_paths.with_paths(|paths| paths.origins.remove(&self.origin_id));
     }
 }